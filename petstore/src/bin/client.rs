@@ -1,7 +1,20 @@
 use hyper_util::client::legacy::connect::HttpConnector;
-use tsp_rust::{http::Body, vendored::tower::ServiceBuilder};
-
-use petstore::{http::operations::client_raw as pets, models::synthetic::PetKind, Pet};
+use tsp_rust::{
+    http::{multipart::File, Body},
+    patch::Patch,
+    vendored::chrono::{Duration, TimeZone, Utc},
+    vendored::futures::{io::Cursor, AsyncReadExt},
+};
+
+use petstore::{
+    http::{
+        auth::{ApiKey, Bearer, Credentials},
+        client::PetStoreClient,
+    },
+    models::synthetic::{PetKind, RenameContentType, UploadPhotoBody},
+    petstore_logic::{API_KEY, TOKEN},
+    Pet, PetPatch, PetsListOptions, PhotoMetadata, Rating, Zoom,
+};
 
 type HyperClient = hyper_util::client::legacy::Client<HttpConnector, Body>;
 
@@ -11,53 +24,149 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         hyper_util::client::legacy::Client::builder(hyper_util::rt::TokioExecutor::new())
             .build_http();
 
-    let mut service = ServiceBuilder::new()
-        .map_request(|request: ::tsp_rust::vendored::http::Request<::tsp_rust::http::Body>| -> ::tsp_rust::vendored::http::Request<_> {
-            let (mut parts, body) = request.into_parts();
-
-            // canonicalize URI
-            parts.uri = ::tsp_rust::vendored::http::Uri::try_from(format!(
-                "http://localhost:8080{}",
-                parts.uri
-            ))
-            .unwrap();
-
-            ::tsp_rust::vendored::http::Request::from_parts(parts, body)
-        })
-        .service(client);
+    let mut pets = PetStoreClient::builder(client)
+        .with_base_url("http://localhost:8080".parse()?)
+        .with_credential(
+            Credentials::new()
+                .with_api_key(ApiKey::new(API_KEY))
+                .with_bearer(Bearer::new(TOKEN.to_string())),
+        )
+        .build();
 
     println!("Creating pet");
 
-    pets::create(
-        &mut service,
-        Pet {
-            name: "Fido".to_string(),
-            age: 2,
-            kind: PetKind::Dog,
-        },
-    )
+    pets.create(Pet {
+        name: "Fido".to_string(),
+        age: 2,
+        kind: PetKind::Dog,
+    })
     .await
     .expect("create failed");
 
     println!("Listing pets");
 
-    let pets = pets::list(&mut service).await.expect("list failed");
+    let list = pets.list().await.expect("list failed");
 
-    for pet in pets {
+    for pet in list {
         println!("Pet: {:?}", pet);
     }
 
-    println!("Deleting pet");
+    println!("Listing pets whose names start with 'R'");
+
+    let list = pets
+        .list_with_options(PetsListOptions {
+            limit: None,
+            name_prefix: Some("R".to_string()),
+        })
+        .await
+        .expect("list failed");
+
+    println!("Found {} pets", list.len());
+
+    println!("Patching pet");
+
+    let pet = pets
+        .patch(
+            "Fido",
+            PetPatch {
+                age: Patch::Value(3),
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("patch failed");
+
+    println!("Pet: {:?}", pet);
+
+    println!("Uploading photo");
+
+    let photo = pets
+        .upload_photo(
+            "Fido",
+            UploadPhotoBody {
+                photo: File::new(&b"not really a png"[..])
+                    .with_filename("fido.png")
+                    .with_content_type("image/png"),
+                metadata: Some(PhotoMetadata {
+                    caption: Some("Fido at the park".to_string()),
+                    rating: Some(Rating::Great),
+                    zoom: Some(Zoom::Close),
+                    ..Default::default()
+                }),
+                tags: vec!["park".to_string(), "sunny".to_string()],
+            },
+        )
+        .await
+        .expect("upload failed");
+
+    println!("Photo: {:?}", photo);
+
+    println!("Uploading avatar");
+
+    let upload = pets
+        .upload_avatar("Fido", Cursor::new(vec![0; 64 * 1024]))
+        .await
+        .expect("avatar upload failed");
+
+    println!("Upload: {:?}", upload);
 
-    pets::delete(&mut service, "Fido")
+    println!("Downloading avatar");
+
+    // The avatar is read from the response as it arrives.
+    let mut avatar = pets
+        .get_avatar("Fido")
+        .await
+        .expect("avatar download failed")
+        .avatar;
+
+    let mut data = Vec::new();
+
+    avatar
+        .read_to_end(&mut data)
+        .await
+        .expect("avatar download failed");
+
+    println!("Avatar: {} bytes", data.len());
+
+    println!("Renaming pet");
+
+    let pet = pets
+        .rename("Fido", RenameContentType::ApplicationJson, "Rex")
+        .await
+        .expect("rename failed");
+
+    println!("Renamed: {:?}", pet);
+
+    let age = pets.get_age("Rex").await.expect("get age failed");
+
+    println!("Age: {} ({:?})", age.age, age.content_type);
+
+    println!("Recording visit");
+
+    let recorded = pets
+        .record_visit(
+            "Rex",
+            Utc.with_ymd_and_hms(2024, 5, 1, 9, 30, 0).unwrap(),
+            Duration::minutes(45),
+            vec![0xfb, 0xff, 0x00],
+        )
         .await
-        .expect("delete failed");
+        .expect("record visit failed");
+
+    println!(
+        "Visit: {:?}, last modified {}",
+        recorded.visit, recorded.last_modified
+    );
+
+    println!("Deleting pet");
+
+    pets.delete("Rex").await.expect("delete failed");
 
     println!("Listing pets");
 
-    let pets = pets::list(&mut service).await.expect("list failed");
+    let list = pets.list().await.expect("list failed");
 
-    for pet in pets {
+    for pet in list {
         println!("Pet: {:?}", pet);
     }
 
@@ -1,63 +1,240 @@
 use petstore::{
-    http::{operations::client_raw as pets, router::PetStoreRouter},
-    models::synthetic::PetKind,
-    petstore_logic::PetStore,
-    Pet,
+    http::{
+        auth::{ApiKey, Bearer, Credentials},
+        client::PetStoreClient,
+        router::PetStoreRouter,
+    },
+    models::synthetic::{PetKind, RenameContentType, UpdateResponse, UploadPhotoBody},
+    petstore_logic::{Caller, PetStore, StaticCredentials, API_KEY, TOKEN},
+    Pet, PetPatch, Pets, PetsListOptions, PhotoMetadata, Rating, Zoom,
+};
+use tsp_rust::{
+    http::{
+        auth::{AuthError, AuthenticateError},
+        multipart::File,
+        Error as HttpError,
+    },
+    patch::Patch,
+    vendored::chrono::{Duration, TimeZone, Utc},
+    vendored::futures::io::Cursor,
+    vendored::http::header,
+    IntoOperationError,
 };
 
 /// The point of this example is to show how the RPC abstraction of tower::Service is transferrable. This example uses
-/// the generated HTTP client functions and generated HTTP server functions without a TCP socket. The data is still
-/// marshalled through the HTTP types, but not through the actual HTTP wire protocol. This is possible because the
-/// HTTP router implements the same service trait that the HTTP client functions are abstract over. It shows how the
-/// wire protocol simply carries the types over the wire but the data interface on either end of the socket is exactly
-/// the same.
+/// the generated HTTP client and generated HTTP server functions without a TCP socket. The data is still marshalled
+/// through the HTTP types, but not through the actual HTTP wire protocol. This is possible because the HTTP router
+/// implements the same service trait that the HTTP client is abstract over. It shows how the wire protocol simply
+/// carries the types over the wire but the data interface on either end of the socket is exactly the same.
+///
+/// The generated client also implements the `Pets` trait, so the same logic runs against the in-process `PetStore`
+/// and against the router through the client without any changes.
 ///
 /// This approach could be useful, for example, in testing. It allows testing the HTTP data marshalling without any
 /// underlying dependency on a particular HTTP protocol implementation.
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Instead of creating a hyper HTTP client, create an instance of the HTTP router. This can be passed in to the
-    // client methods in _exactly_ the same way as the hyper client, as both implement tower::Service over HTTP types.
-    let mut backend = PetStoreRouter::new(PetStore::new());
+    println!("== In process ==");
+
+    // In process, the caller is passed to the operations directly.
+    run(&mut PetStore::new(), Caller::new("example")).await?;
+
+    println!("== Through the HTTP router ==");
+
+    // Instead of creating a hyper HTTP client, wrap an instance of the HTTP router. It can be passed to the client in
+    // _exactly_ the same way as the hyper client, as both implement tower::Service over HTTP types.
+    let router = PetStoreRouter::new(
+        PetStore::new(),
+        StaticCredentials::new(API_KEY, TOKEN, Caller::new("example")),
+    );
+
+    // The router rejects requests that do not carry an accepted credential.
+    println!("Listing pets without a credential");
+
+    match PetStoreClient::builder(router.clone()).build().list().await {
+        Err(HttpError::UnexpectedStatus(status, parts)) => println!(
+            "Rejected with status {}, challenge: {:?}",
+            status,
+            parts.headers.get(header::WWW_AUTHENTICATE)
+        ),
+        other => anyhow::bail!("expected the request to be rejected, got {:?}", other),
+    }
+
+    // Deleting a pet requires a bearer token, so a client with only an API key cannot send the request at all.
+    println!("Deleting a pet with only an API key");
+
+    match PetStoreClient::builder(router.clone())
+        .with_credential(Credentials::new().with_api_key(ApiKey::new(API_KEY)))
+        .build()
+        .delete("Fido")
+        .await
+    {
+        Err(HttpError::Service(AuthenticateError::Auth(AuthError::Unsatisfied(requirements)))) => {
+            println!("Not sent, requirements: {:?}", requirements.options)
+        }
+        other => anyhow::bail!("expected the request not to be sent, got {:?}", other),
+    }
 
+    // Each request is sent with the credentials that its operation accepts.
+    let mut client = PetStoreClient::builder(router)
+        .with_credential(
+            Credentials::new()
+                .with_api_key(ApiKey::new(API_KEY))
+                .with_bearer(Bearer::new(TOKEN.to_string())),
+        )
+        .build();
+
+    // Through the router, the caller is identified by the credential that the client sends.
+    run(&mut client, ()).await
+}
+
+async fn run<P: Pets>(pets: &mut P, caller: P::Principal) -> anyhow::Result<()> {
     println!("Creating pet");
 
-    pets::create(
-        &mut backend,
+    pets.create(
+        caller.clone(),
         Pet {
             name: "Fido".to_string(),
             age: 2,
             kind: PetKind::Dog,
         },
     )
-    .await
-    .map_err(|_| anyhow::anyhow!("create failed"))?;
+    .await?;
 
     println!("Listing pets");
 
-    let pets = pets::list(&mut backend)
-        .await
-        .map_err(|_| anyhow::anyhow!("list failed"))?;
+    for pet in pets.list(caller.clone()).await? {
+        println!("Pet: {:?}", pet);
+    }
 
-    for pet in pets {
+    println!("Listing the first pet whose name starts with 'F'");
+
+    let options = PetsListOptions {
+        limit: Some(1),
+        name_prefix: Some("F".to_string()),
+    };
+
+    for pet in pets.list_with_options(caller.clone(), options).await? {
         println!("Pet: {:?}", pet);
     }
 
+    println!("Updating pet");
+
+    // The response tells whether the pet was created, replaced or left unchanged by its status code.
+    let fido = Pet {
+        name: "Fido".to_string(),
+        age: 2,
+        kind: PetKind::Dog,
+    };
+
+    match pets.update(caller.clone(), "Fido", fido).await? {
+        UpdateResponse::PetCreated(created) => println!("Created: {:?}", created.pet),
+        UpdateResponse::PetReplaced(replaced) => println!("Replaced: {:?}", replaced.pet),
+        UpdateResponse::PetUnchanged(_) => println!("Unchanged"),
+    }
+
+    println!("Patching pet");
+
+    // Only the properties that are set in the patch are changed.
+    let pet = pets
+        .patch(
+            caller.clone(),
+            "Fido",
+            PetPatch {
+                age: Patch::Value(3),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+    println!("Patched: {:?}", pet);
+
+    println!("Uploading photo");
+
+    // Through the router, the file and its metadata are sent as the parts of a multipart/form-data body.
+    let photo = pets
+        .upload_photo(
+            caller.clone(),
+            "Fido",
+            UploadPhotoBody {
+                photo: File::new(&b"not really a png"[..])
+                    .with_filename("fido.png")
+                    .with_content_type("image/png"),
+                metadata: Some(PhotoMetadata {
+                    caption: Some("Fido at the park".to_string()),
+                    rating: Some(Rating::Great),
+                    zoom: Some(Zoom::Close),
+                    ..Default::default()
+                }),
+                tags: vec!["park".to_string(), "sunny".to_string()],
+            },
+        )
+        .await?;
+
+    println!("Uploaded: {:?}", photo);
+
+    println!("Uploading avatar");
+
+    // The avatar is streamed to the operation, which reads it as it arrives.
+    let upload = pets
+        .upload_avatar(caller.clone(), "Fido", Cursor::new(vec![0; 64 * 1024]))
+        .await?;
+
+    println!("Uploaded: {:?}", upload);
+
+    println!("Renaming pet");
+
+    // The new name is sent as `text/plain`. The client accepts the age as either content type, so it is received as
+    // the one that the server prefers.
+    let pet = pets
+        .rename(caller.clone(), "Fido", RenameContentType::TextPlain, "Rex")
+        .await?;
+
+    println!("Renamed: {:?}", pet);
+
+    let age = pets.get_age(caller.clone(), "Rex").await?;
+
+    println!("Age: {} ({:?})", age.age, age.content_type);
+
+    println!("Recording visit");
+
+    // The day is sent in the path as a Unix timestamp, the length in the query as an ISO 8601 duration and the digest
+    // in a header as base64. The visit comes back with each of its fields in their own encodings.
+    let recorded = pets
+        .record_visit(
+            caller.clone(),
+            "Rex",
+            Utc.with_ymd_and_hms(2024, 5, 1, 9, 30, 0).unwrap(),
+            Duration::minutes(45),
+            vec![0xfb, 0xff, 0x00],
+        )
+        .await?;
+
+    println!(
+        "Visit: {:?}, last modified {}",
+        recorded.visit, recorded.last_modified
+    );
+
     println!("Deleting pet");
 
-    pets::delete(&mut backend, "Fido")
-        .await
-        .map_err(|_| anyhow::anyhow!("delete failed"))?;
+    pets.delete(caller.clone(), "Rex").await?;
 
     println!("Listing pets");
 
-    let pets = pets::list(&mut backend)
-        .await
-        .map_err(|_| anyhow::anyhow!("list failed"))?;
-
-    for pet in pets {
+    for pet in pets.list(caller.clone()).await? {
         println!("Pet: {:?}", pet);
     }
 
+    println!("Deleting pet again");
+
+    // The declared error is recovered the same way whether it was raised in process or sent over HTTP.
+    match pets.delete(caller.clone(), "Rex").await {
+        Ok(()) => anyhow::bail!("expected delete to fail with a not found error"),
+        Err(error) => match error.into_operation_error() {
+            Ok(error) => println!("Not found: {}", error.message),
+            Err(error) => return Err(error.into()),
+        },
+    }
+
     Ok(())
 }
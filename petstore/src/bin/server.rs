@@ -2,7 +2,10 @@ use std::net::SocketAddr;
 
 use hyper::server::conn::http1;
 use hyper_util::rt::TokioIo;
-use petstore::{http::router::PetStoreRouter, petstore_logic::PetStore};
+use petstore::{
+    http::router::PetStoreRouter,
+    petstore_logic::{Caller, PetStore, StaticCredentials, API_KEY, TOKEN},
+};
 use tokio::net::TcpListener;
 
 #[tokio::main]
@@ -12,6 +15,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let listener = TcpListener::bind(addr).await?;
 
     let petstore = PetStore::new();
+    let authenticator = StaticCredentials::new(API_KEY, TOKEN, Caller::new("example"));
 
     loop {
         let (stream, _) = listener.accept().await?;
@@ -19,12 +23,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let io = TokioIo::new(stream);
 
         let petstore = petstore.clone();
+        let authenticator = authenticator.clone();
 
         tokio::spawn(async move {
             if let Err(err) = http1::Builder::new()
                 .serve_connection(
                     io,
-                    hyper_util::service::TowerToHyperService::new(PetStoreRouter::new(petstore)),
+                    hyper_util::service::TowerToHyperService::new(PetStoreRouter::new(
+                        petstore,
+                        authenticator,
+                    )),
                 )
                 .await
             {
@@ -5,34 +5,117 @@ mod generated;
 pub use generated::*;
 
 pub mod petstore_logic {
-    use super::{Pet, Pets};
+    use super::{
+        http::auth::Authenticator,
+        models::synthetic::{
+            PetAgeContentType, RenameContentType, UpdateResponse, UploadPhotoBody,
+        },
+        Appointment, Avatar, NotFoundError, Pet, PetAge, PetCreated, PetPatch, PetReplaced, PetUnchanged,
+        Pets, PetsListOptions, Photo, Upload, Visit, VisitRecorded,
+    };
     use std::{collections::HashMap, sync::Arc};
     use tokio::sync::Mutex;
+    use tsp_rust::{
+        http::stream::ByteStream,
+        patch::MergePatch,
+        serialize::Integer,
+        vendored::{
+            chrono::{DateTime, Duration, FixedOffset, Utc},
+            futures::{io::Cursor, AsyncRead, AsyncReadExt},
+        },
+    };
+
+    /// The API key accepted by the example server.
+    pub const API_KEY: &str = "petstore-example-key";
+
+    /// The bearer token accepted by the example server.
+    pub const TOKEN: &str = "petstore-example-token";
+
+    /// The caller that pet store operations are performed on behalf of.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Caller {
+        pub name: String,
+    }
+
+    impl Caller {
+        pub fn new(name: impl Into<String>) -> Self {
+            Self { name: name.into() }
+        }
+    }
+
+    /// Accepts requests that carry a single, fixed API key or bearer token, and identifies them as a single caller.
+    #[derive(Clone)]
+    pub struct StaticCredentials {
+        key: Arc<str>,
+        token: Arc<str>,
+        caller: Caller,
+    }
+
+    impl StaticCredentials {
+        pub fn new(key: impl AsRef<str>, token: impl AsRef<str>, caller: Caller) -> Self {
+            Self {
+                key: Arc::from(key.as_ref()),
+                token: Arc::from(token.as_ref()),
+                caller,
+            }
+        }
+    }
+
+    impl Authenticator for StaticCredentials {
+        type Principal = Caller;
+
+        async fn validate_api_key(&self, key: &str) -> Option<Caller> {
+            (key == &*self.key).then(|| self.caller.clone())
+        }
+
+        async fn validate_bearer(&self, token: &str) -> Option<Caller> {
+            (token == &*self.token).then(|| self.caller.clone())
+        }
+    }
 
     #[derive(Clone, Default)]
     pub struct PetStore {
         pets: Arc<Mutex<HashMap<String, Pet>>>,
+        avatars: Arc<Mutex<HashMap<String, Vec<u8>>>>,
     }
 
     impl PetStore {
         pub fn new() -> Self {
-            Self {
-                pets: Arc::new(Mutex::new(HashMap::new())),
-            }
+            Self::default()
         }
     }
 
     impl Pets for PetStore {
-        type Error<OperationError> = std::convert::Infallible;
+        type Error<OperationError>
+            = tsp_rust::Error<OperationError>
+        where
+            OperationError: std::fmt::Debug + Send + Sync + 'static;
+
+        type Principal = Caller;
 
-        async fn list(&mut self) -> Result<Vec<Pet>, Self::Error<::core::convert::Infallible>> {
+        async fn list_with_options(
+            &mut self,
+            _caller: Caller,
+            options: PetsListOptions,
+        ) -> Result<Vec<Pet>, Self::Error<::core::convert::Infallible>> {
             let pets = self.pets.lock().await;
 
-            Ok(pets.values().cloned().collect())
+            let prefix = options.name_prefix.unwrap_or_default();
+            let limit = options
+                .limit
+                .map_or(usize::MAX, |limit| limit.max(0) as usize);
+
+            Ok(pets
+                .values()
+                .filter(|pet| pet.name.starts_with(&prefix))
+                .take(limit)
+                .cloned()
+                .collect())
         }
 
         async fn create(
             &mut self,
+            _caller: Caller,
             pet: Pet,
         ) -> Result<Pet, Self::Error<::core::convert::Infallible>> {
             let mut pets = self.pets.lock().await;
@@ -48,30 +131,214 @@ pub mod petstore_logic {
 
         async fn update(
             &mut self,
+            _caller: Caller,
             id: impl AsRef<str> + Send,
             pet: Pet,
-        ) -> Result<Pet, Self::Error<::core::convert::Infallible>> {
+        ) -> Result<UpdateResponse, Self::Error<::core::convert::Infallible>> {
+            let mut pets = self.pets.lock().await;
+
+            // Each outcome is a different response, which is sent with its own status code.
+            Ok(match pets.insert(id.as_ref().to_string(), pet.clone()) {
+                None => UpdateResponse::PetCreated(PetCreated { pet }),
+                Some(previous) if previous == pet => UpdateResponse::PetUnchanged(PetUnchanged {}),
+                Some(_) => UpdateResponse::PetReplaced(PetReplaced { pet }),
+            })
+        }
+
+        async fn patch(
+            &mut self,
+            _caller: Caller,
+            id: impl AsRef<str> + Send,
+            patch: PetPatch,
+        ) -> Result<Pet, Self::Error<NotFoundError>> {
             let mut pets = self.pets.lock().await;
 
+            let Some(pet) = pets.get_mut(id.as_ref()) else {
+                return Err(NotFoundError {
+                    message: format!("pet '{}' does not exist", id.as_ref()),
+                }
+                .into());
+            };
+
+            patch.apply_to(pet).map_err(tsp_rust::Error::service)?;
+
+            Ok(pet.clone())
+        }
+
+        async fn upload_photo(
+            &mut self,
+            _caller: Caller,
+            id: impl AsRef<str> + Send,
+            body: UploadPhotoBody,
+        ) -> Result<Photo, Self::Error<NotFoundError>> {
+            let pets = self.pets.lock().await;
+
             if !pets.contains_key(id.as_ref()) {
-                panic!("pet does not exist");
+                return Err(NotFoundError {
+                    message: format!("pet '{}' does not exist", id.as_ref()),
+                }
+                .into());
+            }
+
+            let metadata = body.metadata.unwrap_or_default();
+
+            // The example does not keep photos, it only describes what it received.
+            Ok(Photo {
+                filename: body.photo.filename,
+                content_type: body.photo.content_type,
+                size: body.photo.contents.len() as i64,
+                caption: metadata.caption,
+                rating: metadata.rating,
+                zoom: metadata.zoom,
+                tags: body.tags,
+            })
+        }
+
+        async fn upload_avatar(
+            &mut self,
+            _caller: Caller,
+            id: impl AsRef<str> + Send,
+            avatar: impl AsyncRead + Send + Sync + 'static,
+        ) -> Result<Upload, Self::Error<NotFoundError>> {
+            if !self.pets.lock().await.contains_key(id.as_ref()) {
+                return Err(NotFoundError {
+                    message: format!("pet '{}' does not exist", id.as_ref()),
+                }
+                .into());
             }
 
-            pets.insert(id.as_ref().to_string(), pet.clone());
+            let mut data = Vec::new();
+
+            std::pin::pin!(avatar)
+                .read_to_end(&mut data)
+                .await
+                .map_err(tsp_rust::Error::service)?;
+
+            let size = data.len();
+
+            self.avatars
+                .lock()
+                .await
+                .insert(id.as_ref().to_owned(), data);
+
+            Ok(Upload { size: size as i64 })
+        }
+
+        async fn get_avatar(
+            &mut self,
+            _caller: Caller,
+            id: impl AsRef<str> + Send,
+        ) -> Result<Avatar, Self::Error<NotFoundError>> {
+            let Some(data) = self.avatars.lock().await.get(id.as_ref()).cloned() else {
+                return Err(NotFoundError {
+                    message: format!("pet '{}' has no avatar", id.as_ref()),
+                }
+                .into());
+            };
+
+            Ok(Avatar {
+                avatar: ByteStream::new(Cursor::new(data)),
+            })
+        }
+
+        async fn rename(
+            &mut self,
+            _caller: Caller,
+            id: impl AsRef<str> + Send,
+            _content_type: RenameContentType,
+            name: impl AsRef<str> + Send,
+        ) -> Result<Pet, Self::Error<NotFoundError>> {
+            let mut pets = self.pets.lock().await;
+
+            let Some(mut pet) = pets.remove(id.as_ref()) else {
+                return Err(NotFoundError {
+                    message: format!("pet '{}' does not exist", id.as_ref()),
+                }
+                .into());
+            };
+
+            // Pets are stored by name, so the renamed pet moves to its new name.
+            pet.name = name.as_ref().to_string();
+            pets.insert(pet.name.clone(), pet.clone());
 
             Ok(pet)
         }
 
+        async fn get_age(
+            &mut self,
+            _caller: Caller,
+            id: impl AsRef<str> + Send,
+        ) -> Result<PetAge, Self::Error<NotFoundError>> {
+            let pets = self.pets.lock().await;
+
+            let Some(pet) = pets.get(id.as_ref()) else {
+                return Err(NotFoundError {
+                    message: format!("pet '{}' does not exist", id.as_ref()),
+                }
+                .into());
+            };
+
+            // Clients that accept either content type receive the age as plain text.
+            Ok(PetAge {
+                content_type: PetAgeContentType::TextPlain,
+                age: pet.age,
+            })
+        }
+
+        async fn record_visit(
+            &mut self,
+            _caller: Caller,
+            id: impl AsRef<str> + Send,
+            day: DateTime<Utc>,
+            length: Duration,
+            notes_digest: Vec<u8>,
+        ) -> Result<VisitRecorded, Self::Error<NotFoundError>> {
+            let pets = self.pets.lock().await;
+
+            if !pets.contains_key(id.as_ref()) {
+                return Err(NotFoundError {
+                    message: format!("pet '{}' does not exist", id.as_ref()),
+                }
+                .into());
+            }
+
+            // The example clinic is two hours ahead of UTC, and books a follow-up two weeks after the visit ends.
+            let clinic_offset = FixedOffset::east_opt(2 * 60 * 60).unwrap();
+            let follow_up = (day + length + Duration::weeks(2)).with_timezone(&clinic_offset);
+
+            Ok(VisitRecorded {
+                last_modified: day + length,
+                visit: Visit {
+                    day,
+                    length,
+                    notes_digest,
+                    // The example charges by the minute.
+                    cost: Integer::from(length.num_minutes() * 125),
+                    follow_up: Appointment {
+                        at: follow_up,
+                        date: follow_up.date_naive(),
+                        time: follow_up.time(),
+                    },
+                },
+            })
+        }
+
         async fn delete(
             &mut self,
+            _caller: Caller,
             id: impl AsRef<str> + Send,
-        ) -> Result<(), Self::Error<::core::convert::Infallible>> {
+        ) -> Result<(), Self::Error<NotFoundError>> {
             let mut pets = self.pets.lock().await;
 
             if pets.remove(id.as_ref()).is_none() {
-                panic!("pet does not exist");
+                return Err(NotFoundError {
+                    message: format!("pet '{}' does not exist", id.as_ref()),
+                }
+                .into());
             }
 
+            self.avatars.lock().await.remove(id.as_ref());
+
             Ok(())
         }
     }
@@ -10,6 +10,7 @@ import { emitModel } from "./model.js";
 import { emitUnion } from "./union.js";
 import { emitInterface } from "./interface.js";
 import { emitScalar } from "./scalar.js";
+import { emitMergePatch } from "./patch.js";
 
 export function emitDeclarations(ctx: RustContext): string[] {
   const normalLines = [];
@@ -17,12 +18,21 @@ export function emitDeclarations(ctx: RustContext): string[] {
 
   const cursor = createPathCursor("models");
 
-  while (!ctx.typeQueue.isEmpty() || ctx.synthetics.length > 0) {
+  while (
+    !ctx.typeQueue.isEmpty() ||
+    !ctx.patchQueue.isEmpty() ||
+    ctx.synthetics.length > 0
+  ) {
     while (!ctx.typeQueue.isEmpty()) {
       const type = ctx.typeQueue.take()!;
       normalLines.push(...emitDeclaration(ctx, type, cursor));
     }
 
+    while (!ctx.patchQueue.isEmpty()) {
+      const model = ctx.patchQueue.take()!;
+      normalLines.push(...emitMergePatch(ctx, model, cursor));
+    }
+
     while (ctx.synthetics.length > 0) {
       const synthetic = ctx.synthetics.shift()!;
       switch (synthetic.kind) {
@@ -0,0 +1,271 @@
+import {
+  ModelProperty,
+  Scalar,
+  Type,
+  UnionVariant,
+  getEncode,
+  getTypeName,
+  isArrayModelType,
+  isRecordModelType,
+} from "@typespec/compiler";
+import { RustContext } from "../ctx.js";
+import { reportDiagnostic } from "../lib.js";
+import { getFullyQualifiedTypeName } from "../util/name.js";
+import {
+  referenceHostPath,
+  referenceVendoredHostPath,
+} from "../util/vendored.js";
+import { getRustScalar } from "./scalar.js";
+
+/**
+ * The Rust types that the number encodings of `tsp_rust::serialize` accept as the number of seconds.
+ */
+const NUMBER_TYPES = new Set([
+  "i8",
+  "i16",
+  "i32",
+  "i64",
+  "isize",
+  "u8",
+  "u16",
+  "u32",
+  "u64",
+  "usize",
+  "f32",
+  "f64",
+]);
+
+/**
+ * The encodings that each encodable standard scalar supports, keyed by the name of the encoding. Number encodings
+ * take the Rust type of the number that they are encoded as.
+ *
+ * TypeSpec has no known encodings of plain dates and times, so theirs are named by string, like `@encode("rfc7231")`.
+ */
+const ENCODINGS: Record<string, Record<string, (number: string) => string>> = {
+  "TypeSpec.utcDateTime": {
+    rfc3339: () => referenceHostPath("serialize", "Rfc3339"),
+    rfc7231: () => referenceHostPath("serialize", "Rfc7231"),
+    unixTimestamp: (number) =>
+      `${referenceHostPath("serialize", "UnixTimestamp")}<${number}>`,
+  },
+  "TypeSpec.offsetDateTime": {
+    rfc3339: () => referenceHostPath("serialize", "Rfc3339"),
+    rfc7231: () => referenceHostPath("serialize", "Rfc7231"),
+  },
+  "TypeSpec.plainDate": {
+    rfc3339: () => referenceHostPath("serialize", "Rfc3339"),
+    rfc7231: () => referenceHostPath("serialize", "Rfc7231"),
+  },
+  "TypeSpec.plainTime": {
+    rfc3339: () => referenceHostPath("serialize", "Rfc3339"),
+    rfc7231: () => referenceHostPath("serialize", "Rfc7231"),
+  },
+  "TypeSpec.duration": {
+    ISO8601: () => referenceHostPath("serialize", "Iso8601"),
+    seconds: (number) =>
+      `${referenceHostPath("serialize", "Seconds")}<${number}>`,
+  },
+  "TypeSpec.bytes": {
+    base64: () => referenceHostPath("serialize", "Base64"),
+    base64url: () => referenceHostPath("serialize", "Base64Url"),
+  },
+};
+
+/**
+ * The properties and variants whose unsupported encoding was already reported, as their adapter is looked up wherever
+ * they are used.
+ */
+const REPORTED_TARGETS = new WeakSet<ModelProperty | UnionVariant>();
+
+/**
+ * Gets the `tsp_rust::serialize` adapter that converts the value of a property to and from its `@encode` encoding, if
+ * it has one. Properties without an encoding use the adapter that their Rust type requires as metadata, if any.
+ *
+ * The encoding of the property takes precedence over the encoding of its scalar. Unsupported encodings are reported,
+ * and the property is then serialized as its Rust type would be.
+ *
+ * The adapter is a `TextEncoding`, as it is used for HTTP metadata, so only scalar properties have one. Fields of
+ * serialized values use `getFieldAdapter`, which also adapts the values inside arrays and records.
+ */
+export function getEncodingAdapter(
+  ctx: RustContext,
+  property: ModelProperty
+): string | undefined {
+  if (property.type.kind !== "Scalar") {
+    reportUnsupportedEncoding(ctx, property);
+    return undefined;
+  }
+
+  return (
+    getScalarAdapter(ctx, property, property.type, property) ??
+    getDefaultAdapter(ctx, property.type, property)
+  );
+}
+
+/**
+ * Gets the `serde_with` adapter of a field whose value, or the values that it contains, have an encoding, if any.
+ *
+ * Arrays and records are adapted through the `serde_with` adapters of `Vec` and `HashMap`. The encoding of a property
+ * whose type is a union or a model is reported as unsupported, as its values are serialized by their own types.
+ */
+export function getFieldAdapter(
+  ctx: RustContext,
+  property: ModelProperty
+): string | undefined {
+  return getTypeAdapter(ctx, property, property.type, property);
+}
+
+/**
+ * Gets the `serde_with` adapter of a union variant whose type has an encoding, if any. Variants have no `@encode` of
+ * their own, so only the encodings of their scalars apply.
+ */
+export function getVariantAdapter(
+  ctx: RustContext,
+  variant: UnionVariant
+): string | undefined {
+  return getTypeAdapter(ctx, undefined, variant.type, variant);
+}
+
+function getTypeAdapter(
+  ctx: RustContext,
+  property: ModelProperty | undefined,
+  type: Type,
+  target: ModelProperty | UnionVariant
+): string | undefined {
+  if (type.kind === "Scalar") {
+    return getScalarAdapter(ctx, property, type, target);
+  }
+
+  if (type.kind === "Model" && type.indexer) {
+    const isArray = isArrayModelType(ctx.program, type);
+
+    if (isArray || isRecordModelType(ctx.program, type)) {
+      const valueAdapter = getTypeAdapter(
+        ctx,
+        property,
+        type.indexer.value,
+        target
+      );
+
+      if (!valueAdapter) return undefined;
+
+      return isArray
+        ? `Vec<${valueAdapter}>`
+        : `std::collections::HashMap<${referenceVendoredHostPath("serde_with", "Same")}, ${valueAdapter}>`;
+    }
+  }
+
+  if (property) reportUnsupportedEncoding(ctx, property);
+
+  return undefined;
+}
+
+/**
+ * Gets the adapter of a scalar. The encoding of the property that holds it, if any, takes precedence over its own.
+ */
+function getScalarAdapter(
+  ctx: RustContext,
+  property: ModelProperty | undefined,
+  scalar: Scalar,
+  target: ModelProperty | UnionVariant
+): string | undefined {
+  const encoding =
+    (property && getEncode(ctx.program, property)) ??
+    getEncode(ctx.program, scalar);
+
+  if (!encoding?.encoding) return undefined;
+
+  const standardScalar = getStandardScalar(ctx, scalar);
+
+  const adapter =
+    standardScalar && ENCODINGS[standardScalar]?.[encoding.encoding];
+
+  const number = getRustScalar(ctx.program, encoding.type, target).owned;
+
+  if (
+    adapter &&
+    (encoding.type.name === "string" || NUMBER_TYPES.has(number))
+  ) {
+    return adapter(number);
+  }
+
+  reportDiagnosticOnce(ctx, target, encoding.encoding, scalar);
+
+  return undefined;
+}
+
+/**
+ * Reports the `@encode` of a property whose type cannot be adapted, if it has one.
+ */
+function reportUnsupportedEncoding(ctx: RustContext, property: ModelProperty) {
+  const encoding = getEncode(ctx.program, property);
+
+  if (encoding?.encoding) {
+    reportDiagnosticOnce(ctx, property, encoding.encoding, property.type);
+  }
+}
+
+function reportDiagnosticOnce(
+  ctx: RustContext,
+  target: ModelProperty | UnionVariant,
+  encoding: string,
+  type: Type
+) {
+  if (REPORTED_TARGETS.has(target)) return;
+
+  REPORTED_TARGETS.add(target);
+
+  reportDiagnostic(ctx.program, {
+    code: "unrecognized-encoding",
+    format: {
+      encoding,
+      type:
+        type.kind === "Scalar"
+          ? getFullyQualifiedTypeName(type)
+          : getTypeName(type),
+    },
+    target,
+  });
+}
+
+/**
+ * Gets the adapter of a metadata property whose Rust type has no conversion to and from header values, if any.
+ */
+function getDefaultAdapter(
+  ctx: RustContext,
+  scalar: Scalar,
+  target: ModelProperty | UnionVariant
+): string | undefined {
+  const { owned } = getRustScalar(ctx.program, scalar, target);
+
+  // Arbitrary-precision integers are serialized by their own type, but sent as metadata in their decimal digits.
+  if (owned === referenceHostPath("serialize", "Integer")) {
+    return referenceHostPath("serialize", "BigInteger");
+  }
+
+  return undefined;
+}
+
+/**
+ * Finds the name of the encodable standard scalar that a scalar extends, if any.
+ */
+function getStandardScalar(
+  ctx: RustContext,
+  scalar: Scalar
+): string | undefined {
+  const standardScalars = Object.keys(ENCODINGS).map(
+    (name) => [ctx.program.resolveTypeReference(name)[0], name] as const
+  );
+
+  let current: Scalar | undefined = scalar;
+
+  while (current) {
+    const standardScalar = standardScalars.find(([type]) => type === current);
+
+    if (standardScalar) return standardScalar[1];
+
+    current = current.baseScalar;
+  }
+
+  return undefined;
+}
@@ -1,7 +1,12 @@
 import { Enum, getDoc } from "@typespec/compiler";
 import { RustContext } from "../ctx.js";
-import { referenceVendoredHostPath } from "../util/vendored.js";
+import {
+  referenceHostPath,
+  referenceVendoredHostPath,
+} from "../util/vendored.js";
 import { parseCase } from "../util/case.js";
+import { reportDiagnostic } from "../lib.js";
+import { emitEnumValidation } from "./validate.js";
 
 export function* emitEnum(ctx: RustContext, enum_: Enum): Iterable<string> {
   // It's a core compiler error for an enum not to have all its values be of the same type.
@@ -12,21 +17,28 @@ export function* emitEnum(ctx: RustContext, enum_: Enum): Iterable<string> {
   )}, ${referenceVendoredHostPath("serde", "Serialize")})]`;
   yield `#[serde(crate = "${referenceVendoredHostPath("serde")}")]`;
 
+  const representation = getNumericRepresentation(enum_);
+
+  if (representation) {
+    // Numeric enums are serialized as their values, through the conversions emitted below.
+    // prettier-ignore
+    yield `#[serde(try_from = "${representation}", into = "${representation}")]`;
+  }
+
   const doc = getDoc(ctx.program, enum_);
   if (doc) yield `#[doc = ${JSON.stringify(doc)}]`;
 
   yield `pub enum ${enum_.name} {`;
 
-  // TODO: only works properly with string enums. need a different solution
-  // for numeric enums and an even differenter solution for float enums.
-
   for (const [name, member] of enum_.members) {
     const nameCase = parseCase(name);
     const variantName = nameCase.pascalCase;
 
     const value = member.value ?? name;
 
-    yield `  #[serde(rename = ${JSON.stringify(value)})]`;
+    if (!representation) {
+      yield `  #[serde(rename = ${JSON.stringify(value)})]`;
+    }
     yield "  " + variantName + ",";
   }
 
@@ -45,7 +57,7 @@ export function* emitEnum(ctx: RustContext, enum_: Enum): Iterable<string> {
     const value = member.value ?? name;
 
     yield `      ${enum_.name}::${variantName} => write!(f, ${JSON.stringify(
-      value
+      String(value)
     )}),`;
   }
 
@@ -53,4 +65,125 @@ export function* emitEnum(ctx: RustContext, enum_: Enum): Iterable<string> {
   yield "  }";
   yield "}";
   yield "";
+
+  if (representation) {
+    yield* emitNumericConversions(ctx, enum_, representation);
+  }
+
+  yield* emitEnumValidation(
+    enum_.name,
+    [...enum_.members.keys()].map((name) => [parseCase(name).pascalCase, false])
+  );
+}
+
+/**
+ * Gets the Rust type that the values of an enum are converted to and from, if all of its values are numbers.
+ *
+ * Enums whose values are all integers are represented as `i64`, and any other numeric enum as `f64`.
+ */
+function getNumericRepresentation(enum_: Enum): "i64" | "f64" | undefined {
+  const values = [...enum_.members.values()].map((member) => member.value);
+
+  if (values.length === 0 || !values.every((v) => typeof v === "number")) {
+    return undefined;
+  }
+
+  return values.every((v) => Number.isInteger(v)) ? "i64" : "f64";
+}
+
+/**
+ * Emits the conversions of a numeric enum to and from its values, which its serde implementations go through.
+ *
+ * Integer values that are not safe integers are reported instead, as they can't be emitted as exact `i64` literals.
+ */
+function* emitNumericConversions(
+  ctx: RustContext,
+  enum_: Enum,
+  representation: "i64" | "f64"
+): Iterable<string> {
+  if (representation === "i64") {
+    // Integers beyond the safe range may already have been rounded by the compiler.
+    const invalid = [...enum_.members.values()].filter(
+      (member) => !Number.isSafeInteger(member.value)
+    );
+
+    for (const member of invalid) {
+      reportDiagnostic(ctx.program, {
+        code: "invalid-enum-value",
+        target: member,
+        format: {
+          value: String(member.value),
+          type: representation,
+        },
+      });
+    }
+
+    if (invalid.length > 0) return;
+  }
+
+  const members = [...enum_.members].map(
+    ([name, member]) =>
+      [
+        parseCase(name).pascalCase,
+        formatNumericLiteral(member.value as number, representation),
+      ] as const
+  );
+
+  const unknownValue = referenceHostPath("serialize", "UnknownEnumValue");
+
+  yield `impl TryFrom<${representation}> for ${enum_.name} {`;
+  yield `  type Error = ${unknownValue}<${representation}>;`;
+  yield "";
+  yield `  fn try_from(value: ${representation}) -> Result<Self, Self::Error> {`;
+
+  if (representation === "i64") {
+    yield "    match value {";
+    for (const [variantName, literal] of members) {
+      yield `      ${literal} => Ok(Self::${variantName}),`;
+    }
+    // prettier-ignore
+    yield `      _ => Err(${unknownValue} { enum_name: ${JSON.stringify(enum_.name)}, value }),`;
+    yield "    }";
+  } else {
+    // Floats can't be matched against, so each value is compared in turn.
+    for (const [variantName, literal] of members) {
+      yield `    if value == ${literal} {`;
+      yield `      return Ok(Self::${variantName});`;
+      yield "    }";
+      yield "";
+    }
+    // prettier-ignore
+    yield `    Err(${unknownValue} { enum_name: ${JSON.stringify(enum_.name)}, value })`;
+  }
+
+  yield "  }";
+  yield "}";
+  yield "";
+
+  yield `impl From<${enum_.name}> for ${representation} {`;
+  yield `  fn from(value: ${enum_.name}) -> Self {`;
+  yield "    match value {";
+  for (const [variantName, literal] of members) {
+    yield `      ${enum_.name}::${variantName} => ${literal},`;
+  }
+  yield "    }";
+  yield "  }";
+  yield "}";
+  yield "";
+}
+
+/**
+ * Formats a number as a Rust literal of the given type. Integral floats need a fractional part to be float literals.
+ */
+function formatNumericLiteral(
+  value: number,
+  representation: "i64" | "f64"
+): string {
+  const literal = String(value);
+
+  if (representation === "f64" && !/[.e]/.test(literal)) {
+    return `${literal}.0`;
+  }
+
+  return literal;
 }
@@ -1,5 +1,6 @@
 import {
   Interface,
+  ModelProperty,
   Operation,
   Type,
   UnionVariant,
@@ -17,12 +18,23 @@ import {
 import { emitDocumentation } from "./documentation.js";
 import { indent } from "../util/indent.js";
 import { bifilter } from "../util/bifilter.js";
-
-export const ERROR_FRAGMENT = [
-  "/// The error type which may be returned by this trait's operations.",
-  "type Error<OperationError>: std::error::Error + Send + Sync + 'static;",
-  "",
-];
+import { getPrincipalKind, getPrincipalType } from "../http/auth.js";
+import { emitMergePatchReference, getMergePatchModel } from "./patch.js";
+import { createOrGetModuleForNamespace } from "./namespace.js";
+
+export function* emitErrorFragment(): Iterable<string> {
+  yield "/// The error type which may be returned by this trait's operations.";
+  yield "///";
+  yield "/// Declared operation errors that can be recovered from this type are sent to clients as their modeled responses.";
+  yield "type Error<OperationError>: std::error::Error";
+  yield "  + Send";
+  yield "  + Sync";
+  yield "  + 'static";
+  yield `  + ${referenceHostPath("IntoOperationError")}<OperationError>`;
+  yield "where";
+  yield "  OperationError: std::fmt::Debug + Send + Sync + 'static;";
+  yield "";
+}
 
 export function* emitInterface(
   ctx: RustContext,
@@ -33,10 +45,11 @@ export function* emitInterface(
 
   yield* emitDocumentation(ctx, iface);
   yield `pub trait ${name} {`;
-  yield* indent(ERROR_FRAGMENT);
+  yield* indent(emitErrorFragment());
   yield* emitOperationGroup(ctx, iface.operations.values(), cursor);
   yield "}";
   yield "";
+  yield* emitOperationOptions(ctx, iface.operations.values(), cursor);
 }
 
 export function* emitOperationGroup(
@@ -44,12 +57,104 @@ export function* emitOperationGroup(
   operations: Iterable<Operation>,
   cursor: PathCursor
 ): Iterable<string> {
-  for (const op of operations) {
+  const ops = [...operations];
+
+  if (ops.some((op) => getPrincipalKind(ctx, op))) {
+    yield "  /// The caller that operations are performed on behalf of, as identified by the service's authentication.";
+    yield "  type Principal: Clone + Send + Sync + 'static;";
+    yield "";
+  }
+
+  for (const op of ops) {
     yield* emitOperation(ctx, op, cursor);
     yield "";
   }
 }
 
+/**
+ * Gets the optional parameters of an operation, which are passed to its implementations in an options struct.
+ */
+export function getOptionalParameters(op: Operation): ModelProperty[] {
+  return getAllProperties(op.parameters).filter(
+    (param) => param.optional && !isValueLiteralType(param.type)
+  );
+}
+
+/**
+ * Gets the name of the struct that holds the optional parameters of an operation, which is declared alongside the trait
+ * of the operation and named after it, as operations of different traits may share a name.
+ */
+function getOperationOptionsName(op: Operation): string {
+  const container = op.interface ?? op.namespace!;
+
+  return (
+    parseCase(container.name).pascalCase +
+    parseCase(op.name).pascalCase +
+    "Options"
+  );
+}
+
+/**
+ * Emits a reference to the options struct of an operation.
+ */
+export function emitOperationOptionsReference(
+  ctx: RustContext,
+  op: Operation,
+  cursor: PathCursor
+): string {
+  // The trait of the operations of a namespace is declared in the module of its parent namespace.
+  const namespace = op.interface
+    ? op.interface.namespace!
+    : op.namespace!.namespace!;
+
+  return cursor.pathTo(
+    createOrGetModuleForNamespace(ctx, namespace).cursor,
+    getOperationOptionsName(op)
+  );
+}
+
+/**
+ * Emits the options structs of the operations of a trait that have optional parameters.
+ */
+export function* emitOperationOptions(
+  ctx: RustContext,
+  operations: Iterable<Operation>,
+  cursor: PathCursor
+): Iterable<string> {
+  for (const op of operations) {
+    const optionalParameters = getOptionalParameters(op);
+
+    if (optionalParameters.length === 0) continue;
+
+    const opNameCase = parseCase(op.name);
+    const traitName = parseCase((op.interface ?? op.namespace!).name)
+      .pascalCase;
+
+    yield `/// The optional parameters of [\`${traitName}::${opNameCase.snakeCase}\`].`;
+    yield "#[derive(Debug, Clone, Default)]";
+    yield `pub struct ${getOperationOptionsName(op)} {`;
+
+    for (const param of optionalParameters) {
+      const paramNameCase = parseCase(param.name);
+
+      const typeReference = emitTypeReference(
+        ctx,
+        param.type,
+        param,
+        "owned",
+        cursor,
+        opNameCase.pascalCase + paramNameCase.pascalCase
+      );
+
+      yield* indent(emitDocumentation(ctx, param));
+      yield `  pub ${paramNameCase.snakeCase}: Option<${typeReference}>,`;
+    }
+
+    yield "}";
+    yield "";
+  }
+}
+
 export function* emitOperation(
   ctx: RustContext,
   op: Operation,
@@ -59,7 +164,7 @@ export function* emitOperation(
 
   const opName = opNameCase.snakeCase;
 
-  const hasOptions = getAllProperties(op.parameters).some((p) => p.optional);
+  const hasOptions = getOptionalParameters(op).length > 0;
 
   const [successResult, errorResult] = splitReturnType(
     ctx,
@@ -74,24 +179,38 @@ export function* emitOperation(
 
   const params: string[] = [];
 
-  const documentation = emitDocumentation(ctx, op);
+  const principalKind = getPrincipalKind(ctx, op);
+
+  if (principalKind) {
+    params.push(`principal: ${getPrincipalType(principalKind, "Self")}`);
+  }
+
+  // Collected up front, as operations with options repeat their documentation on both methods.
+  const documentation = [...emitDocumentation(ctx, op)];
 
   for (const param of getAllProperties(op.parameters)) {
     // If the type is a value literal, then we consider it a _setting_ and not a parameter.
     // This allows us to exclude metadata parameters (such as contentType) from the generated interface.
     if (isValueLiteralType(param.type)) continue;
 
+    // Optional parameters are passed in the options struct of the operation.
+    if (param.optional) continue;
+
     const paramNameCase = parseCase(param.name);
     const paramName = paramNameCase.snakeCase;
 
-    const outputTypeReference = emitTypeReference(
-      ctx,
-      param.type,
-      param,
-      "param",
-      cursor,
-      opNameCase.pascalCase + paramNameCase.pascalCase
-    );
+    const patchModel = getMergePatchModel(ctx, op, param);
+
+    const outputTypeReference = patchModel
+      ? emitMergePatchReference(ctx, patchModel, cursor)
+      : emitTypeReference(
+          ctx,
+          param.type,
+          param,
+          "param",
+          cursor,
+          opNameCase.pascalCase + paramNameCase.pascalCase
+        );
 
     params.push(`${paramName}: ${outputTypeReference}`);
   }
@@ -100,24 +219,22 @@ export function* emitOperation(
     params.length > 0 ? ", " + params.join(", ") : "";
 
   if (hasOptions) {
-    const optionsTypeName = opNameCase.pascalCase + "Options";
-
-    const paramNamesLine =
-      params.length > 0
-        ? ", " + params.map((p) => p.split(":", 2)[0].trim()).join(", ")
-        : "";
+    const optionsTypeReference = emitOperationOptionsReference(ctx, op, cursor);
 
-    // TODO: how to extract parameter documentation?
+    const argumentNames = [
+      ...params.map((p) => p.split(":", 2)[0].trim()),
+      "Default::default()",
+    ];
 
-    // prettier-ignore
+    // The documentation of the optional parameters is on the fields of the options struct.
     yield* indent(documentation);
     yield `  fn ${opName}(&mut self${paramsDeclarationLine}) -> ${returnType} {`;
-    yield `    Self::${opName}_with_options(self${paramNamesLine}, Default::default()).await`;
+    yield `    self.${opName}_with_options(${argumentNames.join(", ")})`;
     yield "  }";
     yield "";
     yield* indent(documentation);
     // prettier-ignore
-    yield `  fn ${opName}_with_options(&mut self${paramsDeclarationLine}, options: ${optionsTypeName}) -> ${returnType};`;
+    yield `  fn ${opName}_with_options(&mut self${paramsDeclarationLine}, options: ${optionsTypeReference}) -> ${returnType};`;
     yield "";
   } else {
     yield* indent(documentation);
@@ -216,7 +333,7 @@ export function splitReturnType(
         : {
             kind: "ordinary",
             typeReference: successTypeReference,
-            target: successVariants[0].type,
+            target: successVariants[0]?.type,
           };
 
     const errorSplit: SplitReturnType =
@@ -230,7 +347,7 @@ export function splitReturnType(
         : {
             kind: "ordinary",
             typeReference: errorTypeReference,
-            target: errorVariants[0].type,
+            target: errorVariants[0]?.type,
           };
 
     return [successSplit, errorSplit];
@@ -2,9 +2,7 @@ import {
   Model,
   ModelProperty,
   NumericLiteral,
-  Scalar,
   Type,
-  getEncode,
   getFriendlyName,
   getProjectedName,
   isArrayModelType,
@@ -17,15 +15,20 @@ import { KEYWORDS } from "./keywords.js";
 import { getFullyQualifiedTypeName } from "../util/name.js";
 import { getRecordValueName, getArrayElementName } from "../util/pluralism.js";
 import { RustTranslation } from "./scalar.js";
-import { referenceVendoredHostPath } from "../util/vendored.js";
+import {
+  referenceHostPath,
+  referenceVendoredHostPath,
+} from "../util/vendored.js";
 import {
   RustTypeSpecLiteralType,
   emitTypeReference,
   isValueLiteralType,
 } from "./reference.js";
 import { emitDocumentation } from "./documentation.js";
-import { reportDiagnostic } from "../lib.js";
 import { bifilter } from "../util/bifilter.js";
+import { ValidatedField, emitModelValidation } from "./validate.js";
+import { getFieldAdapter } from "./encoding.js";
+import { hasStreamedBody, isStreamedBodyProperty } from "../http/stream.js";
 
 export function* emitModel(
   ctx: RustContext,
@@ -61,15 +64,27 @@ export function* emitModel(
 
   const defaultMode = getDefaultMode(ctx, fields);
 
-  const requiresAs = fields.some(
-    (f) => f.type.kind === "Scalar" && getEncode(ctx.program, f)
-  );
+  // Fields with an encoding are serialized through its adapter.
+  const encodingAdapters = new Map<ModelProperty, string>();
+
+  for (const field of fields) {
+    if (isStreamedBodyProperty(ctx, field)) continue;
+
+    const adapter = getFieldAdapter(ctx, field);
+
+    if (adapter) encodingAdapters.set(field, adapter);
+  }
+
+  const requiresAs = encodingAdapters.size > 0;
 
   yield* emitDocumentation(ctx, model);
 
   const structName = model.name === "" ? altName! : modelNameCase.pascalCase;
 
-  const derives: string[] = ["Debug", "Clone", "PartialEq"];
+  // A streamed body is read once, so it can be neither cloned nor compared.
+  const derives: string[] = hasStreamedBody(ctx, model)
+    ? ["Debug"]
+    : ["Debug", "Clone", "PartialEq"];
 
   if (defaultMode === "derive") {
     derives.push("Default");
@@ -89,6 +104,8 @@ export function* emitModel(
 
   yield `pub struct ${structName} {`;
 
+  const validatedFields: ValidatedField[] = [];
+
   for (const field of fields) {
     const nameCase = parseCase(field.name);
     const basicName = nameCase.snakeCase;
@@ -128,19 +145,35 @@ export function* emitModel(
       yield `  #[serde(skip_serializing_if = "Option::is_none")]`;
     }
 
-    if (field.type.kind === "Scalar") {
-      yield* indent(
-        getEncodingAsLines(ctx, field as ModelProperty & { type: Scalar })
-      );
+    // A streamed body is sent and read as the body itself, never as part of a serialized value, and is not validated
+    // since it is not in memory.
+    const isStreamed = isStreamedBodyProperty(ctx, field);
+
+    if (isStreamed) {
+      yield `  #[serde(skip)]`;
+    }
+
+    const adapter = encodingAdapters.get(field);
+
+    if (adapter) {
+      const as = field.optional ? `Option<${adapter}>` : adapter;
+
+      yield `  #[serde_as(as = ${JSON.stringify(as)})]`;
     }
 
     yield `  pub ${name}: ${fullType},`;
     yield "";
+
+    if (!isStreamed) {
+      validatedFields.push({ property: field, fieldName: basicName, jsonName });
+    }
   }
 
   yield "}";
   yield "";
 
+  yield* emitModelValidation(ctx, structName, validatedFields, cursor);
+
   // Add an impl for const settings if necessary.
   if (settings.length > 0) {
     yield `impl ${structName} {`;
@@ -164,7 +197,12 @@ export function* emitModel(
 
 export function isWellKnownModel(ctx: RustContext, type: Model): boolean {
   const fullName = getFullyQualifiedTypeName(type);
-  return fullName === "TypeSpec.Record" || fullName === "TypeSpec.Array";
+  return (
+    fullName === "TypeSpec.Record" ||
+    fullName === "TypeSpec.Array" ||
+    fullName === "TypeSpec.Http.HttpPart" ||
+    fullName === "TypeSpec.Http.File"
+  );
 }
 
 export function emitWellKnownModel(
@@ -205,6 +243,21 @@ export function emitWellKnownModel(
         return `&[${innerReference}]`;
       }
     }
+    case "HttpPart": {
+      // Parts of multipart bodies are fields of their body struct, typed by the part contents.
+      return emitTypeReference(
+        ctx,
+        type.templateMapper?.args[0]!,
+        type,
+        disposition,
+        cursor,
+        preferredAlternativeName
+      );
+    }
+    case "File": {
+      const file = referenceHostPath("http", "multipart", "File");
+      return disposition === "owned" ? file : `&${file}`;
+    }
     default:
       throw new Error(`UNREACHABLE: ${type.name}`);
   }
@@ -214,7 +267,7 @@ export function emitWellKnownModel(
  * Returns the names of all fields that need to be boxed in order to avoid an
  * infinite struct.
  */
-function getModelRecursion(ctx: RustContext, model: Model): string[] {
+export function getModelRecursion(ctx: RustContext, model: Model): string[] {
   const queue = [...model.properties].map(([name, type]) => ({
     type: type,
     field: name,
@@ -316,41 +369,6 @@ function getDefaultMode(
   return undefined;
 }
 
-function getEncodingAsLines(
-  ctx: RustContext,
-  p: ModelProperty & { type: Scalar }
-): string[] {
-  const encoding = getEncode(ctx.program, p);
-
-  if (!encoding) return [];
-
-  if (
-    p.type === ctx.program.resolveTypeReference("TypeSpec.utcDateTime")[0] &&
-    encoding.type === ctx.program.resolveTypeReference("TypeSpec.int32")[0] &&
-    encoding.encoding === "unixTimestamp"
-  ) {
-    // We represent this in JSON as a number
-    debugger;
-    return [
-      // prettier-ignore
-      `#[serde_as(as = "${referenceVendoredHostPath("serde_with", "TimestampSeconds")}<i64>")]`,
-    ];
-  }
-
-  // TODO: many more required encodings
-
-  reportDiagnostic(ctx.program, {
-    code: "unrecognized-encoding",
-    format: {
-      encoding: encoding.encoding,
-      type: getFullyQualifiedTypeName(p.type),
-    },
-    target: p,
-  });
-
-  return [];
-}
-
 export function getRustLiteralTypeAndValue(
   type: RustTypeSpecLiteralType
 ): [string, string] {
@@ -7,7 +7,11 @@ import {
 } from "../ctx.js";
 import { indent } from "../util/indent.js";
 import { isIterable, join } from "../util/iter.js";
-import { ERROR_FRAGMENT, emitOperationGroup } from "./interface.js";
+import {
+  emitErrorFragment,
+  emitOperationGroup,
+  emitOperationOptions,
+} from "./interface.js";
 import { parseCase } from "../util/case.js";
 import { OnceQueue } from "../util/onceQueue.js";
 import { emitDocumentation } from "./documentation.js";
@@ -47,9 +51,11 @@ export function visitAllTypes(ctx: RustContext, namespace: Namespace) {
       // prettier-ignore
       `/// A trait representing the operations defined in the '${getNamespaceFullName(namespace)}' namespace.`,
       `pub trait ${parseCase(namespace.name).pascalCase} {`,
-      ...indent(ERROR_FRAGMENT),
+      ...indent(emitErrorFragment()),
       ...emitOperationGroup(ctx, operations.values(), parentModule.cursor),
       "}",
+      "",
+      ...emitOperationOptions(ctx, operations.values(), parentModule.cursor),
     ]);
   }
 }
@@ -0,0 +1,233 @@
+import {
+  Model,
+  ModelProperty,
+  Operation,
+  getEffectiveModelType,
+  getProjectedName,
+} from "@typespec/compiler";
+import { getHttpOperation } from "@typespec/http";
+import { PathCursor, RustContext } from "../ctx.js";
+import { parseCase } from "../util/case.js";
+import { UnimplementedError } from "../util/error.js";
+import { indent } from "../util/indent.js";
+import {
+  referenceHostPath,
+  referenceVendoredHostPath,
+} from "../util/vendored.js";
+import { emitDocumentation } from "./documentation.js";
+import { KEYWORDS } from "./keywords.js";
+import { getModelRecursion, isWellKnownModel } from "./model.js";
+import { emitTypeReference, isValueLiteralType } from "./reference.js";
+import { emitPatchValidation } from "./validate.js";
+import { getFieldAdapter } from "./encoding.js";
+
+const MERGE_PATCH_CONTENT_TYPE = "application/merge-patch+json";
+
+/**
+ * Gets the model that an operation parameter patches, if the parameter is the body of an HTTP operation that is sent
+ * as a JSON merge patch.
+ */
+export function getMergePatchModel(
+  ctx: RustContext,
+  operation: Operation,
+  param: ModelProperty
+): Model | undefined {
+  const [httpOperation] = getHttpOperation(ctx.program, operation);
+
+  const body = httpOperation.parameters.body;
+
+  if (
+    !body ||
+    body.parameter !== param ||
+    !body.contentTypes.includes(MERGE_PATCH_CONTENT_TYPE)
+  ) {
+    return undefined;
+  }
+
+  if (!isPatchableModel(ctx, body.type)) {
+    throw new UnimplementedError(
+      "merge patch bodies that are not named models"
+    );
+  }
+
+  return getEffectiveModelType(ctx.program, body.type);
+}
+
+/**
+ * Emits a reference to the `<Model>Patch` type of a model, declaring it alongside the model if necessary.
+ */
+export function emitMergePatchReference(
+  ctx: RustContext,
+  model: Model,
+  cursor: PathCursor
+): string {
+  const modelReference = emitTypeReference(
+    ctx,
+    model,
+    model,
+    "owned",
+    cursor,
+    "**unreachable**"
+  );
+
+  ctx.patchQueue.add(model);
+
+  return modelReference + "Patch";
+}
+
+/**
+ * Emits the `<Model>Patch` type of a model, which holds a `tsp_rust::patch::Patch` for each of its properties.
+ */
+export function* emitMergePatch(
+  ctx: RustContext,
+  model: Model,
+  cursor: PathCursor
+): Iterable<string> {
+  const modelName = emitTypeReference(
+    ctx,
+    model,
+    model,
+    "owned",
+    cursor,
+    "**unreachable**"
+  );
+  const structName = modelName + "Patch";
+
+  const patch = referenceHostPath("patch", "Patch");
+  const mergePatch = referenceHostPath("patch", "MergePatch");
+  const patchError = referenceHostPath("patch", "PatchError");
+
+  const recursionPoints = getModelRecursion(ctx, model);
+
+  const fields = [...model.properties.values()]
+    .filter((property) => !isValueLiteralType(property.type))
+    .map((property) => {
+      const nameCase = parseCase(property.name);
+      const fieldName = nameCase.snakeCase;
+
+      const merged = isPatchableModel(ctx, property.type);
+
+      const typeReference = merged
+        ? emitMergePatchReference(
+            ctx,
+            getEffectiveModelType(ctx.program, property.type as Model),
+            cursor
+          )
+        : emitTypeReference(
+            ctx,
+            property.type,
+            property,
+            "owned",
+            cursor,
+            modelName + nameCase.pascalCase
+          );
+
+      return {
+        property,
+        fieldName,
+        rustName: KEYWORDS.has(fieldName) ? `r#${fieldName}` : fieldName,
+        jsonName:
+          getProjectedName(ctx.program, property, "json") ?? property.name,
+        merged,
+        // Patched models are serialized by their own patch types, which adapt their fields.
+        adapter: merged ? undefined : getFieldAdapter(ctx, property),
+        typeReference: recursionPoints.includes(property.name)
+          ? `Box<${typeReference}>`
+          : typeReference,
+      };
+    });
+
+  yield `/// A JSON merge patch for [\`${modelName}\`].`;
+  yield "///";
+  yield "/// Properties that are models are patched recursively. All other properties are replaced as a whole.";
+  if (fields.some((field) => field.adapter)) {
+    // prettier-ignore
+    yield `#[${referenceVendoredHostPath("serde_with", "serde_as")}(crate = "${referenceVendoredHostPath("serde_with")}")]`;
+  }
+  // prettier-ignore
+  yield `#[derive(Debug, Clone, PartialEq, Default, ${referenceVendoredHostPath("serde", "Deserialize")}, ${referenceVendoredHostPath("serde", "Serialize")})]`;
+  yield `#[serde(crate = "${referenceVendoredHostPath("serde")}")]`;
+  yield `pub struct ${structName} {`;
+  for (const field of fields) {
+    const { property, fieldName, rustName, jsonName, adapter, typeReference } =
+      field;
+
+    yield* indent(emitDocumentation(ctx, property));
+    if (fieldName !== jsonName) {
+      yield `  #[serde(rename = ${JSON.stringify(jsonName)})]`;
+    }
+    // prettier-ignore
+    yield `  #[serde(default, skip_serializing_if = "${patch}::is_absent")]`;
+    if (adapter) {
+      yield `  #[serde_as(as = ${JSON.stringify(`${patch}<${adapter}>`)})]`;
+    }
+    yield `  pub ${rustName}: ${patch}<${typeReference}>,`;
+    yield "";
+  }
+  yield "}";
+  yield "";
+
+  const applyLines = fields.map(({ property, rustName, jsonName, merged }) => {
+    const name = JSON.stringify(jsonName);
+
+    if (merged) {
+      return property.optional
+        ? `self.${rustName}.merge_optional(&mut target.${rustName})?;`
+        : `self.${rustName}.merge(&mut target.${rustName}, ${name})?;`;
+    } else {
+      return property.optional
+        ? `self.${rustName}.apply_optional(&mut target.${rustName});`
+        : `self.${rustName}.apply(&mut target.${rustName}, ${name})?;`;
+    }
+  });
+
+  const intoLines = fields.map(({ property, rustName, jsonName, merged }) => {
+    const name = JSON.stringify(jsonName);
+
+    if (merged) {
+      return property.optional
+        ? // prettier-ignore
+          `${rustName}: self.${rustName}.into_optional().map(${mergePatch}::into_target).transpose()?,`
+        : // prettier-ignore
+          `${rustName}: ${mergePatch}::into_target(self.${rustName}.into_required(${name})?)?,`;
+    } else {
+      return property.optional
+        ? `${rustName}: self.${rustName}.into_optional(),`
+        : `${rustName}: self.${rustName}.into_required(${name})?,`;
+    }
+  });
+
+  yield `impl ${mergePatch} for ${structName} {`;
+  yield `  type Target = ${modelName};`;
+  yield "";
+  // prettier-ignore
+  yield `  fn apply_to(self, ${fields.length > 0 ? "target" : "_"}: &mut ${modelName}) -> Result<(), ${patchError}> {`;
+  yield* indent(indent(applyLines));
+  if (applyLines.length > 0) yield "";
+  yield "    Ok(())";
+  yield "  }";
+  yield "";
+  yield `  fn into_target(self) -> Result<${modelName}, ${patchError}> {`;
+  yield `    Ok(${modelName} {`;
+  yield* indent(indent(indent(intoLines)));
+  yield "    })";
+  yield "  }";
+  yield "}";
+  yield "";
+
+  yield* emitPatchValidation(ctx, structName, fields, cursor);
+}
+
+/**
+ * Whether a type is a named model, whose values are patched recursively rather than replaced.
+ */
+function isPatchableModel(
+  ctx: RustContext,
+  type: ModelProperty["type"]
+): type is Model {
+  return (
+    type.kind === "Model" &&
+    !isWellKnownModel(ctx, type) &&
+    getEffectiveModelType(ctx.program, type).name !== ""
+  );
+}
@@ -14,16 +14,40 @@ import {
   Union,
   isArrayModelType,
 } from "@typespec/compiler";
+import { isHeader, isPathParam, isQueryParam } from "@typespec/http";
 import { PathCursor, RustContext } from "../ctx.js";
 import { RustTranslation, getRustScalar } from "./scalar.js";
-import { referenceVendoredHostPath } from "../util/vendored.js";
+import {
+  referenceHostPath,
+  referenceVendoredHostPath,
+} from "../util/vendored.js";
 import { emitWellKnownModel, isWellKnownModel } from "./model.js";
 import { parseCase } from "../util/case.js";
 import { createOrGetModuleForNamespace } from "./namespace.js";
 import { getArrayElementName } from "../util/pluralism.js";
+import { isStreamedBodyProperty } from "../http/stream.js";
 
 export type NamespacedType = Extract<Type, { namespace?: Namespace }>;
 
+function isMetadataProperty(
+  ctx: RustContext,
+  position: DiagnosticTarget | typeof NoTarget
+): boolean {
+  if (
+    typeof position !== "object" ||
+    !("kind" in position) ||
+    position.kind !== "ModelProperty"
+  ) {
+    return false;
+  }
+
+  return (
+    isHeader(ctx.program, position) ||
+    isQueryParam(ctx.program, position) ||
+    isPathParam(ctx.program, position)
+  );
+}
+
 export function emitTypeReference(
   ctx: RustContext,
   type: Type,
@@ -33,8 +57,24 @@ export function emitTypeReference(
   preferredAlternativeName: string
 ): string {
   switch (type.kind) {
-    case "Scalar":
-      return getRustScalar(ctx.program, type, position)[disposition];
+    case "Scalar": {
+      const translation = getRustScalar(ctx.program, type, position);
+
+      // Only bodies are streamed, so bytes in the metadata of a request, like an encoded header, are passed as values.
+      if (
+        disposition === "param" &&
+        translation.owned === "Vec<u8>" &&
+        isMetadataProperty(ctx, position)
+      ) {
+        return translation.owned;
+      }
+
+      if (disposition === "owned" && isStreamedBodyProperty(ctx, position)) {
+        return referenceHostPath("http", "stream", "ByteStream");
+      }
+
+      return translation[disposition];
+    }
     case "Model": {
       if (isArrayModelType(ctx.program, type)) {
         const argTypeReference = emitTypeReference(
@@ -154,7 +194,16 @@ export function emitTypeReference(
     case "Enum": {
       ctx.typeQueue.add(type);
 
-      return cursor.resolveAbsolutePathOld("models", type.name);
+      if (!type.namespace) {
+        throw new Error(
+          "UNREACHABLE: no parent namespace of named enum in emitTypeReference"
+        );
+      }
+
+      // Enums are declared in the module of their namespace, like models.
+      const parentModule = createOrGetModuleForNamespace(ctx, type.namespace);
+
+      return cursor.pathTo(parentModule.cursor, type.name);
     }
     case "Number":
     case "String":
@@ -5,7 +5,10 @@ import {
   Scalar,
   formatDiagnostic,
 } from "@typespec/compiler";
-import { referenceVendoredHostPath } from "../util/vendored.js";
+import {
+  referenceHostPath,
+  referenceVendoredHostPath,
+} from "../util/vendored.js";
 import { reportDiagnostic } from "../lib.js";
 import { getFullyQualifiedTypeName } from "../util/name.js";
 import { RustContext } from "../ctx.js";
@@ -79,10 +82,11 @@ function createScalarsMap(program: Program): Map<Scalar, RustTranslation> {
   const entries = [
     [
       program.resolveTypeReference("TypeSpec.bytes"),
+      // Parameters own their reader, as binary bodies are streamed through a `Body` that cannot borrow it.
       obp(
         "Vec<u8>",
         "&[u8]",
-        `impl ${referenceVendoredHostPath("futures", "AsyncRead")} + Send`,
+        `impl ${referenceVendoredHostPath("futures", "AsyncRead")} + Send + Sync + 'static`,
         "{}"
       ),
     ],
@@ -104,8 +108,11 @@ function createScalarsMap(program: Program): Map<Scalar, RustTranslation> {
     [program.resolveTypeReference("TypeSpec.float32"), copy("f32")],
     [program.resolveTypeReference("TypeSpec.float64"), copy("f64")],
 
-    // TODO: bigint
-    [program.resolveTypeReference("TypeSpec.integer"), copy("isize")],
+    // Sized integers extend `integer`, so only unsized ones are arbitrary-precision.
+    [
+      program.resolveTypeReference("TypeSpec.integer"),
+      copy(referenceHostPath("serialize", "Integer")),
+    ],
     [
       program.resolveTypeReference("TypeSpec.plainDate"),
       copy(referenceVendoredHostPath("chrono", "naive", "NaiveDate")),
@@ -123,7 +130,16 @@ function createScalarsMap(program: Program): Map<Scalar, RustTranslation> {
         )
       ),
     ],
-    // [program.resolveTypeReference("TypeSpec.offsetDateTime"), copy(vendoredModulePath("chrono", "DateTime"))],
+    // The offset of the date and time is kept as it was received, rather than being converted to UTC.
+    [
+      program.resolveTypeReference("TypeSpec.offsetDateTime"),
+      copy(
+        referenceVendoredHostPath(
+          "chrono",
+          `DateTime<${referenceVendoredHostPath("chrono", "FixedOffset")}>`
+        )
+      ),
+    ],
     [
       program.resolveTypeReference("TypeSpec.duration"),
       copy(referenceVendoredHostPath("chrono", "Duration")),
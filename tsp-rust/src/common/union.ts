@@ -14,6 +14,9 @@ import {
 } from "../util/vendored.js";
 import { parseCase } from "../util/case.js";
 import { emitTypeReference } from "./reference.js";
+import { emitEnumValidation } from "./validate.js";
+import { hasStreamedBody } from "../http/stream.js";
+import { getVariantAdapter } from "./encoding.js";
 
 export function* emitUnion(
   ctx: RustContext,
@@ -40,7 +43,34 @@ export function* emitUnion(
     ? undefined
     : getDiscriminator(ctx.program, union)?.propertyName;
 
-  yield `#[derive(Debug, Clone, PartialEq, ${referenceVendoredHostPath(
+  const variants = isPartialSynthetic
+    ? union.variants.map((v) => [v.name, v] as const)
+    : [...union.variants.entries()];
+
+  if (isExtensibleStringUnion(ctx, variants)) {
+    const doc = isPartialSynthetic ? undefined : getDoc(ctx.program, union);
+
+    yield* emitExtensibleStringUnion(ctx, name, variants, doc);
+    return;
+  }
+
+  // Responses with a streamed body can be neither cloned nor compared.
+  const derives = variants.some(
+    ([, v]) => v.type.kind === "Model" && hasStreamedBody(ctx, v.type)
+  )
+    ? "Debug"
+    : "Debug, Clone, PartialEq";
+
+  // Variants whose values have an encoding are serialized through its adapter.
+  const variantAdapters = new Map(
+    variants.map(([, v]) => [v, getVariantAdapter(ctx, v)] as const)
+  );
+
+  if ([...variantAdapters.values()].some((adapter) => adapter)) {
+    // prettier-ignore
+    yield `#[${referenceVendoredHostPath("serde_with", "serde_as")}(crate = "${referenceVendoredHostPath("serde_with")}")]`;
+  }
+  yield `#[derive(${derives}, ${referenceVendoredHostPath(
     "serde",
     "Deserialize"
   )}, ${referenceVendoredHostPath("serde", "Serialize")})]`;
@@ -57,9 +87,7 @@ export function* emitUnion(
 
   yield `pub enum ${name} {`;
 
-  const variants = isPartialSynthetic
-    ? union.variants.map((v) => [v.name, v] as const)
-    : union.variants.entries();
+  const validatedVariants: [string, boolean][] = [];
 
   let idx = 0;
   for (const [key, variant] of variants) {
@@ -74,9 +102,11 @@ export function* emitUnion(
       // prettier-ignore
       yield `  #[serde(with = "${referenceHostPath("serialize", "null_variant")}")]`;
       yield "  Null,";
+      validatedVariants.push(["Null", false]);
     } else if (variant.type.kind == "String") {
       yield `  #[serde(rename = ${JSON.stringify(variant.type.value)})]`;
       yield `  ${variantNameCase.pascalCase},`;
+      validatedVariants.push([variantNameCase.pascalCase, false]);
     } else {
       const variantTypeReference = emitTypeReference(
         ctx,
@@ -87,12 +117,154 @@ export function* emitUnion(
         name + variantNameCase.pascalCase
       );
 
-      yield `  ${variantNameCase.pascalCase}(${variantTypeReference}),`;
+      const adapter = variantAdapters.get(variant);
+
+      const as = adapter
+        ? `#[serde_as(as = ${JSON.stringify(adapter)})] `
+        : "";
+
+      yield `  ${variantNameCase.pascalCase}(${as}${variantTypeReference}),`;
+      validatedVariants.push([variantNameCase.pascalCase, true]);
     }
   }
 
   yield "}";
   yield "";
+
+  yield* emitEnumValidation(name, validatedVariants);
+}
+
+type UnionVariantEntry = readonly [string | symbol, UnionVariant];
+
+/**
+ * Determines whether a union is an extensible enum: a union of string literals that also accepts any other string,
+ * like `"dog" | "cat" | string`.
+ */
+function isExtensibleStringUnion(
+  ctx: RustContext,
+  variants: UnionVariantEntry[]
+): boolean {
+  const stringType = ctx.program.checker.getStdType("string");
+
+  const openVariants = variants.filter(([, v]) => v.type === stringType);
+
+  return (
+    openVariants.length === 1 &&
+    variants.length > 1 &&
+    variants.every(
+      ([, v]) => v.type === stringType || v.type.kind === "String"
+    )
+  );
+}
+
+/**
+ * Emits an extensible enum, which has a variant for each string literal of its union and an `Unknown` variant for any
+ * other string. Unknown values are kept as they were received, so they are sent back unchanged when re-serialized.
+ *
+ * If a string literal is itself named `Unknown`, the catch-all variant is named `Other` instead.
+ */
+function* emitExtensibleStringUnion(
+  ctx: RustContext,
+  name: string,
+  variants: UnionVariantEntry[],
+  doc: string | undefined
+): Iterable<string> {
+  const knownVariants: [string, string][] = [];
+
+  let idx = 0;
+  for (const [key, variant] of variants) {
+    idx += 1;
+
+    if (variant.type.kind !== "String") continue;
+
+    const variantName =
+      typeof key === "string" ? key : getVariantName(ctx, variant, idx);
+
+    knownVariants.push([
+      parseCase(variantName).pascalCase,
+      variant.type.value,
+    ]);
+  }
+
+  const catchAll = getCatchAllVariantName(
+    new Set(knownVariants.map(([variantName]) => variantName))
+  );
+
+  yield `#[derive(Debug, Clone, PartialEq, ${referenceVendoredHostPath(
+    "serde",
+    "Deserialize"
+  )}, ${referenceVendoredHostPath("serde", "Serialize")})]`;
+  yield `#[serde(crate = "${referenceVendoredHostPath("serde")}")]`;
+  yield `#[serde(from = "String", into = "String")]`;
+
+  if (doc) yield `#[doc = ${JSON.stringify(doc)}]`;
+
+  yield `pub enum ${name} {`;
+
+  for (const [variantName] of knownVariants) {
+    yield `  ${variantName},`;
+  }
+
+  yield "  /// A value that is not one of the known variants, kept as it was received.";
+  yield `  ${catchAll}(String),`;
+  yield "}";
+  yield "";
+
+  yield `impl From<String> for ${name} {`;
+  yield "  fn from(value: String) -> Self {";
+  yield "    match value.as_str() {";
+  for (const [variantName, value] of knownVariants) {
+    yield `      ${JSON.stringify(value)} => Self::${variantName},`;
+  }
+  yield `      _ => Self::${catchAll}(value),`;
+  yield "    }";
+  yield "  }";
+  yield "}";
+  yield "";
+
+  yield `impl From<${name}> for String {`;
+  yield `  fn from(value: ${name}) -> Self {`;
+  yield "    match value {";
+  for (const [variantName, value] of knownVariants) {
+    // prettier-ignore
+    yield `      ${name}::${variantName} => String::from(${JSON.stringify(value)}),`;
+  }
+  yield `      ${name}::${catchAll}(value) => value,`;
+  yield "    }";
+  yield "  }";
+  yield "}";
+  yield "";
+
+  // Unknown values are accepted as they are, so there is nothing to validate.
+  yield* emitEnumValidation(name, []);
+}
+
+/**
+ * Gets the name of the variant of an extensible enum that holds unknown values, which must not collide with the names
+ * of its known variants.
+ */
+function getCatchAllVariantName(knownVariantNames: Set<string>): string {
+  for (const candidate of ["Unknown", "Other"]) {
+    if (!knownVariantNames.has(candidate)) return candidate;
+  }
+
+  let idx = 2;
+  while (knownVariantNames.has(`Other${idx}`)) idx += 1;
+
+  return `Other${idx}`;
+}
+
+/**
+ * Gets the name of the Rust enum variant that a union variant is emitted as, given its 1-based position in the union.
+ */
+export function getRustVariantName(
+  ctx: RustContext,
+  variant: UnionVariant,
+  idx: number
+): string {
+  if (isNullType(variant.type)) return "Null";
+
+  return parseCase(getVariantName(ctx, variant, idx)).pascalCase;
 }
 
 function getVariantName(
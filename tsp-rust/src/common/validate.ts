@@ -0,0 +1,424 @@
+import {
+  ModelProperty,
+  Program,
+  Type,
+  getFormat,
+  getMaxItems,
+  getMaxLength,
+  getMaxValue,
+  getMaxValueExclusive,
+  getMinItems,
+  getMinLength,
+  getMinValue,
+  getMinValueExclusive,
+  getPattern,
+} from "@typespec/compiler";
+import { PathCursor, RustContext } from "../ctx.js";
+import { indent } from "../util/indent.js";
+import {
+  referenceHostPath,
+  referenceVendoredHostPath,
+} from "../util/vendored.js";
+import { reportDiagnostic } from "../lib.js";
+import { KEYWORDS } from "./keywords.js";
+import { emitTypeReference } from "./reference.js";
+
+/**
+ * A property of a generated struct that is checked by its Validate impl.
+ */
+export interface ValidatedField {
+  property: ModelProperty;
+  /** The name of the Rust field. */
+  fieldName: string;
+  /** The name of the property in the serialized JSON representation. */
+  jsonName: string;
+}
+
+const INTEGER_TYPES = new Set([
+  "i8",
+  "i16",
+  "i32",
+  "i64",
+  "i128",
+  "isize",
+  "u8",
+  "u16",
+  "u32",
+  "u64",
+  "u128",
+  "usize",
+]);
+
+const FLOAT_TYPES = new Set(["f32", "f64"]);
+
+/**
+ * Emits an impl of `tsp_rust::validate::Validate` that checks the constraint decorators on each field of a struct,
+ * then validates the field values themselves.
+ */
+export function* emitModelValidation(
+  ctx: RustContext,
+  structName: string,
+  fields: ValidatedField[],
+  cursor: PathCursor
+): Iterable<string> {
+  const body = [...emitFieldValidations(ctx, fields, cursor)];
+
+  yield* emitValidateImpl(structName, body);
+}
+
+/**
+ * Emits an impl of `tsp_rust::validate::Validate` for the merge patch of a model, which checks the values that the patch
+ * sets like those of the model, and rejects the removal of required properties.
+ */
+export function* emitPatchValidation(
+  ctx: RustContext,
+  structName: string,
+  fields: ValidatedField[],
+  cursor: PathCursor
+): Iterable<string> {
+  const body = [...emitFieldValidations(ctx, fields, cursor, true)];
+
+  yield* emitValidateImpl(structName, body);
+}
+
+function* emitValidateImpl(
+  structName: string,
+  body: string[]
+): Iterable<string> {
+  const validatorParam = body.length > 0 ? "validator" : "_";
+
+  yield `impl ${referenceHostPath("validate", "Validate")} for ${structName} {`;
+  // prettier-ignore
+  yield `  fn validate_into(&self, ${validatorParam}: &mut ${referenceHostPath("validate", "Validator")}) {`;
+  yield* indent(indent(body));
+  yield "  }";
+  yield "}";
+  yield "";
+}
+
+/**
+ * Emits an impl of `tsp_rust::validate::Validate` for a generated enum that validates the value of each variant.
+ *
+ * @param variants - the names of the enum variants, paired with whether the variant carries a value.
+ */
+export function* emitEnumValidation(
+  name: string,
+  variants: [string, boolean][]
+): Iterable<string> {
+  const hasValues = variants.some(([, hasValue]) => hasValue);
+
+  yield `impl ${referenceHostPath("validate", "Validate")} for ${name} {`;
+  // prettier-ignore
+  yield `  fn validate_into(&self, ${hasValues ? "validator" : "_"}: &mut ${referenceHostPath("validate", "Validator")}) {`;
+
+  if (hasValues) {
+    yield "    match self {";
+    for (const [variant, hasValue] of variants) {
+      if (hasValue) {
+        // prettier-ignore
+        yield `      Self::${variant}(value) => ${referenceHostPath("validate", "Validate", "validate_into")}(value, validator),`;
+      } else {
+        yield `      Self::${variant} => {}`;
+      }
+    }
+    yield "    }";
+  }
+
+  yield "  }";
+  yield "}";
+  yield "";
+}
+
+function* emitFieldValidations(
+  ctx: RustContext,
+  fields: ValidatedField[],
+  cursor: PathCursor,
+  patch: boolean = false
+): Iterable<string> {
+  for (const field of fields) {
+    const fieldName = KEYWORDS.has(field.fieldName)
+      ? `r#${field.fieldName}`
+      : field.fieldName;
+
+    if (patch && !field.property.optional) {
+      yield `if self.${fieldName}.is_null() {`;
+      // prettier-ignore
+      yield `  validator.field(${JSON.stringify(field.jsonName)}, |validator| validator.violation("required property cannot be removed"));`;
+      yield "}";
+    }
+
+    const checks = [...emitConstraintChecks(ctx, field.property, cursor)];
+
+    // Scalars carry no constraints of their own beyond those declared on the property.
+    const recurse =
+      field.property.type.kind !== "Scalar" &&
+      field.property.type.kind !== "Intrinsic";
+
+    if (checks.length === 0 && !recurse) continue;
+
+    if (recurse) {
+      // prettier-ignore
+      checks.push(`${referenceHostPath("validate", "Validate", "validate_into")}(value, validator);`);
+    }
+
+    if (patch) {
+      yield `if let ${referenceHostPath("patch", "Patch", "Value")}(value) = &self.${fieldName} {`;
+    } else if (field.property.optional) {
+      yield `if let Some(value) = &self.${fieldName} {`;
+    } else {
+      yield `{`;
+      yield `  let value = &self.${fieldName};`;
+    }
+
+    yield `  validator.field(${JSON.stringify(field.jsonName)}, |validator| {`;
+    yield* indent(indent(checks));
+    yield "  });";
+    yield "}";
+  }
+}
+
+function* emitConstraintChecks(
+  ctx: RustContext,
+  property: ModelProperty,
+  cursor: PathCursor
+): Iterable<string> {
+  const { program } = ctx;
+
+  const minLength = getConstraint(program, property, getMinLength);
+  const maxLength = getConstraint(program, property, getMaxLength);
+
+  if (minLength !== undefined)
+    yield `validator.min_length(value, ${minLength});`;
+  if (maxLength !== undefined)
+    yield `validator.max_length(value, ${maxLength});`;
+
+  const numericChecks = [
+    ["min_value", getConstraint(program, property, getMinValue)],
+    ["max_value", getConstraint(program, property, getMaxValue)],
+    [
+      "min_value_exclusive",
+      getConstraint(program, property, getMinValueExclusive),
+    ],
+    [
+      "max_value_exclusive",
+      getConstraint(program, property, getMaxValueExclusive),
+    ],
+  ] as const;
+
+  for (const [check, bound] of numericChecks) {
+    if (bound === undefined) continue;
+
+    const typeReference = emitTypeReference(
+      ctx,
+      property.type,
+      property,
+      "owned",
+      cursor,
+      "**unreachable**"
+    );
+
+    const literal = getNumericBound(typeReference, bound);
+
+    if (literal === undefined) {
+      reportDiagnostic(program, {
+        code: "invalid-constraint",
+        target: property,
+        format: {
+          value: String(bound),
+          type: typeReference,
+        },
+      });
+      continue;
+    }
+
+    yield `validator.${check}(value, ${literal});`;
+  }
+
+  if (isSafeInt(program, property.type)) {
+    yield `validator.safe_integer(value);`;
+  }
+
+  const minItems = getConstraint(program, property, getMinItems);
+  const maxItems = getConstraint(program, property, getMaxItems);
+
+  if (minItems !== undefined)
+    yield `validator.min_items(value.len(), ${minItems});`;
+  if (maxItems !== undefined)
+    yield `validator.max_items(value.len(), ${maxItems});`;
+
+  const pattern = getConstraint(program, property, getPattern);
+
+  if (pattern !== undefined) {
+    if (isSupportedPattern(pattern)) {
+      const patternType = referenceHostPath("validate", "Pattern");
+      // prettier-ignore
+      yield `static PATTERN: ${patternType} = ${patternType}::new(${JSON.stringify(pattern)});`;
+      yield `validator.pattern(value, &PATTERN);`;
+    } else {
+      reportDiagnostic(program, {
+        code: "unsupported-pattern",
+        target: property,
+        format: { pattern },
+      });
+    }
+  }
+
+  const format = getConstraint(program, property, getFormat);
+
+  if (format !== undefined)
+    yield `validator.format(value, ${JSON.stringify(format)});`;
+}
+
+/**
+ * Gets a constraint from a property, or from the scalar type of the property if the property does not declare it.
+ */
+function getConstraint<T>(
+  program: Program,
+  property: ModelProperty,
+  get: (program: Program, target: Type) => T | undefined
+): T | undefined {
+  const fromProperty = get(program, property);
+
+  if (fromProperty !== undefined) return fromProperty;
+
+  let scalar = property.type.kind === "Scalar" ? property.type : undefined;
+
+  while (scalar !== undefined) {
+    const fromScalar = get(program, scalar);
+
+    if (fromScalar !== undefined) return fromScalar;
+
+    scalar = scalar.baseScalar;
+  }
+
+  return undefined;
+}
+
+/**
+ * Determines whether a type is a `safeint`, which is emitted as an `i64` that is also checked to be exact as a double.
+ */
+function isSafeInt(program: Program, type: Type): boolean {
+  const [safeint] = program.resolveTypeReference("TypeSpec.safeint");
+
+  let scalar = type.kind === "Scalar" ? type : undefined;
+
+  while (scalar !== undefined) {
+    if (scalar === safeint) return true;
+
+    scalar = scalar.baseScalar;
+  }
+
+  return false;
+}
+
+const INTEGER_RANGES: Record<string, [bigint, bigint]> = {
+  i8: [-(2n ** 7n), 2n ** 7n - 1n],
+  i16: [-(2n ** 15n), 2n ** 15n - 1n],
+  i32: [-(2n ** 31n), 2n ** 31n - 1n],
+  i64: [-(2n ** 63n), 2n ** 63n - 1n],
+  i128: [-(2n ** 127n), 2n ** 127n - 1n],
+  isize: [-(2n ** 63n), 2n ** 63n - 1n],
+  u8: [0n, 2n ** 8n - 1n],
+  u16: [0n, 2n ** 16n - 1n],
+  u32: [0n, 2n ** 32n - 1n],
+  u64: [0n, 2n ** 64n - 1n],
+  u128: [0n, 2n ** 128n - 1n],
+  usize: [0n, 2n ** 64n - 1n],
+};
+
+/**
+ * Gets the Rust literal of a numeric bound in the type of the value it constrains, or `undefined` if the type cannot
+ * represent the bound.
+ */
+function getNumericBound(
+  typeReference: string,
+  bound: number
+): string | undefined {
+  if (!Number.isFinite(bound)) return undefined;
+
+  if (INTEGER_TYPES.has(typeReference)) {
+    if (!Number.isInteger(bound)) return undefined;
+
+    const [min, max] = INTEGER_RANGES[typeReference];
+
+    return min <= BigInt(bound) && BigInt(bound) <= max
+      ? BigInt(bound).toString()
+      : undefined;
+  } else if (FLOAT_TYPES.has(typeReference)) {
+    return Number.isInteger(bound) ? `${bound}.0` : String(bound);
+  } else if (typeReference === referenceHostPath("serialize", "Integer")) {
+    if (!Number.isInteger(bound)) return undefined;
+
+    const [min, max] = INTEGER_RANGES.i128;
+
+    if (BigInt(bound) < min || max < BigInt(bound)) return undefined;
+
+    const repr = Number.isSafeInteger(bound) ? "i64" : "i128";
+
+    return `${typeReference}::from(${BigInt(bound)}${repr})`;
+  } else if (
+    typeReference === referenceVendoredHostPath("bigdecimal", "BigDecimal")
+  ) {
+    const [digits, scale] = getDecimalDigits(bound);
+    const [min, max] = INTEGER_RANGES.i128;
+
+    if (digits < min || max < digits) return undefined;
+
+    // prettier-ignore
+    return `${typeReference}::new(${referenceVendoredHostPath("num_bigint", "BigInt")}::from(${digits}i128), ${scale})`;
+  } else {
+    return undefined;
+  }
+}
+
+/**
+ * Splits the shortest decimal representation of a number into its digits and the number of them that follow the
+ * decimal point, such that the number is `digits * 10^-scale`.
+ */
+function getDecimalDigits(value: number): [bigint, number] {
+  const [mantissa, exponent = "0"] = String(value).split("e");
+  const [whole, fraction = ""] = mantissa.split(".");
+
+  const digits = BigInt(whole + fraction);
+  const scale = fraction.length - Number(exponent);
+
+  return scale >= 0 ? [digits, scale] : [digits * 10n ** BigInt(-scale), 0];
+}
+
+/**
+ * Determines whether the Rust `regex` crate accepts a pattern.
+ *
+ * Patterns are ECMAScript regular expressions, so they are checked by compiling them in JavaScript, then by rejecting
+ * the constructs that `regex` does not support: lookaround and backreferences.
+ */
+function isSupportedPattern(pattern: string): boolean {
+  try {
+    new RegExp(pattern);
+  } catch {
+    return false;
+  }
+
+  let inClass = false;
+
+  for (let i = 0; i < pattern.length; i++) {
+    const c = pattern[i];
+
+    if (c === "\\") {
+      const next = pattern[i + 1];
+
+      if (!inClass && (/[1-9]/.test(next) || next === "k")) return false;
+
+      i++;
+    } else if (inClass) {
+      if (c === "]") inClass = false;
+    } else if (c === "[") {
+      inClass = true;
+    } else if (c === "(" && pattern[i + 1] === "?") {
+      const group = pattern.slice(i + 2, i + 4);
+
+      if (/^(=|!|<=|<!)/.test(group)) return false;
+    }
+  }
+
+  return true;
+}
@@ -9,16 +9,13 @@ import {
   Union,
   UnionVariant,
 } from "@typespec/compiler";
-import {
-  HttpOperationParameter,
-  HttpService,
-  ServiceAuthentication,
-} from "@typespec/http";
+import { HttpService } from "@typespec/http";
 import { parseCase } from "./util/case.js";
 import { OnceQueue } from "./util/onceQueue.js";
 import { emitDeclaration } from "./common/declaration.js";
 import { createOrGetModuleForNamespace } from "./common/namespace.js";
 import { emitUnion } from "./common/union.js";
+import { emitMergePatch } from "./common/patch.js";
 import { UnimplementedError } from "./util/error.js";
 
 export type RustVisibility =
@@ -36,17 +33,16 @@ export interface RustContext {
   httpService: HttpService;
   serviceTitle?: string;
   serviceVersion?: string;
-  authenticationInfo?: ServiceAuthentication;
 
   contextTypeName: string;
   errorTypeName: string;
 
   typeQueue: OnceQueue<RustDeclarationType>;
+  /** Models whose merge patch types are declared alongside them. */
+  patchQueue: OnceQueue<Model>;
   synthetics: Synthetic[];
   syntheticNames: Map<RustDeclarationType, string>;
 
-  options: OptionsStructDefinition[];
-
   // new stuff
   rootModule: Module;
   baseNamespace: Namespace;
@@ -69,14 +65,13 @@ export interface PartialUnionSynthetic {
   variants: UnionVariant[];
 }
 
-export interface OptionsStructDefinition {
-  name: string;
-  fields: HttpOperationParameter[];
-}
-
 export function completePendingDeclarations(ctx: RustContext): void {
   // Add all pending declarations to the module tree.
-  while (!ctx.typeQueue.isEmpty() || ctx.synthetics.length > 0) {
+  while (
+    !ctx.typeQueue.isEmpty() ||
+    !ctx.patchQueue.isEmpty() ||
+    ctx.synthetics.length > 0
+  ) {
     while (!ctx.typeQueue.isEmpty()) {
       const type = ctx.typeQueue.take()!;
 
@@ -90,6 +85,14 @@ export function completePendingDeclarations(ctx: RustContext): void {
       module.declarations.push([...emitDeclaration(ctx, type, module.cursor)]);
     }
 
+    while (!ctx.patchQueue.isEmpty()) {
+      const model = ctx.patchQueue.take()!;
+
+      const module = createOrGetModuleForNamespace(ctx, model.namespace!);
+
+      module.declarations.push([...emitMergePatch(ctx, model, module.cursor)]);
+    }
+
     while (ctx.synthetics.length > 0) {
       const synthetic = ctx.synthetics.shift()!;
 
@@ -1,104 +1,682 @@
-import { ApiKeyAuth } from "@typespec/http";
+import {
+  Namespace,
+  Operation,
+  navigateTypesInNamespace,
+} from "@typespec/compiler";
+import {
+  HttpAuth,
+  getAuthentication,
+  getAuthenticationForOperation,
+} from "@typespec/http";
 import { parseCase } from "../util/case.js";
-import { RustContext } from "../ctx.js";
-import { referenceVendoredHostPath } from "../util/vendored.js";
-
-// TODO: whole module is dead code
-
-export interface AuthCode {
-  fields: string[];
-  config_lines: string[];
-  declarations: string[];
-  headers: [string, string][];
-}
-
-//TODO: why isn't this exposed in @typespec/http?
-type ApiKeyLocation = "header" | "query" | "cookie";
-
-export function generateAuth(ctx: RustContext): AuthCode {
-  const auth = ctx.authenticationInfo;
-  if (!auth) {
-    return {
-      config_lines: [],
-      fields: [],
-      declarations: [],
-      headers: [],
-    };
-  }
+import { Module, RustContext } from "../ctx.js";
+import {
+  referenceHostPath,
+  referenceVendoredHostPath,
+} from "../util/vendored.js";
+import { HttpContext } from "./feature.js";
+import { reportDiagnostic } from "../lib.js";
 
-  if (auth.options.length > 1) {
-    // TODO
-    console.error("Only one authentication option is supported");
-  }
+/**
+ * The authentication declared by a service and its operations with `@useAuth`.
+ */
+export interface AuthInfo {
+  /** Every scheme that can authenticate an operation of the service. */
+  schemes: AuthScheme[];
+  /** The declared schemes that cannot be generated. */
+  unsupported: HttpAuth[];
+}
+
+export type SupportedHttpAuth = Extract<
+  HttpAuth,
+  { type: "apiKey" | "http" | "oauth2" }
+>;
+
+type OAuth2Auth = Extract<HttpAuth, { type: "oauth2" }>;
+type ClientCredentialsFlow = Extract<
+  OAuth2Auth["flows"][number],
+  { type: "clientCredentials" }
+>;
+
+export interface AuthScheme {
+  scheme: SupportedHttpAuth;
+  /** The name of the generated credential type and of the variant of the generated `Scheme` enum. */
+  name: string;
+  /** The name of the method of the generated `Authenticator` trait that verifies the credential. */
+  validatorName: string;
+  /** The name of the method of the generated `Credentials` struct that sets the credential. */
+  setterName: string;
+}
+
+/**
+ * The authentication requirements of an operation.
+ */
+export interface OperationAuth {
+  /** The alternative sets of schemes that can authenticate the operation. The schemes of a set are used together. */
+  options: AuthScheme[][];
+  /** Whether the operation can also be called without any credential. */
+  optional: boolean;
+}
+
+/**
+ * How the caller of an operation is identified, which determines the principal passed to its trait method.
+ *
+ * - "required": the operation receives the principal that authenticated the request.
+ * - "optional": the operation receives the principal if the request carried a valid credential.
+ */
+export type PrincipalKind = "required" | "optional";
+
+const authInfoCache = new WeakMap<Namespace, AuthInfo | undefined>();
+
+export function getAuthInfo(ctx: RustContext): AuthInfo | undefined {
+  const namespace = ctx.service.type;
+
+  if (authInfoCache.has(namespace)) return authInfoCache.get(namespace);
+
+  const authentications = [getAuthentication(ctx.program, namespace)];
+
+  navigateTypesInNamespace(namespace, {
+    operation(operation) {
+      authentications.push(
+        getAuthenticationForOperation(ctx.program, operation)
+      );
+    },
+  });
+
+  const schemes = new Map<string, AuthScheme>();
+  const unsupported = new Map<string, HttpAuth>();
+  let declared = false;
 
-  const option = auth.options[0];
+  for (const authentication of authentications) {
+    if (!authentication) continue;
 
-  if (option.schemes.length > 1) {
-    // TODO
-    throw new Error("Only one authentication scheme is supported");
+    declared = true;
+
+    for (const option of authentication.options) {
+      for (const scheme of option.schemes) {
+        if (scheme.type === "noAuth") continue;
+        if (schemes.has(scheme.id) || unsupported.has(scheme.id)) continue;
+
+        if (!isSupportedScheme(scheme)) {
+          unsupported.set(scheme.id, scheme);
+          continue;
+        }
+
+        const nameCase = parseCase(getSchemeBaseName(scheme));
+
+        schemes.set(scheme.id, {
+          scheme,
+          name: nameCase.pascalCase,
+          validatorName: "validate_" + nameCase.snakeCase,
+          setterName: "with_" + nameCase.snakeCase,
+        });
+      }
+    }
   }
 
-  const scheme = option.schemes[0];
+  const auth = declared
+    ? {
+        schemes: [...schemes.values()],
+        unsupported: [...unsupported.values()],
+      }
+    : undefined;
+
+  authInfoCache.set(namespace, auth);
+
+  return auth;
+}
 
+function isSupportedScheme(scheme: HttpAuth): scheme is SupportedHttpAuth {
   switch (scheme.type) {
-    case "apiKey": {
-      return generateApiKeyAuth(scheme);
-    }
+    case "apiKey":
+      return true;
     case "http":
-    //   return generateHttpAuth(ctx, option);
-    case "oauth2":
-    //   return generateOAuth2Auth(ctx, option);
+      return scheme.scheme === "Bearer" || scheme.scheme === "Basic";
+    case "oauth2": {
+      // Clients can only obtain tokens with the client credentials flow, whose token URL is parsed with
+      // `http::Uri::from_static`.
+      const flow = getClientCredentialsFlow(scheme);
+
+      return flow !== undefined && isValidTokenUrl(flow.tokenUrl);
+    }
     default:
-      throw new Error(
-        `Unsupported authentication scheme: ${(scheme as any).type}`
-      );
+      return false;
   }
 }
 
-function generateApiKeyAuth(
-  scheme: ApiKeyAuth<ApiKeyLocation, string>
-): AuthCode {
-  const id =
-    scheme.id.endsWith("Auth") || scheme.id.endsWith("auth")
-      ? scheme.id.slice(0, -4)
-      : scheme.id;
-  const idCase = parseCase(id);
-  const structName = idCase.pascalCase;
+function getClientCredentialsFlow(
+  scheme: OAuth2Auth
+): ClientCredentialsFlow | undefined {
+  return scheme.flows.find(
+    (flow): flow is ClientCredentialsFlow => flow.type === "clientCredentials"
+  );
+}
+
+/**
+ * Determines whether a token URL is an absolute http or https URL that `http::Uri` accepts as it is.
+ */
+function isValidTokenUrl(url: string): boolean {
+  // URL parsers escape characters that are not allowed in a URI, but `http::Uri` rejects them.
+  if (!/^[A-Za-z0-9\-._~:/?#[\]@!$&'()*+,;=%]+$/.test(url)) return false;
+
+  try {
+    const { protocol } = new URL(url);
 
-  if (scheme.in !== "header") {
-    throw new Error("TODO: only header auth is supported");
+    return protocol === "http:" || protocol === "https:";
+  } catch {
+    return false;
   }
+}
 
-  const headerValuePath = referenceVendoredHostPath(
-    "reqwest",
-    "header",
-    "HeaderValue"
-  );
+export function reportUnsupportedAuthSchemes(
+  ctx: HttpContext,
+  auth: AuthInfo
+) {
+  for (const scheme of auth.unsupported) {
+    const flow =
+      scheme.type === "oauth2" ? getClientCredentialsFlow(scheme) : undefined;
 
-  return {
-    config_lines: [`pub api_key: auth::${structName},`],
-    fields: ["api_key"],
-    headers: [[scheme.name, `ctx.api_key.as_header_value()`]],
-    declarations: [
-      "pub mod auth {",
-      `  pub struct ${structName} {`,
-      `    key: String,`,
-      "  }",
-      "  ",
-      `  impl ${structName} {`,
-      "    pub fn new(key: impl AsRef<str>) -> Self {",
-      "      Self {",
-      // prettier-ignore
-      `        key: key.as_ref().to_string()`,
-      "      }",
-      "    }",
-      "",
-      `    pub(super) fn as_header_value(&self) -> ${headerValuePath} {`,
-      `      ${headerValuePath}::from_str(&self.key).unwrap()`,
-      "    }",
-      "  }",
-      "}",
-      "",
-    ],
+    if (flow) {
+      reportDiagnostic(ctx.program, {
+        code: "invalid-token-url",
+        format: { scheme: scheme.id, url: flow.tokenUrl },
+        target: ctx.httpService.namespace,
+      });
+      continue;
+    }
+
+    reportDiagnostic(ctx.program, {
+      code: "unsupported-auth-scheme",
+      format: { scheme: scheme.id, type: scheme.type },
+      target: ctx.httpService.namespace,
+    });
+  }
+}
+
+/**
+ * Gets the authentication requirements of an operation, including those it inherits from its interface and namespace,
+ * or undefined if the operation is not authenticated.
+ */
+export function getOperationAuth(
+  ctx: RustContext,
+  operation: Operation
+): OperationAuth | undefined {
+  const auth = getAuthInfo(ctx);
+
+  if (!auth) return undefined;
+
+  const authentication = getAuthenticationForOperation(ctx.program, operation);
+
+  if (!authentication) return undefined;
+
+  const schemes = new Map(auth.schemes.map((s) => [s.scheme.id, s]));
+  const options: AuthScheme[][] = [];
+  let optional = false;
+
+  for (const option of authentication.options) {
+    const declared = option.schemes.filter((s) => s.type !== "noAuth");
+
+    if (declared.length === 0) {
+      optional = true;
+      continue;
+    }
+
+    const supported = declared.flatMap((s) => schemes.get(s.id) ?? []);
+
+    // An option that includes a scheme that cannot be generated can never be satisfied.
+    if (supported.length === declared.length) {
+      options.push(supported);
+    }
+  }
+
+  // Operations that can only be called anonymously have no caller to identify.
+  if (options.length === 0 && optional) return undefined;
+
+  return { options, optional };
+}
+
+/**
+ * Returns how the caller of an operation is identified, or undefined if the operation is not authenticated.
+ */
+export function getPrincipalKind(
+  ctx: RustContext,
+  operation: Operation
+): PrincipalKind | undefined {
+  const operationAuth = getOperationAuth(ctx, operation);
+
+  if (!operationAuth) return undefined;
+
+  return operationAuth.optional ? "optional" : "required";
+}
+
+/**
+ * Gets the type of the principal parameter of an operation, given the type that declares its `Principal`.
+ */
+export function getPrincipalType(kind: PrincipalKind, owner: string): string {
+  return kind === "required"
+    ? `${owner}::Principal`
+    : `Option<${owner}::Principal>`;
+}
+
+/**
+ * Gets the `WWW-Authenticate` challenges that the router sends when it rejects a request to an operation.
+ */
+export function getAuthChallenges(
+  ctx: HttpContext,
+  operation: Operation
+): string[] {
+  const options = getOperationAuth(ctx, operation)?.options ?? [];
+
+  const challenges = options.flat().flatMap(({ scheme }) => {
+    switch (scheme.type) {
+      case "http":
+        return [scheme.scheme];
+      case "oauth2":
+        return ["Bearer"];
+      default:
+        return [];
+    }
+  });
+
+  return [...new Set(challenges)];
+}
+
+/**
+ * Formats the authentication requirements that the generated client attaches to the requests of an operation.
+ */
+export function formatAuthRequirements(
+  ctx: HttpContext,
+  operation: Operation
+): string {
+  const operationAuth = getOperationAuth(ctx, operation);
+
+  const options = (operationAuth?.options ?? [])
+    .map(
+      (schemes) =>
+        `&[${schemes.map(({ scheme }) => JSON.stringify(scheme.id)).join(", ")}]`
+    )
+    .join(", ");
+
+  const optional = operationAuth?.optional ?? true;
+
+  // prettier-ignore
+  return `${referenceHostPath("http", "auth", "AuthRequirements")} { options: &[${options}], optional: ${optional} }`;
+}
+
+/**
+ * Formats the options that the router passes to the generated `authenticate` function for an operation.
+ */
+export function formatAuthOptions(
+  ctx: HttpContext,
+  operation: Operation,
+  schemeEnum: string
+): string {
+  const options = (getOperationAuth(ctx, operation)?.options ?? [])
+    .map(
+      (schemes) =>
+        `&[${schemes.map(({ name }) => `${schemeEnum}::${name}`).join(", ")}]`
+    )
+    .join(", ");
+
+  return `&[${options}]`;
+}
+
+export function emitAuth(ctx: HttpContext, auth: AuthInfo) {
+  const authModule: Module = {
+    name: "auth",
+    cursor: ctx.httpModule.cursor.enter("auth"),
+
+    declarations: [],
+
+    visibility: "pub",
+    inline: false,
   };
+
+  ctx.httpModule.declarations.push(authModule);
+
+  for (const scheme of auth.schemes) {
+    authModule.declarations.push([...emitCredential(scheme)]);
+  }
+
+  authModule.declarations.push([...emitSchemeEnum(ctx, auth)]);
+  authModule.declarations.push([...emitCredentialSet(ctx, auth)]);
+  authModule.declarations.push([...emitAuthenticator(ctx, auth)]);
+}
+
+function* emitCredential({ scheme, name }: AuthScheme): Iterable<string> {
+  const runtimeAuth = referenceHostPath("http", "auth");
+
+  yield* emitSchemeDocumentation(scheme);
+
+  if (scheme.type === "http") {
+    // HTTP schemes have no parameters, so the runtime credentials are used as they are.
+    if (scheme.scheme === "Bearer") {
+      yield `pub type ${name}<P> = ${runtimeAuth}::BearerToken<P>;`;
+    } else {
+      yield `pub type ${name} = ${runtimeAuth}::BasicCredentials;`;
+    }
+    yield "";
+    return;
+  }
+
+  if (scheme.type === "oauth2") {
+    yield* emitClientCredentials(scheme, name);
+    return;
+  }
+
+  yield "#[derive(Debug, Clone)]";
+  yield `pub struct ${name}(${runtimeAuth}::ApiKey);`;
+  yield "";
+  yield `impl ${name} {`;
+  yield "  pub fn new(key: impl Into<String>) -> Self {";
+  // prettier-ignore
+  yield `    Self(${runtimeAuth}::ApiKey::new(${getApiKeyLocation(scheme)}, ${JSON.stringify(scheme.name)}, key))`;
+  yield "  }";
+  yield "}";
+  yield "";
+  yield `impl ${runtimeAuth}::Credential for ${name} {`;
+  yield "  fn apply<'a>(";
+  yield "    &'a self,";
+  // prettier-ignore
+  yield `    request: &'a mut ${referenceVendoredHostPath("http", "Request")}<${referenceHostPath("http", "Body")}>,`;
+  // prettier-ignore
+  yield `  ) -> ${referenceVendoredHostPath("futures", "future", "BoxFuture")}<'a, Result<(), ${runtimeAuth}::AuthError>> {`;
+  yield `    ${runtimeAuth}::Credential::apply(&self.0, request)`;
+  yield "  }";
+  yield "}";
+  yield "";
+}
+
+function* emitClientCredentials(
+  scheme: OAuth2Auth,
+  name: string
+): Iterable<string> {
+  const oauth2 = referenceHostPath("http", "auth", "oauth2");
+  const flow = getClientCredentialsFlow(scheme)!;
+
+  // The scopes required by the scheme are requested if it declares any. Otherwise, all scopes of the flow are.
+  const scopes =
+    scheme.defaultScopes.length > 0
+      ? scheme.defaultScopes
+      : flow.scopes.map((scope) => scope.value);
+
+  yield "#[derive(Debug, Clone, Copy)]";
+  yield `pub struct ${name};`;
+  yield "";
+  yield `impl ${name} {`;
+  yield "  /// The URL that access tokens are requested from.";
+  yield `  pub const TOKEN_URL: &'static str = ${JSON.stringify(flow.tokenUrl)};`;
+  yield "";
+  yield "  /// The scopes requested for access tokens.";
+  // prettier-ignore
+  yield `  pub const SCOPES: &'static [&'static str] = &[${scopes.map((scope) => JSON.stringify(scope)).join(", ")}];`;
+  yield "";
+  yield "  /// Configures the client credentials flow to request the declared scopes from the declared token URL.";
+  yield "  pub fn credentials(";
+  yield "    client_id: impl Into<String>,";
+  yield "    client_secret: impl Into<String>,";
+  yield `  ) -> ${oauth2}::ClientCredentials {`;
+  yield `    ${oauth2}::ClientCredentials::new(`;
+  // Schemes whose token URL would not parse are unsupported, so `from_static` cannot panic.
+  // prettier-ignore
+  yield `      ${referenceVendoredHostPath("http", "Uri")}::from_static(Self::TOKEN_URL),`;
+  yield "      client_id,";
+  yield "      client_secret,";
+  yield "    )";
+  yield "    .with_scopes(Self::SCOPES.iter().copied())";
+  yield "  }";
+  yield "";
+  yield "  /// Creates a layer that authenticates requests with access tokens obtained with the client credentials flow.";
+  yield "  pub fn client_credentials(";
+  yield "    client_id: impl Into<String>,";
+  yield "    client_secret: impl Into<String>,";
+  yield `  ) -> ${oauth2}::ClientCredentialsLayer {`;
+  // prettier-ignore
+  yield `    ${oauth2}::ClientCredentialsLayer::new(Self::credentials(client_id, client_secret))`;
+  yield "  }";
+  yield "}";
+  yield "";
+}
+
+function* emitSchemeEnum(
+  ctx: HttpContext,
+  auth: AuthInfo
+): Iterable<string> {
+  const serviceName = parseCase(ctx.httpService.namespace.name).pascalCase;
+
+  yield `/// The authentication schemes declared by the ${serviceName} service.`;
+  yield "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]";
+  yield "pub enum Scheme {";
+  for (const { name } of auth.schemes) {
+    yield `  ${name},`;
+  }
+  yield "}";
+  yield "";
+  yield "impl Scheme {";
+  yield "  /// Gets the name of the scheme in the TypeSpec definition of the service.";
+  yield "  pub fn id(self) -> &'static str {";
+  yield "    match self {";
+  for (const { scheme, name } of auth.schemes) {
+    yield `      Self::${name} => ${JSON.stringify(scheme.id)},`;
+  }
+  yield "    }";
+  yield "  }";
+  yield "}";
+  yield "";
+}
+
+function* emitCredentialSet(
+  ctx: HttpContext,
+  auth: AuthInfo
+): Iterable<string> {
+  const serviceName = parseCase(ctx.httpService.namespace.name).pascalCase;
+  const runtimeAuth = referenceHostPath("http", "auth");
+
+  yield `/// Credentials for the schemes declared by the ${serviceName} service.`;
+  yield "///";
+  yield "/// Each request is sent with the credentials that satisfy the authentication requirements of its operation.";
+  yield "#[derive(Debug, Clone, Default)]";
+  yield `pub struct Credentials(${runtimeAuth}::CredentialSet);`;
+  yield "";
+  yield "impl Credentials {";
+  yield "  pub fn new() -> Self {";
+  yield "    Self::default()";
+  yield "  }";
+  for (const { scheme, name, setterName } of auth.schemes) {
+    yield "";
+    yield `  /// Sets the credential used for the \`${scheme.id}\` scheme.`;
+    if (isTokenScheme(scheme)) {
+      const credentialType =
+        scheme.type === "oauth2"
+          ? `${runtimeAuth}::BearerToken<P>`
+          : `${name}<P>`;
+
+      // prettier-ignore
+      yield `  pub fn ${setterName}<P: ${runtimeAuth}::TokenProvider>(self, credential: ${credentialType}) -> Self {`;
+    } else {
+      yield `  pub fn ${setterName}(self, credential: ${name}) -> Self {`;
+    }
+    yield `    Self(self.0.with(${JSON.stringify(scheme.id)}, credential))`;
+    yield "  }";
+  }
+  yield "}";
+  yield "";
+  yield `impl ${runtimeAuth}::Credential for Credentials {`;
+  yield "  fn apply<'a>(";
+  yield "    &'a self,";
+  // prettier-ignore
+  yield `    request: &'a mut ${referenceVendoredHostPath("http", "Request")}<${referenceHostPath("http", "Body")}>,`;
+  // prettier-ignore
+  yield `  ) -> ${referenceVendoredHostPath("futures", "future", "BoxFuture")}<'a, Result<(), ${runtimeAuth}::AuthError>> {`;
+  yield `    ${runtimeAuth}::Credential::apply(&self.0, request)`;
+  yield "  }";
+  yield "}";
+  yield "";
+}
+
+function* emitAuthenticator(
+  ctx: HttpContext,
+  auth: AuthInfo
+): Iterable<string> {
+  const serviceName = parseCase(ctx.httpService.namespace.name).pascalCase;
+  const runtimeAuth = referenceHostPath("http", "auth");
+  const output =
+    "impl std::future::Future<Output = Option<Self::Principal>> + Send";
+
+  yield `/// Verifies the credentials sent with requests to the ${serviceName} service.`;
+  yield "pub trait Authenticator: Clone + Send + Sync + 'static {";
+  yield "  /// The caller that a valid credential identifies, which is passed to the operations it authenticates.";
+  yield "  type Principal: Clone + Send + Sync + 'static;";
+  for (const { scheme, validatorName } of auth.schemes) {
+    yield "";
+    switch (scheme.type) {
+      case "apiKey":
+        yield `  /// Checks an API key sent in the \`${scheme.name}\` ${scheme.in}.`;
+        yield `  fn ${validatorName}(&self, key: &str) -> ${output};`;
+        break;
+      case "http":
+        if (scheme.scheme === "Bearer") {
+          yield "  /// Checks a bearer token sent in the `Authorization` header.";
+          yield `  fn ${validatorName}(&self, token: &str) -> ${output};`;
+        } else {
+          yield "  /// Checks a username and password sent in the `Authorization` header.";
+          yield `  fn ${validatorName}(`;
+          yield "    &self,";
+          yield `    credentials: &${runtimeAuth}::BasicCredentials,`;
+          yield `  ) -> ${output};`;
+        }
+        break;
+      case "oauth2":
+        yield "  /// Checks an OAuth2 access token sent in the `Authorization` header.";
+        yield `  fn ${validatorName}(&self, token: &str) -> ${output};`;
+        break;
+    }
+  }
+  yield "}";
+  yield "";
+
+  const request = referenceVendoredHostPath("http", "Request");
+
+  yield "/// Gets the principal identified by the credential of a request for a scheme, if the authenticator accepts it.";
+  yield "pub async fn authenticate_scheme<A: Authenticator, B>(";
+  yield "  authenticator: &A,";
+  yield `  request: &${request}<B>,`;
+  yield "  scheme: Scheme,";
+  yield ") -> Option<A::Principal> {";
+  if (auth.schemes.length === 0) {
+    // Every declared scheme is unsupported, so there is no credential to check and no scheme to match.
+    yield "  let _ = (authenticator, request);";
+  }
+  yield "  match scheme {";
+  for (const { scheme, name, validatorName } of auth.schemes) {
+    yield `    Scheme::${name} => {`;
+    switch (scheme.type) {
+      case "apiKey":
+        // prettier-ignore
+        yield `      let key = ${runtimeAuth}::extract_api_key(request, ${getApiKeyLocation(scheme)}, ${JSON.stringify(scheme.name)})?;`;
+        yield `      authenticator.${validatorName}(&key).await`;
+        break;
+      case "http":
+        if (scheme.scheme === "Bearer") {
+          // prettier-ignore
+          yield `      let token = ${runtimeAuth}::extract_bearer_token(request)?;`;
+          yield `      authenticator.${validatorName}(&token).await`;
+        } else {
+          // prettier-ignore
+          yield `      let credentials = ${runtimeAuth}::extract_basic_credentials(request)?;`;
+          yield `      authenticator.${validatorName}(&credentials).await`;
+        }
+        break;
+      case "oauth2":
+        // prettier-ignore
+        yield `      let token = ${runtimeAuth}::extract_bearer_token(request)?;`;
+        yield `      authenticator.${validatorName}(&token).await`;
+        break;
+    }
+    yield "    }";
+  }
+  yield "  }";
+  yield "}";
+  yield "";
+
+  yield "/// A request that was authenticated by the router.";
+  yield "#[derive(Debug, Clone)]";
+  yield "pub struct Authenticated<P> {";
+  yield "  /// The schemes whose credentials were accepted.";
+  yield "  pub schemes: &'static [Scheme],";
+  yield "  /// The principal identified by the first of the schemes.";
+  yield "  pub principal: P,";
+  yield "}";
+  yield "";
+
+  yield "/// Authenticates a request with the first of the alternative sets of schemes whose credentials are all accepted.";
+  yield "pub async fn authenticate<A: Authenticator, B>(";
+  yield "  authenticator: &A,";
+  yield `  request: &${request}<B>,`;
+  yield "  options: &'static [&'static [Scheme]],";
+  yield ") -> Option<Authenticated<A::Principal>> {";
+  yield "  'options: for &schemes in options {";
+  yield "    let mut principal = None;";
+  yield "";
+  yield "    for &scheme in schemes {";
+  yield "      match authenticate_scheme(authenticator, request, scheme).await {";
+  yield "        Some(accepted) => {";
+  yield "          principal.get_or_insert(accepted);";
+  yield "        }";
+  yield "        None => continue 'options,";
+  yield "      }";
+  yield "    }";
+  yield "";
+  yield "    if let Some(principal) = principal {";
+  // prettier-ignore
+  yield `      ${referenceVendoredHostPath("log", "debug!")}("request authenticated with {:?}", schemes);`;
+  yield "";
+  yield "      return Some(Authenticated { schemes, principal });";
+  yield "    }";
+  yield "  }";
+  yield "";
+  yield "  None";
+  yield "}";
+  yield "";
+}
+
+function* emitSchemeDocumentation(
+  scheme: SupportedHttpAuth
+): Iterable<string> {
+  if (scheme.description) {
+    for (const line of scheme.description.trim().split(/\r?\n/g)) {
+      yield `/// ${line}`;
+    }
+  } else if (scheme.type === "apiKey") {
+    yield `/// An API key sent in the \`${scheme.name}\` ${scheme.in}.`;
+  } else if (scheme.type === "oauth2") {
+    // prettier-ignore
+    yield `/// OAuth2 access tokens obtained from \`${getClientCredentialsFlow(scheme)!.tokenUrl}\` with the client credentials flow.`;
+  } else if (scheme.scheme === "Bearer") {
+    yield "/// A bearer token sent in the `Authorization` header.";
+  } else {
+    yield "/// A username and password sent in the `Authorization` header.";
+  }
+}
+
+function isTokenScheme(scheme: SupportedHttpAuth): boolean {
+  return (
+    scheme.type === "oauth2" ||
+    (scheme.type === "http" && scheme.scheme === "Bearer")
+  );
+}
+
+function getApiKeyLocation(scheme: Extract<HttpAuth, { type: "apiKey" }>) {
+  return referenceHostPath(
+    "http",
+    "auth",
+    "ApiKeyLocation",
+    parseCase(scheme.in).pascalCase
+  );
+}
+
+function getSchemeBaseName(scheme: HttpAuth): string {
+  return scheme.id.endsWith("Auth") || scheme.id.endsWith("auth")
+    ? scheme.id.slice(0, -4)
+    : scheme.id;
 }
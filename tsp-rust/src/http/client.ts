@@ -0,0 +1,392 @@
+import { HttpService, OperationContainer } from "@typespec/http";
+import {
+  HttpContext,
+  getClientParameters,
+  getStreamedResponseBounds,
+  streamsResponse,
+} from "./feature.js";
+import { Module, PathCursor } from "../ctx.js";
+import { parseCase } from "../util/case.js";
+import {
+  emitOperationOptionsReference,
+  getOptionalParameters,
+  splitReturnType,
+} from "../common/interface.js";
+import { createOrGetModuleForNamespace } from "../common/namespace.js";
+import { emitTypeReference } from "../common/reference.js";
+import { emitDocumentation } from "../common/documentation.js";
+import {
+  referenceHostPath,
+  referenceVendoredHostPath,
+} from "../util/vendored.js";
+import { indent } from "../util/indent.js";
+import {
+  emitServerConfig,
+  getDefaultServerUrl,
+  getServerConfigs,
+} from "./server.js";
+import { getPrincipalKind, getPrincipalType } from "./auth.js";
+
+export function emitClient(ctx: HttpContext, service: HttpService) {
+  const clientModule: Module = {
+    name: "client",
+    cursor: ctx.httpModule.cursor.enter("client"),
+
+    declarations: [],
+
+    visibility: "pub",
+    inline: false,
+  };
+
+  ctx.httpModule.declarations.push(clientModule);
+
+  const serviceName = parseCase(service.namespace.name).pascalCase;
+
+  for (const config of getServerConfigs(ctx.servers, serviceName)) {
+    clientModule.declarations.push([
+      ...emitServerConfig(ctx, config, clientModule.cursor),
+    ]);
+  }
+
+  clientModule.declarations.push([
+    ...emitClientDefinition(ctx, service, clientModule.cursor),
+  ]);
+}
+
+function* emitClientDefinition(
+  ctx: HttpContext,
+  service: HttpService,
+  cursor: PathCursor
+): Iterable<string> {
+  const serviceName = parseCase(service.namespace.name).pascalCase;
+  const clientName = serviceName + "Client";
+  const builderName = clientName + "Builder";
+
+  const baseUrl = referenceHostPath("http", "BaseUrl");
+  const requestOptions = referenceHostPath("http", "RequestOptions");
+  const uri = referenceVendoredHostPath("http", "Uri");
+
+  // Without a server that can be resolved without arguments, request paths are sent unresolved until a base URL is
+  // configured.
+  const defaultServerUrl = getDefaultServerUrl(ctx.servers);
+  const defaultBaseUrl = defaultServerUrl ?? "/";
+
+  yield `/// A client for the ${serviceName} service.`;
+  yield "///";
+  yield "/// Requests are sent through the wrapped service, with their URIs resolved against the base URL of the client.";
+  yield "#[derive(Debug, Clone)]";
+  yield `pub struct ${clientName}<S> {`;
+  yield `  service: ${baseUrl}<S>,`;
+  yield `  options: ${requestOptions},`;
+  yield "}";
+  yield "";
+
+  yield `impl<S> ${clientName}<S> {`;
+  yield `  pub fn builder(service: S) -> ${builderName}<S> {`;
+  yield `    ${builderName} {`;
+  yield "      service,";
+  yield `      base_url: ${uri}::from_static(${JSON.stringify(defaultBaseUrl)}),`;
+  yield "      options: Default::default(),";
+  yield "    }";
+  yield "  }";
+  yield "";
+  yield "  pub fn into_inner(self) -> S {";
+  yield "    self.service.into_inner()";
+  yield "  }";
+
+  const clientRaw = cursor.pathTo(
+    ctx.httpModule.cursor.enter("operations", "client_raw")
+  );
+
+  for (const httpOperation of service.operations) {
+    const { operation } = httpOperation;
+    const operationNameCase = parseCase(operation.name);
+
+    const parameters = getClientParameters(ctx, httpOperation, cursor);
+
+    const [returnType, errorType] = splitReturnType(
+      ctx,
+      operation.returnType,
+      cursor,
+      operationNameCase.pascalCase
+    );
+
+    // Optional parameters are given in the options struct of the operation, like they are to its trait.
+    const requiredParameters = parameters.filter(
+      (param) => !param.property.optional
+    );
+
+    const argumentNames = [
+      "&mut self.service",
+      ...parameters.map((param) =>
+        param.property.optional ? `options.${param.name}` : param.name
+      ),
+      "&self.options",
+    ];
+
+    const prototype = (name: string, extraParameters: string[]) => [
+      // prettier-ignore
+      `  pub async fn ${name}<ResponseBody: ${referenceVendoredHostPath("http_body", "Body")}>(`,
+      "    &mut self,",
+      ...requiredParameters.map(
+        ({ name, typeReference }) => `    ${name}: ${typeReference},`
+      ),
+      ...extraParameters.map((param) => `    ${param},`),
+      "  ) -> Result<",
+      `    ${returnType.typeReference},`,
+      // prettier-ignore
+      `    ${referenceHostPath("http", "Error")}<ResponseBody, S::Error, ${errorType.typeReference}>,`,
+      "  >",
+      "  where",
+      `    S: ${referenceHostPath("http", "Service")}<ResponseBody>,`,
+      ...(streamsResponse(ctx, httpOperation)
+        ? getStreamedResponseBounds().map((bound) => `    ${bound}`)
+        : []),
+      "  {",
+    ];
+
+    yield "";
+    yield* indent(emitDocumentation(ctx, operation));
+
+    if (getOptionalParameters(operation).length > 0) {
+      const optionsTypeReference = emitOperationOptionsReference(
+        ctx,
+        operation,
+        cursor
+      );
+
+      const requiredArgumentNames = [
+        ...requiredParameters.map((param) => param.name),
+        "Default::default()",
+      ];
+
+      yield* prototype(operationNameCase.snakeCase, []);
+      // prettier-ignore
+      yield `    self.${operationNameCase.snakeCase}_with_options(${requiredArgumentNames.join(", ")}).await`;
+      yield "  }";
+      yield "";
+      yield* indent(emitDocumentation(ctx, operation));
+      yield* prototype(operationNameCase.snakeCase + "_with_options", [
+        `options: ${optionsTypeReference}`,
+      ]);
+    } else {
+      yield* prototype(operationNameCase.snakeCase, []);
+    }
+
+    // prettier-ignore
+    yield `    ${clientRaw}::${operationNameCase.snakeCase}_with_options(${argumentNames.join(", ")}).await`;
+    yield "  }";
+  }
+
+  yield "}";
+  yield "";
+
+  yield `/// Configures a ${clientName}.`;
+  yield `pub struct ${builderName}<S> {`;
+  yield "  service: S,";
+  yield `  base_url: ${uri},`;
+  yield `  options: ${requestOptions},`;
+  yield "}";
+  yield "";
+
+  yield `impl<S> ${builderName}<S> {`;
+  yield "  /// Sets the URL that the paths of requests are resolved against.";
+  yield "  ///";
+  if (defaultServerUrl !== undefined) {
+    yield `  /// Defaults to \`${defaultBaseUrl}\`.`;
+  } else {
+    yield "  /// By default, requests are sent with path-only URIs.";
+  }
+  yield `  pub fn with_base_url(mut self, base_url: ${uri}) -> Self {`;
+  yield "    self.base_url = base_url;";
+  yield "    self";
+  yield "  }";
+  yield "";
+  yield "  /// Resolves the paths of requests against the URL of a declared server.";
+  yield "  pub fn with_server(";
+  yield "    self,";
+  yield `    server: &impl ${referenceHostPath("http", "ServerConfig")},`;
+  // prettier-ignore
+  yield `  ) -> Result<Self, ${referenceVendoredHostPath("http", "uri", "InvalidUri")}> {`;
+  yield "    Ok(self.with_base_url(server.uri()?))";
+  yield "  }";
+  yield "";
+  yield "  /// Sets the options used for every request sent by the client.";
+  // prettier-ignore
+  yield `  pub fn with_request_options(mut self, options: ${requestOptions}) -> Self {`;
+  yield "    self.options = options;";
+  yield "    self";
+  yield "  }";
+  yield "";
+  yield "  /// Sets whether model arguments are checked against their constraints before requests are sent.";
+  yield "  pub fn with_validation(mut self, validate: bool) -> Self {";
+  yield "    self.options.validate = validate;";
+  yield "    self";
+  yield "  }";
+  yield "";
+  yield "  /// Wraps the service that requests are sent through in a layer, such as an OAuth2 token layer.";
+  yield `  pub fn with_layer<L: ${referenceVendoredHostPath("tower", "Layer")}<S>>(`;
+  yield "    self,";
+  yield "    layer: L,";
+  yield `  ) -> ${builderName}<L::Service> {`;
+  yield `    ${builderName} {`;
+  yield "      service: layer.layer(self.service),";
+  yield "      base_url: self.base_url,";
+  yield "      options: self.options,";
+  yield "    }";
+  yield "  }";
+  yield "";
+  if (ctx.auth) {
+    const authenticate = referenceHostPath("http", "auth", "Authenticate");
+
+    yield "  /// Authenticates every request sent by the client with a credential.";
+    // prettier-ignore
+    yield `  pub fn with_credential<C: ${referenceHostPath("http", "auth", "Credential")}>(`;
+    yield "    self,";
+    yield "    credential: C,";
+    yield `  ) -> ${builderName}<${authenticate}<S, C>> {`;
+    yield `    ${builderName} {`;
+    yield `      service: ${authenticate}::new(self.service, credential),`;
+    yield "      base_url: self.base_url,";
+    yield "      options: self.options,";
+    yield "    }";
+    yield "  }";
+    yield "";
+  }
+
+  yield `  pub fn build(self) -> ${clientName}<S> {`;
+  yield `    ${clientName} {`;
+  yield `      service: ${baseUrl}::new(self.base_url, self.service),`;
+  yield "      options: self.options,";
+  yield "    }";
+  yield "  }";
+  yield "}";
+  yield "";
+
+  const containers = new Set(
+    service.operations.map((operation) => operation.container)
+  );
+
+  for (const container of containers) {
+    yield* emitClientTraitImpl(ctx, service, container, clientName, cursor);
+  }
+}
+
+/**
+ * Implements the operation trait of a container for the client, so that code written against the trait can run
+ * against a remote service.
+ */
+function* emitClientTraitImpl(
+  ctx: HttpContext,
+  service: HttpService,
+  container: OperationContainer,
+  clientName: string,
+  cursor: PathCursor
+): Iterable<string> {
+  const operations = service.operations.filter(
+    (operation) => operation.container === container
+  );
+
+  const traitName = parseCase(container.name).pascalCase;
+
+  const traitReference =
+    container.kind === "Namespace"
+      ? cursor.pathTo(
+          createOrGetModuleForNamespace(ctx, container).cursor,
+          traitName
+        )
+      : emitTypeReference(
+          ctx,
+          container,
+          container,
+          "owned",
+          cursor,
+          "**unreachable**"
+        );
+
+  const httpBody = referenceVendoredHostPath("http_body", "Body");
+
+  yield `impl<S, ResponseBody> ${traitReference} for ${clientName}<S>`;
+  yield "where";
+  yield `  S: ${referenceVendoredHostPath("tower", "Service")}<`;
+  // prettier-ignore
+  yield `      ${referenceVendoredHostPath("http", "Request")}<${referenceHostPath("http", "Body")}>,`;
+  // prettier-ignore
+  yield `      Response = ${referenceVendoredHostPath("http", "Response")}<ResponseBody>,`;
+  yield "    > + Send,";
+  yield "  S::Future: Send,";
+  yield "  S::Error: std::error::Error + Send + Sync + 'static,";
+  yield `  ResponseBody: ${httpBody} + Send + 'static,`;
+  yield "  ResponseBody::Data: Send,";
+  yield "  ResponseBody::Error: std::error::Error + Send + Sync + 'static,";
+  yield "{";
+  // prettier-ignore
+  yield `  type Error<OperationError> = ${referenceHostPath("http", "Error")}<ResponseBody, S::Error, OperationError>`;
+  yield "  where";
+  yield "    OperationError: std::fmt::Debug + Send + Sync + 'static;";
+
+  const receivesPrincipal = operations.some((operation) =>
+    getPrincipalKind(ctx, operation.operation)
+  );
+
+  if (receivesPrincipal) {
+    yield "";
+    yield "  /// Remote calls are performed on behalf of the credential that the client was built with.";
+    yield "  type Principal = ();";
+  }
+
+  for (const httpOperation of operations) {
+    const { operation } = httpOperation;
+    const operationNameCase = parseCase(operation.name);
+
+    const parameters = getClientParameters(ctx, httpOperation, cursor);
+
+    const [returnType, errorType] = splitReturnType(
+      ctx,
+      operation.returnType,
+      cursor,
+      operationNameCase.pascalCase
+    );
+
+    const principalKind = getPrincipalKind(ctx, operation);
+
+    // Operations with optional parameters are implemented through the method that takes their options struct.
+    const hasOptions = getOptionalParameters(operation).length > 0;
+
+    const methodName = hasOptions
+      ? operationNameCase.snakeCase + "_with_options"
+      : operationNameCase.snakeCase;
+
+    const requiredParameters = parameters.filter(
+      (param) => !param.property.optional
+    );
+
+    const argumentNames = [
+      "self",
+      ...requiredParameters.map((param) => param.name),
+      ...(hasOptions ? ["options"] : []),
+    ];
+
+    yield "";
+    yield `  async fn ${methodName}(`;
+    yield "    &mut self,";
+    if (principalKind) {
+      yield `    _principal: ${getPrincipalType(principalKind, "Self")},`;
+    }
+    for (const { name, typeReference } of requiredParameters) {
+      yield `    ${name}: ${typeReference},`;
+    }
+    if (hasOptions) {
+      // prettier-ignore
+      yield `    options: ${emitOperationOptionsReference(ctx, operation, cursor)},`;
+    }
+    // prettier-ignore
+    yield `  ) -> Result<${returnType.typeReference}, Self::Error<${errorType.typeReference}>> {`;
+    // prettier-ignore
+    yield `    ${clientName}::${methodName}(${argumentNames.join(", ")}).await`;
+    yield "  }";
+  }
+
+  yield "}";
+  yield "";
+}
@@ -1,8 +1,13 @@
 import {
   JSONSchemaType,
+  Model,
+  ModelProperty,
   Type,
+  Union,
+  UnionVariant,
   isArrayModelType,
   isErrorModel,
+  isNullType,
 } from "@typespec/compiler";
 import {
   Module,
@@ -36,12 +41,40 @@ import { registerFeature } from "../feature.js";
 import { parseCase } from "../util/case.js";
 import { bifilter } from "../util/bifilter.js";
 import { emitTypeReference, isValueLiteralType } from "../common/reference.js";
-import { SplitReturnType, splitReturnType } from "../common/interface.js";
+import {
+  SplitReturnType,
+  emitOperationOptionsReference,
+  getOptionalParameters,
+  splitReturnType,
+} from "../common/interface.js";
 import { indent } from "../util/indent.js";
 import { getRustScalar } from "../common/scalar.js";
 import { createOrGetModuleForNamespace } from "../common/namespace.js";
+import { getRustVariantName } from "../common/union.js";
 import { getRustLiteralTypeAndValue } from "../common/model.js";
+import { getEncodingAdapter } from "../common/encoding.js";
+import { emitMergePatchReference, getMergePatchModel } from "../common/patch.js";
+import { emitMultipartImpls } from "./multipart.js";
 import { emitRouter } from "./router.js";
+import { emitClient } from "./client.js";
+import {
+  DEFAULT_BINARY_CONTENT_TYPE,
+  DEFAULT_CONTENT_TYPE,
+  MULTIPART_CONTENT_TYPE,
+  SERIALIZED_CONTENT_TYPES,
+  getDeclaredContentType,
+  hasStreamedBody,
+  isStreamedBodyProperty,
+} from "./stream.js";
+import {
+  AuthInfo,
+  emitAuth,
+  formatAuthRequirements,
+  getAuthInfo,
+  getPrincipalKind,
+  getPrincipalType,
+  reportUnsupportedAuthSchemes,
+} from "./auth.js";
 
 declare global {
   interface RustEmitterFeature {
@@ -56,17 +89,25 @@ export interface HttpContext extends RustContext {
   httpOptions: HttpOptions;
   httpModule: Module;
   servers: HttpServer[];
+  auth?: AuthInfo;
 
   impls: Impl[];
 }
 
-const HttpTraits = ["FromParts", "FromResponse", "Responder"] as const;
+const HttpTraits = [
+  "FromParts",
+  "FromResponse",
+  "Responder",
+  "Multipart",
+] as const;
 type HttpTrait = (typeof HttpTraits)[number];
 
 interface TraitArgs {
   FromParts: [];
   FromResponse: [body: Type];
-  Responder: [isBody: boolean];
+  /** Composite responses also carry the variants of the enum that they are emitted as. */
+  Responder: [isBody: boolean, variants?: UnionVariant[]];
+  Multipart: [];
 }
 
 const impls = new Map<HttpTrait, Set<Type | string>>(
@@ -157,9 +198,21 @@ async function emitHttp(ctx: RustContext, options: RustEmitterFeature["http"]) {
 
   httpModule.declarations.push(operationsModule);
 
+  const auth = getAuthInfo(httpContext);
+
+  // Operations that can only be authenticated with unsupported schemes still get an authenticator, so that the router
+  // rejects their requests rather than serving them unauthenticated.
+  if (auth) {
+    reportUnsupportedAuthSchemes(httpContext, auth);
+
+    httpContext.auth = auth;
+    emitAuth(httpContext, auth);
+  }
+
   emitRawClient(httpContext, httpService, operationsModule);
   emitRawServer(httpContext, httpService, operationsModule);
   emitRouter(httpContext, httpService);
+  emitClient(httpContext, httpService);
 
   const implsModule: Module = {
     name: "_impls",
@@ -202,6 +255,57 @@ function emitRawClient(
   }
 }
 
+/**
+ * A parameter of a generated client operation.
+ */
+export interface ClientParameter {
+  property: ModelProperty;
+  /** The name of the Rust parameter. */
+  name: string;
+  typeReference: string;
+}
+
+/**
+ * Gets the parameters of a generated client operation. Parameters with value literal types are settings of the
+ * operation and are not exposed to the caller.
+ */
+export function getClientParameters(
+  ctx: HttpContext,
+  httpOperation: HttpOperation,
+  cursor: PathCursor
+): ClientParameter[] {
+  const { operation } = httpOperation;
+  const operationNameCase = parseCase(operation.name);
+
+  return [...operation.parameters.properties.values()]
+    .filter((param) => !isValueLiteralType(param.type))
+    .map(function formParameter(param) {
+      const parameterNameCase = parseCase(param.name);
+
+      const patchModel = getMergePatchModel(ctx, operation, param);
+
+      // Optional parameters are owned, so that callers can pass `None` without naming the type of a borrowed value.
+      const typeReference = patchModel
+        ? emitMergePatchReference(ctx, patchModel, cursor)
+        : emitTypeReference(
+            ctx,
+            param.type,
+            param,
+            param.optional ? "owned" : "param",
+            cursor,
+            operationNameCase.pascalCase + parameterNameCase.pascalCase
+          );
+
+      return {
+        property: param,
+        name: parameterNameCase.snakeCase,
+        typeReference: param.optional
+          ? `Option<${typeReference}>`
+          : typeReference,
+      };
+    });
+}
+
 const ITERTOOLS_FORMATS = new Set(["csv", "tsv", "ssv", "pipes"]);
 
 function* emitRawClientOperation(
@@ -212,26 +316,13 @@ function* emitRawClientOperation(
   const { operation, verb, responses } = httpOperation;
   const operationNameCase = parseCase(operation.name);
 
-  const [settings, parameters] = bifilter(
-    operation.parameters.properties.values(),
-    (param) => isValueLiteralType(param.type)
-  );
-
-  const parameterLines = parameters.map(function formParameter(param) {
-    const parameterNameCase = parseCase(param.name);
-    const name = parameterNameCase.snakeCase;
+  const clientParameters = getClientParameters(ctx, httpOperation, cursor);
 
-    const typeReference = emitTypeReference(
-      ctx,
-      param.type,
-      param,
-      "param",
-      cursor,
-      operationNameCase.pascalCase + parameterNameCase.pascalCase
-    );
+  const parameters = clientParameters.map((param) => param.property);
 
-    return `${name}: ${typeReference},`;
-  });
+  const parameterLines = clientParameters.map(
+    ({ name, typeReference }) => `${name}: ${typeReference},`
+  );
 
   const [returnType, errorType] = splitReturnType(
     ctx,
@@ -240,12 +331,30 @@ function* emitRawClientOperation(
     operationNameCase.pascalCase
   );
 
+  const requestOptions = referenceHostPath("http", "RequestOptions");
+
+  const parameterNames = clientParameters.map((param) => param.name);
+
   yield "#[allow(unused)]";
   yield* getOperationPrototype(
     operationNameCase.snakeCase,
     parameterLines,
     returnType.typeReference,
-    errorType.typeReference
+    errorType.typeReference,
+    streamsResponse(ctx, httpOperation)
+  );
+  // prettier-ignore
+  yield `  ${operationNameCase.snakeCase}_with_options(service, ${[...parameterNames, `&${requestOptions}::default()`].join(", ")}).await`;
+  yield "}";
+  yield "";
+
+  yield "#[allow(unused)]";
+  yield* getOperationPrototype(
+    operationNameCase.snakeCase + "_with_options",
+    [...parameterLines, `options: &${requestOptions},`],
+    returnType.typeReference,
+    errorType.typeReference,
+    streamsResponse(ctx, httpOperation)
   );
 
   // Make sure the signature is complete before we start emitting the body, since we cannot
@@ -265,6 +374,8 @@ function* emitRawClientOperation(
   yield `  use ${referenceHostPath("http", "Error")} as HttpError;`;
   yield "";
 
+  yield* indent(emitArgumentValidation(httpOperation, parameters));
+
   yield* indent(emitOperationRequest(ctx, httpOperation, cursor));
 
   const requiresOutputContentType = httpOperation.responses.some((resp) =>
@@ -281,36 +392,81 @@ function* emitRawClientOperation(
   yield "  let status_code = parts.status.as_u16();";
   yield "";
 
-  // TODO: we exit the function early if no content-type is present, but we probably want UnexpectedStatus to prevail
-  // in cases like 404 where we may get no body and no content-type if the server sends 403/404/429 etc.
+  // The content type is only checked by the responses that have a body, as responses without one (such as a 204 or a
+  // declared error without a body) may not declare a content type at all.
 
   if (requiresOutputContentType) {
     yield `  let content_type = parts`;
     yield `    .headers`;
     yield `    .get("content-type")`;
-    yield `    .map(|h| String::from(h.to_str().expect("failed to convert header contents to String")))`;
-    // prettier-ignore
-    yield `    .ok_or_else(|| ${referenceHostPath("http", "Error", "UnexpectedContentType")}(`;
-    yield `        None,`;
+    yield `    .map(|h| h.to_str().map(String::from))`;
+    yield `    .transpose()`;
     // prettier-ignore
-    yield `        parts.clone(),`;
-    yield `    ))?;`;
+    yield `    .map_err(|_| HttpError::Parts(${referenceHostPath("http", "PartsError")}::InvalidHeader("content-type")))?;`;
     yield "";
   }
   yield "  match status_code {";
 
-  yield* indent(indent(emitResponseCases(ctx, responses, cursor)));
+  const responseVariants = new Map([
+    ...getResponseVariants(ctx, returnType),
+    ...getResponseVariants(ctx, errorType),
+  ]);
+
+  // prettier-ignore
+  yield* indent(indent(emitResponseCases(ctx, responses, responseVariants, cursor)));
 
   yield "  }";
   yield "}";
   yield "";
 }
 
+/**
+ * Checks the model arguments of a client operation against their TypeSpec constraints, unless disabled by the request
+ * options. The body is validated at the root of the JSON path, as it is the root of the serialized request content.
+ */
+function* emitArgumentValidation(
+  httpOperation: HttpOperation,
+  parameters: ModelProperty[]
+): Iterable<string> {
+  const validatedParameters = parameters.filter(
+    (param) => param.type.kind !== "Scalar" && param.type.kind !== "Intrinsic"
+  );
+
+  if (validatedParameters.length === 0) return;
+
+  const validateInto = referenceHostPath(
+    "validate",
+    "Validate",
+    "validate_into"
+  );
+
+  yield "if options.validate {";
+  // prettier-ignore
+  yield `  let mut validator = ${referenceHostPath("validate", "Validator", "new")}();`;
+
+  for (const param of validatedParameters) {
+    const name = parseCase(param.name).snakeCase;
+
+    if (httpOperation.parameters.body?.parameter === param) {
+      yield `  ${validateInto}(&${name}, &mut validator);`;
+    } else {
+      // prettier-ignore
+      yield `  validator.field(${JSON.stringify(param.name)}, |validator| ${validateInto}(&${name}, validator));`;
+    }
+  }
+
+  yield "";
+  yield "  validator.finish().map_err(HttpError::Validation)?;";
+  yield "}";
+  yield "";
+}
+
 function getOperationPrototype(
   name: string,
   parameters: string[],
   returnType: string,
-  errorType: string
+  errorType: string,
+  streamsResponse: boolean
 ): string[] {
   return [
     `pub async fn ${name}<`,
@@ -326,11 +482,205 @@ function getOperationPrototype(
     "    S::Error,",
     `    ${errorType},`,
     "  >,",
-    "> {",
+    ">",
+    ...(streamsResponse
+      ? ["where", ...getStreamedResponseBounds().map((b) => `  ${b}`)]
+      : []),
+    "{",
   ];
 }
 
-const DEFAULT_CONTENT_TYPE = "application/json";
+/**
+ * Whether any response of an operation has a streamed body.
+ */
+export function streamsResponse(
+  ctx: HttpContext,
+  httpOperation: HttpOperation
+): boolean {
+  return httpOperation.responses.some(
+    (response) =>
+      response.type.kind === "Model" && hasStreamedBody(ctx, response.type)
+  );
+}
+
+/**
+ * Gets the bounds that let a client read a streamed body out of its response body.
+ */
+export function getStreamedResponseBounds(): string[] {
+  return [
+    "ResponseBody: Send + 'static,",
+    "ResponseBody::Error: Into<Box<dyn std::error::Error + Send + Sync>>,",
+  ];
+}
+
+/**
+ * Gets the content type that a request body is sent with, when the operation does not declare a choice of them.
+ */
+function getRequestContentType(
+  ctx: HttpContext,
+  body: HttpOperationRequestBody
+): string {
+  return (
+    body.contentTypes[0] ??
+    (isBytesBody(ctx, body)
+      ? DEFAULT_BINARY_CONTENT_TYPE
+      : DEFAULT_CONTENT_TYPE)
+  );
+}
+
+/**
+ * Whether a request body is a `bytes` value.
+ */
+function isBytesBody(
+  ctx: HttpContext,
+  body: HttpOperationRequestBody
+): boolean {
+  return (
+    body.type.kind === "Scalar" &&
+    getRustScalar(ctx.program, body.type, body.type).owned === "Vec<u8>"
+  );
+}
+
+/**
+ * Whether a request body is streamed rather than serialized, which is the case for `bytes` sent with any content type
+ * that is not otherwise handled.
+ */
+function isStreamingBody(
+  ctx: HttpContext,
+  body: HttpOperationRequestBody
+): boolean {
+  return (
+    isBytesBody(ctx, body) &&
+    !SERIALIZED_CONTENT_TYPES.has(getRequestContentType(ctx, body))
+  );
+}
+
+/**
+ * The content types that a request body may be sent with, chosen through the `Content-Type` header parameter of an
+ * operation.
+ */
+interface ContentTypeChoice {
+  /** The name of the Rust parameter. */
+  name: string;
+  typeReference: string;
+  /** The content types, each with the path of the enum variant that chooses it. */
+  variants: [string, string][];
+}
+
+/**
+ * Gets the choice of content types of an operation whose body declares several of them.
+ */
+function getContentTypeChoice(
+  ctx: HttpContext,
+  httpOperation: HttpOperation,
+  cursor: PathCursor
+): ContentTypeChoice {
+  const { operation, parameters } = httpOperation;
+
+  const parameter = parameters.parameters.find(
+    (parameter) =>
+      parameter.type === "header" &&
+      isContentTypeHeader(ctx.program, parameter.param)
+  )?.param;
+
+  if (!parameter || parameter.optional || parameter.type.kind !== "Union") {
+    throw new UnimplementedError(
+      "dynamic request content type without a required content type parameter"
+    );
+  }
+
+  if (
+    isBytesBody(ctx, parameters.body!) ||
+    parameters.body!.contentTypes.includes(MULTIPART_CONTENT_TYPE)
+  ) {
+    throw new UnimplementedError(
+      "dynamic request content type of a bytes or multipart body"
+    );
+  }
+
+  const parameterNameCase = parseCase(parameter.name);
+
+  const typeReference = emitTypeReference(
+    ctx,
+    parameter.type,
+    parameter,
+    "owned",
+    cursor,
+    parseCase(operation.name).pascalCase + parameterNameCase.pascalCase
+  );
+
+  return {
+    name: parameterNameCase.snakeCase,
+    typeReference,
+    variants: getContentTypeVariants(ctx, parameter.type, typeReference),
+  };
+}
+
+/**
+ * Gets the content types of a union of literal media types, each with the path of the enum variant that chooses it.
+ */
+function getContentTypeVariants(
+  ctx: HttpContext,
+  union: Union,
+  typeReference: string
+): [string, string][] {
+  let idx = 0;
+  const variants: [string, string][] = [];
+
+  for (const variant of union.variants.values()) {
+    idx += 1;
+
+    if (variant.type.kind !== "String") {
+      throw new UnimplementedError(
+        `content type choice of kind '${variant.type.kind}'`
+      );
+    }
+
+    variants.push([
+      variant.type.value,
+      `${typeReference}::${getRustVariantName(ctx, variant, idx)}`,
+    ]);
+  }
+
+  return variants;
+}
+
+/**
+ * Gets the `Content-Type` header property of a response model that declares a choice of content types, if any.
+ */
+function getContentTypeChoiceProperty(
+  ctx: HttpContext,
+  model: Model
+): (ModelProperty & { type: Union }) | undefined {
+  for (const property of model.properties.values()) {
+    if (
+      isHeader(ctx.program, property) &&
+      isContentTypeHeader(ctx.program, property) &&
+      property.type.kind === "Union"
+    ) {
+      return property as ModelProperty & { type: Union };
+    }
+  }
+
+  return undefined;
+}
+
+/**
+ * Gets the content types that a client can decode from the responses of an operation, in the order they are declared.
+ */
+function getResponseContentTypes(operation: HttpOperation): string[] {
+  const contentTypes = new Set<string>();
+
+  for (const response of operation.responses) {
+    for (const content of response.responses) {
+      for (const contentType of content.body?.contentTypes ?? []) {
+        contentTypes.add(contentType);
+      }
+    }
+  }
+
+  return [...contentTypes];
+}
 
 function* emitOperationRequest(
   ctx: HttpContext,
@@ -343,32 +693,92 @@ function* emitOperationRequest(
     parameters: { parameters, body },
   } = operation;
 
-  // prettier-ignore
-  yield `let request = ${referenceVendoredHostPath("http", "Request")}::builder()`;
-  // prettier-ignore
-  yield `  .method(${referenceVendoredHostPath("http", "Method", verb.toUpperCase())})`;
+  const multipartBody =
+    body?.contentTypes[0] === MULTIPART_CONTENT_TYPE ? body : undefined;
+
+  const contentTypeChoice =
+    body && body.contentTypes.length > 1
+      ? getContentTypeChoice(ctx, operation, cursor)
+      : undefined;
+
+  if (contentTypeChoice) {
+    // The caller chooses the content type that the body is serialized as.
+    yield `let ${contentTypeChoice.name} = match ${contentTypeChoice.name} {`;
+    for (const [contentType, variant] of contentTypeChoice.variants) {
+      yield `  ${variant} => ${JSON.stringify(contentType)},`;
+    }
+    yield "};";
+    yield "";
+  }
+
+  if (multipartBody) {
+    if (!multipartBody.parameter) {
+      throw new UnimplementedError(
+        "serialization of deep body references is not yet supported"
+      );
+    }
+
+    impl(ctx, "Multipart", multipartBody.parameter.type);
+
+    // prettier-ignore
+    yield `let form = ${referenceHostPath("http", "multipart", "Form", "encode")}(&${multipartBody.parameter.name}).map_err(HttpError::Serialize)?;`;
+    yield "";
+  }
+
+  if (body?.parameter?.optional) {
+    throw new UnimplementedError("optional request bodies");
+  }
 
+  const headers: [string, string][] = [];
   const queryParameters: [string, string][] = [];
   const pathFormatArgs: string[] = [];
 
+  // Optional parameters are only sent when they are given, so they are bound to variables of the same name first.
+  const optionalHeaders: [string, string, string][] = [];
+  const optionalQueryParameters: [string, string, string][] = [];
+
   for (const parameter of parameters) {
     if (isContentTypeHeader(ctx.program, parameter.param)) continue;
 
+    const variable = parseCase(parameter.param.name).snakeCase;
+
     switch (parameter.type) {
       case "header": {
         const expr = formatParameterArgument(ctx, parameter);
-        // prettier-ignore
-        yield `  .header(${JSON.stringify(parameter.name)}, ${expr})`;
+
+        if (parameter.param.optional) {
+          optionalHeaders.push([variable, parameter.name, expr]);
+        } else {
+          headers.push([parameter.name, expr]);
+        }
         break;
       }
       case "query": {
-        const expr = formatParameterArgument(ctx, parameter);
+        const adapter = getEncodingAdapter(ctx, parameter.param);
 
-        queryParameters.push([parameter.name, expr]);
+        const expr = adapter
+          ? formatUriParameterArgument(parameter, adapter)
+          : formatParameterArgument(ctx, parameter);
 
+        if (parameter.param.optional) {
+          optionalQueryParameters.push([variable, parameter.name, expr]);
+        } else {
+          queryParameters.push([parameter.name, expr]);
+        }
         break;
       }
       case "path": {
+        if (parameter.param.optional) {
+          throw new UnimplementedError("optional path parameters");
+        }
+
+        const adapter = getEncodingAdapter(ctx, parameter.param);
+
+        if (adapter) {
+          pathFormatArgs.push(formatUriParameterArgument(parameter, adapter));
+          break;
+        }
+
         const expr = (
           parameter.param.type.kind === "Scalar"
             ? getRustScalar(ctx.program, parameter.param.type, parameter.param)
@@ -396,78 +806,136 @@ function* emitOperationRequest(
     pathTemplate = pathTemplate.slice(0, -1);
   }
 
-  const queryTemplate =
-    queryParameters.length > 0
-      ? `?${queryParameters.map(([name, _]) => `${name}={}`).join("&")}`
-      : "";
+  let uriString: string;
+
+  if (optionalQueryParameters.length > 0) {
+    // The query is assembled from the parameters that are given.
+    const requiredQuery = queryParameters.map(
+      ([name, expr]) => `format!(${JSON.stringify(`${name}={}`)}, ${expr})`
+    );
+
+    yield `let mut query: Vec<String> = vec![${requiredQuery.join(", ")}];`;
 
-  const formatArgs =
-    pathFormatArgs.length + queryParameters.length > 0
-      ? ", " +
-        pathFormatArgs
-          .concat(queryParameters.map(([, expr]) => expr))
-          .join(", ")
-      : "";
+    for (const [variable, name, expr] of optionalQueryParameters) {
+      yield `if let Some(${variable}) = ${variable} {`;
+      yield `  query.push(format!(${JSON.stringify(`${name}={}`)}, ${expr}));`;
+      yield "}";
+    }
 
-  const uriString =
-    pathFormatArgs.length + queryTemplate.length > 0
-      ? `format!(${JSON.stringify(pathTemplate + queryTemplate)}${formatArgs})`
-      : JSON.stringify(operation.path);
+    const pathString =
+      pathFormatArgs.length > 0
+        ? `format!(${JSON.stringify(pathTemplate)}, ${pathFormatArgs.join(", ")})`
+        : `String::from(${JSON.stringify(operation.path)})`;
 
+    yield `let mut uri = ${pathString};`;
+    yield "if !query.is_empty() {";
+    yield "  uri.push('?');";
+    yield `  uri.push_str(&query.join("&"));`;
+    yield "}";
+    yield "";
+
+    uriString = "uri";
+  } else {
+    const queryTemplate =
+      queryParameters.length > 0
+        ? `?${queryParameters.map(([name, _]) => `${name}={}`).join("&")}`
+        : "";
+
+    const formatArgs =
+      pathFormatArgs.length + queryParameters.length > 0
+        ? ", " +
+          pathFormatArgs
+            .concat(queryParameters.map(([, expr]) => expr))
+            .join(", ")
+        : "";
+
+    uriString =
+      pathFormatArgs.length + queryTemplate.length > 0
+        ? `format!(${JSON.stringify(pathTemplate + queryTemplate)}${formatArgs})`
+        : JSON.stringify(operation.path);
+  }
+
+  // prettier-ignore
+  yield `let request = ${referenceVendoredHostPath("http", "Request")}::builder()`;
   // prettier-ignore
-  yield `  .uri(${uriString})`;
+  yield `  .method(${referenceVendoredHostPath("http", "Method", verb.toUpperCase())})`;
 
-  if (body) {
-    const { contentTypes, parameter, type } = body;
+  const responseContentTypes = getResponseContentTypes(operation);
 
-    if (contentTypes.length > 1) {
-      throw new UnimplementedError(
-        `dynamic request content type: [${contentTypes.join(", ")}]`
-      );
+  if (responseContentTypes.length > 0) {
+    // Servers that offer a choice of content types send one that the client can decode.
+    yield `  .header("accept", ${JSON.stringify(responseContentTypes.join(", "))})`;
+  }
+
+  if (ctx.auth) {
+    // The credential of the client picks the schemes that it authenticates the request with from its requirements.
+    yield `  .extension(${formatAuthRequirements(ctx, operation.operation)})`;
+  }
+
+  for (const [name, expr] of headers) {
+    yield `  .header(${JSON.stringify(name)}, ${expr})`;
+  }
+
+  if (optionalHeaders.length > 0) {
+    yield `  .uri(${uriString});`;
+
+    for (const [variable, name, expr] of optionalHeaders) {
+      yield `let request = match ${variable} {`;
+      yield `  Some(${variable}) => request.header(${JSON.stringify(name)}, ${expr}),`;
+      yield "  None => request,";
+      yield "};";
     }
 
-    const contentType = contentTypes[0] ?? DEFAULT_CONTENT_TYPE;
+    yield "let request = request";
+  } else {
+    // prettier-ignore
+    yield `  .uri(${uriString})`;
+  }
 
-    yield `  .header("content-type", ${JSON.stringify(contentType)})`;
+  if (body) {
+    const { parameter, type } = body;
 
-    switch (contentType) {
-      case "application/json": {
-        if (!parameter) {
-          throw new UnimplementedError(
-            "serialization of deep body references is not yet supported"
-          );
-        }
+    const contentType = getRequestContentType(ctx, body);
 
-        yield `  .body(${referenceHostPath("http", "Body", "new")}(Box::pin(`;
-        yield `    ${referenceVendoredHostPath("futures", "stream", "once")}(`;
-        // prettier-ignore
-        yield `      ${referenceVendoredHostPath("futures", "future", "ready")}(Ok(`;
-        // prettier-ignore
-        yield `        ${referenceVendoredHostPath("http_body", "Frame", "data")}(`;
-        // prettier-ignore
-        yield `          ${referenceVendoredHostPath("serde_json", "to_vec")}(&${parameter.name})`;
-        // prettier-ignore
-        yield `            .map_err(HttpError::Serialize)?`;
-        yield `            .into(),`;
-        yield `        ),`;
-        yield `      )),`;
-        yield `    ),`;
-        yield `  )))`;
+    const contentTypeExpr =
+      contentTypeChoice?.name ?? JSON.stringify(contentType);
 
-        break;
-      }
-      case "application/merge-patch+json": {
-        yield '  .body(unimplemented!("application/merge-patch+json"))';
-        break;
-      }
-      case "multipart/form-data": {
-        yield '  .body(unimplemented!("multipart/form-data"))';
-        break;
+    // The boundary of a multipart body is part of its content type.
+    yield multipartBody
+      ? `  .header("content-type", form.content_type())`
+      : `  .header("content-type", ${contentTypeExpr})`;
+
+    if (!parameter) {
+      throw new UnimplementedError(
+        "serialization of deep body references is not yet supported"
+      );
+    }
+
+    if (multipartBody) {
+      yield "  .body(form.into_body())";
+    } else if (isStreamingBody(ctx, body)) {
+      // Any other content type carries the bytes as they are read.
+      // prettier-ignore
+      yield `  .body(${referenceHostPath("http", "Body", "from_reader")}(${parameter.name}))`;
+    } else {
+      // Bytes parameters are readers, which cannot be serialized.
+      if (isBytesBody(ctx, body)) {
+        throw new UnimplementedError(`bytes bodies sent as '${contentType}'`);
       }
-      default:
-        throw new UnimplementedError(
-          `serialization for HTTP request content-type '${contentType}' is not yet supported.`
-        );
+
+      // Scalar parameters may be borrowed through a trait, like `impl AsRef<str>`, so they are passed as their value.
+      const value =
+        type.kind === "Scalar"
+          ? getRustScalar(ctx.program, type, parameter).paramTemplate.replace(
+              "{}",
+              parameter.name
+            )
+          : parameter.name;
+
+      // The body is serialized by the codec registered for its content type. Merge patches are serialized like any
+      // other JSON body, as their types omit the properties they leave unchanged.
+      // prettier-ignore
+      yield `  .body(${referenceHostPath("http", "serialize_body")}(${contentTypeExpr}, &${value}).map_err(HttpError::Serialize)?)`;
     }
   } else {
     const body = `${referenceHostPath(
@@ -484,14 +952,36 @@ function* emitOperationRequest(
   yield "";
 }
 
+/**
+ * Formats an encoded query or path parameter, which may contain characters that are reserved in a URI, like the `+`
+ * and `/` of base64.
+ */
+function formatUriParameterArgument(
+  parameter: HttpOperationParameter,
+  adapter: string
+): string {
+  const parameterName = parseCase(parameter.param.name).snakeCase;
+
+  // prettier-ignore
+  return `${referenceHostPath("http", "encode_uri_parameter")}::<${adapter}, _>(&${parameterName}).map_err(HttpError::Serialize)?`;
+}
+
 function formatParameterArgument(
   ctx: HttpContext,
   parameter: HttpOperationParameter
 ): string {
   const parameterName = parseCase(parameter.param.name).snakeCase;
 
+  const adapter = getEncodingAdapter(ctx, parameter.param);
+
+  if (adapter) {
+    // prettier-ignore
+    return `${referenceHostPath("http", "encode_header")}::<${adapter}, _>(&${parameterName}).map_err(HttpError::Serialize)?`;
+  }
+
+  // Optional parameters are owned, rather than borrowed through a trait like `impl AsRef<str>`.
   const exprTemplate =
-    parameter.param.type.kind === "Scalar"
+    parameter.param.type.kind === "Scalar" && !parameter.param.optional
       ? getRustScalar(ctx.program, parameter.param.type, parameter.param)
           .paramTemplate
       : "{}";
@@ -526,9 +1016,41 @@ function formatParameterArgument(
   }
 }
 
+/**
+ * The paths of the enum variants that responses are wrapped in when an operation has several success or error
+ * responses, keyed by the type of the response.
+ */
+type ResponseVariants = Map<Type, string>;
+
+function getResponseVariants(
+  ctx: HttpContext,
+  split: SplitReturnType
+): [Type, string][] {
+  if (split.kind !== "union") return [];
+
+  return split.variants.map((variant, idx) => [
+    variant.type,
+    `${split.typeReference}::${getRustVariantName(ctx, variant, idx + 1)}`,
+  ]);
+}
+
+/**
+ * Wraps the value of a response in its enum variant, if the operation has one for it.
+ */
+function wrapResponse(
+  responseVariants: ResponseVariants,
+  response: HttpOperationResponse,
+  value: string
+): string {
+  const variant = responseVariants.get(response.type);
+
+  return variant ? `${variant}(${value})` : value;
+}
+
 function* emitResponseCases(
   ctx: HttpContext,
   responses: HttpOperationResponse[],
+  responseVariants: ResponseVariants,
   cursor: PathCursor
 ): Iterable<string> {
   const hasWildcardStatus = responses.some((r) => r.statusCodes === "*");
@@ -537,14 +1059,20 @@ function* emitResponseCases(
     const statusPattern = getStatusCodePattern(response.statusCodes);
 
     if (response.type.kind === "Intrinsic" && response.type.name === "void") {
-      yield `#[allow(clippy::unit_arg)]`;
+      yield `#[allow(clippy::let_unit_value)]`;
     }
 
     yield `${statusPattern} => {`;
 
     for (const responseContent of response.responses) {
       yield* indent(
-        emitResponseContent(ctx, response, responseContent, cursor)
+        emitResponseContent(
+          ctx,
+          response,
+          responseContent,
+          responseVariants,
+          cursor
+        )
       );
     }
 
@@ -561,6 +1089,7 @@ function* emitResponseContent(
   ctx: HttpContext,
   response: HttpOperationResponse,
   responseContent: HttpOperationResponseContent,
+  responseVariants: ResponseVariants,
   cursor: PathCursor
 ): Iterable<string> {
   const { body } = responseContent;
@@ -582,10 +1111,12 @@ function* emitResponseContent(
 
     // yield "";
 
-    yield "match content_type.as_str() {";
+    // Content types are matched by their essence, so that parameters such as `charset` don't prevent a match.
+    // prettier-ignore
+    yield `match content_type.as_deref().map(${referenceHostPath("http", "codec", "essence")}).as_deref() {`;
 
     for (const contentType of body.contentTypes) {
-      yield `  ${JSON.stringify(contentType)} => {`;
+      yield `  Some(${JSON.stringify(contentType.toLowerCase())}) => {`;
       yield* indent(
         indent(
           emitBodyDeserialization(
@@ -594,6 +1125,7 @@ function* emitResponseContent(
             contentType,
             bodyTypeReference,
             body,
+            responseVariants,
             cursor
           )
         )
@@ -602,7 +1134,7 @@ function* emitResponseContent(
     }
 
     // prettier-ignore
-    yield `  _ => Err(HttpError::UnexpectedContentType(Some(content_type), parts)),`;
+    yield `  _ => Err(HttpError::UnexpectedContentType(content_type, parts)),`;
 
     yield "}";
   } else {
@@ -619,15 +1151,16 @@ function* emitResponseContent(
 
     impl(ctx, "FromParts", response.type);
 
-    const fromParts = `${referenceHostPath(
-      "http",
-      "FromParts"
-    )}::from_parts(parts)`;
+    // prettier-ignore
+    yield `let response = ${referenceHostPath("http", "FromParts")}::from_parts(parts).map_err(HttpError::Parts)?;`;
+    yield "";
+
+    const value = wrapResponse(responseVariants, response, "response");
 
     if (isErrorResponse) {
-      yield `Err(HttpError::Operation(${fromParts}))`;
+      yield `Err(HttpError::Operation(${value}))`;
     } else {
-      yield `Ok(${fromParts})`;
+      yield `Ok(${value})`;
     }
   }
 }
@@ -638,39 +1171,48 @@ function* emitBodyDeserialization(
   contentType: string,
   bodyTypeReference: string,
   body: HttpOperationRequestBody,
+  responseVariants: ResponseVariants,
   cursor: PathCursor
 ): Iterable<string> {
   const isErrorResponse = isErrorModel(ctx.program, response.type);
 
   const bodyIsResponse = body.type === response.type;
 
-  const responseValue = bodyIsResponse
-    ? "body"
-    : `${referenceHostPath(
-        "http",
-        "FromResponse"
-      )}::from_response(body, parts)`;
-
   if (!bodyIsResponse) {
     impl(ctx, "FromResponse", response.type, body.type);
   }
 
-  switch (contentType) {
-    case "application/json": {
-      // prettier-ignore
-      yield `let body: ${bodyTypeReference} = ${referenceHostPath("http", "deserialize_body")}(body).await?;`;
-      yield "";
-      if (isErrorResponse) {
-        yield `Err(HttpError::Operation(${responseValue}))`;
-      } else {
-        yield `Ok(${responseValue})`;
-      }
-      break;
-    }
-    default:
-      throw new UnimplementedError(
-        `deserialization for HTTP response content type '${contentType}' is not yet supported.`
-      );
+  if (contentType === MULTIPART_CONTENT_TYPE) {
+    throw new UnimplementedError(
+      `deserialization for HTTP response content type '${contentType}' is not yet supported.`
+    );
+  }
+
+  if (
+    !bodyIsResponse &&
+    response.type.kind === "Model" &&
+    hasStreamedBody(ctx, response.type)
+  ) {
+    // A streamed body is read by the caller as it arrives.
+    // prettier-ignore
+    yield `let body = ${referenceHostPath("http", "stream", "ByteStream", "from_body")}(body);`;
+  } else {
+    // The body is deserialized by the codec registered for its content type.
+    // prettier-ignore
+    yield `let body: ${bodyTypeReference} = ${referenceHostPath("http", "deserialize_body")}(${JSON.stringify(contentType)}, body).await?;`;
+  }
+  yield "";
+  if (!bodyIsResponse) {
+    // prettier-ignore
+    yield `let body = ${referenceHostPath("http", "FromResponse")}::from_response(body, parts).map_err(HttpError::Parts)?;`;
+    yield "";
+  }
+  const value = wrapResponse(responseVariants, response, "body");
+
+  if (isErrorResponse) {
+    yield `Err(HttpError::Operation(${value}))`;
+  } else {
+    yield `Ok(${value})`;
   }
 }
 
@@ -702,6 +1244,9 @@ function* emitImpl(ctx: HttpContext, impl: Impl): Iterable<string> {
     case "Responder":
       yield* emitResponderImpl(ctx, impl as Impl<"Responder">, cursor);
       break;
+    case "Multipart":
+      yield* emitMultipartImpls(ctx, impl.for as Model, cursor);
+      break;
     default:
       throw new Error(`Unreachable: ${impl.trait satisfies never}`);
   }
@@ -732,24 +1277,41 @@ function* emitFromResponseOrHeaderImpl(
 
   const trait = referenceHostPath("http", impl.trait);
 
+  // A streamed body is referenced through its property, which is what makes it a `ByteStream`.
+  const streamedBody =
+    impl.for.kind === "Model"
+      ? [...impl.for.properties.values()].find((property) =>
+          isStreamedBodyProperty(ctx, property)
+        )
+      : undefined;
+
   const args = impl.args.map((arg) =>
-    emitTypeReference(ctx, arg, arg, "owned", cursor, "**unreachable**")
+    emitTypeReference(
+      ctx,
+      arg,
+      streamedBody ?? arg,
+      "owned",
+      cursor,
+      "**unreachable**"
+    )
   );
 
   const generics = args.length > 0 ? `<${args.join(", ")}>` : "";
 
+  const partsError = referenceHostPath("http", "PartsError");
+
   yield `impl ${trait}${generics} for ${typeReference} {`;
   yield "  #[allow(unused)]";
 
   switch (impl.trait) {
     case "FromParts": {
       // prettier-ignore
-      yield `  fn from_parts(parts: ${referenceVendoredHostPath("http", "response", "Parts")}) -> Self {`;
+      yield `  fn from_parts(parts: ${referenceVendoredHostPath("http", "response", "Parts")}) -> Result<Self, ${partsError}> {`;
       break;
     }
     case "FromResponse": {
       // prettier-ignore
-      yield `  fn from_response(body: ${args[0]}, parts: ${referenceVendoredHostPath("http", "response", "Parts")}) -> Self {`;
+      yield `  fn from_response(body: ${args[0]}, parts: ${referenceVendoredHostPath("http", "response", "Parts")}) -> Result<Self, ${partsError}> {`;
       break;
     }
     default: {
@@ -772,7 +1334,7 @@ function* emitConstructor(
   // TODO: support recursive construction for deeply-nested http data
   switch (type.kind) {
     case "Model": {
-      yield "Self {";
+      yield "Ok(Self {";
       for (const property of type.properties.values()) {
         // Skip settings
         if (isValueLiteralType(property.type)) continue;
@@ -790,17 +1352,35 @@ function* emitConstructor(
               yield `  ${name}: body,`;
             }
           }
+        } else if (
+          isContentTypeHeader(ctx.program, property) &&
+          property.type.kind === "Union" &&
+          !property.optional
+        ) {
+          // A choice of content types is read as the enum of its media types.
+          // prettier-ignore
+          yield `  ${name}: ${referenceHostPath("http", "parse_content_type")}(&parts)?,`;
         } else if (isHeader(ctx.program, property)) {
           const headerInfo = getHeaderFieldOptions(ctx.program, property);
 
-          if (property.optional) {
-            // prettier-ignore
-            yield `  ${name}: parts.headers.get(${JSON.stringify(headerInfo.name)}).map(|h| h.to_str().unwrap().into()),`;
-          } else {
-            // TODO: make this whole thing return a Result
-            // prettier-ignore
-            yield `  ${name}: parts.headers.get(${JSON.stringify(headerInfo.name)}).map(|h| h.to_str().unwrap()).unwrap().into(),`;
-          }
+          const adapter = getEncodingAdapter(ctx, property);
+
+          // Encoded headers are decoded with their adapter, and any other header is parsed from its text.
+          const parseHeader = adapter
+            ? `${referenceHostPath(
+                "http",
+                property.optional
+                  ? "parse_encoded_header"
+                  : "parse_required_encoded_header"
+              )}::<${adapter}, _>`
+            : referenceHostPath(
+                "http",
+                property.optional ? "parse_header" : "parse_required_header"
+              );
+
+          yield `  ${name}: ${parseHeader}(&parts, ${JSON.stringify(
+            headerInfo.name
+          )})?,`;
         } else if (isStatusCode(ctx.program, property)) {
           if (property.optional) {
             yield `  ${name}: Some(parts.status.as_u16().into()),`;
@@ -813,13 +1393,13 @@ function* emitConstructor(
           );
         }
       }
-      yield "}";
+      yield "})";
       break;
     }
     case "Intrinsic":
       switch (type.name) {
         case "void": {
-          yield "()";
+          yield "Ok(())";
           break;
         }
         default: {
@@ -836,12 +1416,51 @@ function* emitConstructor(
   }
 }
 
+/**
+ * Emits the responder of an enum of responses, which responds as its variant does. Each variant carries its own status
+ * code and headers.
+ */
+function* emitCompositeResponderImpl(
+  ctx: HttpContext,
+  typeReference: string,
+  variants: UnionVariant[]
+): Iterable<string> {
+  const responder = referenceHostPath("http", "Responder");
+
+  yield `impl ${responder} for ${typeReference} {`;
+  // prettier-ignore
+  yield `  fn to_response<B: ${referenceVendoredHostPath("http_body", "Body")}, E: std::error::Error>(self, accept: &${referenceHostPath("http", "accept", "Accept")}) -> Result<`;
+  // prettier-ignore
+  yield `    ${referenceVendoredHostPath("http", "Response")}<${referenceHostPath("http", "Body")}>,`;
+  yield `    ${referenceHostPath("http", "ServerError")}<B, E>`;
+  yield `  > {`;
+  yield "    match self {";
+
+  let idx = 0;
+  for (const variant of variants) {
+    idx += 1;
+
+    const variantName = getRustVariantName(ctx, variant, idx);
+
+    if (isNullType(variant.type)) {
+      throw new UnimplementedError("null response variant");
+    }
+
+    // prettier-ignore
+    yield `      Self::${variantName}(value) => ${responder}::to_response(value, accept),`;
+  }
+
+  yield "    }";
+  yield "  }";
+  yield "}";
+}
+
 function* emitResponderImpl(
   ctx: HttpContext,
   impl: Impl<"Responder">,
   cursor: PathCursor
 ): Iterable<string> {
-  const typeIsBody = impl.args[0];
+  const [typeIsBody, variants] = impl.args;
 
   const typeReference = Array.isArray(impl.for)
     ? cursor.pathTo(...impl.for)
@@ -855,8 +1474,12 @@ function* emitResponderImpl(
       );
 
   if (Array.isArray(impl.for)) {
-    // TODO: this is getting very complicated!
-    throw new UnimplementedError("Composite response output.");
+    if (!variants) {
+      throw new UnimplementedError("Composite response output.");
+    }
+
+    yield* emitCompositeResponderImpl(ctx, typeReference, variants);
+    return;
   }
 
   if (impl.for.kind === "Intrinsic") return [];
@@ -864,11 +1487,17 @@ function* emitResponderImpl(
   if (impl.for.kind === "Model" && isArrayModelType(ctx.program, impl.for))
     return [];
 
+  const contentTypeProperty =
+    impl.for.kind === "Model"
+      ? getContentTypeChoiceProperty(ctx, impl.for)
+      : undefined;
+
+  // Only responses with a choice of content types consult the request's `Accept` header.
+  const acceptName = contentTypeProperty ? "accept" : "_";
+
   yield `impl ${referenceHostPath("http", "Responder")} for ${typeReference} {`;
-  yield `  fn to_response<B: ${referenceVendoredHostPath(
-    "http_body",
-    "Body"
-  )}, E: std::error::Error>(self) -> Result<`;
+  // prettier-ignore
+  yield `  fn to_response<B: ${referenceVendoredHostPath("http_body", "Body")}, E: std::error::Error>(self, ${acceptName}: &${referenceHostPath("http", "accept", "Accept")}) -> Result<`;
   yield `    ${referenceVendoredHostPath(
     "http",
     "Response"
@@ -878,45 +1507,81 @@ function* emitResponderImpl(
 
   switch (impl.for.kind) {
     case "Model": {
-      if (typeIsBody) {
-        // prettier-ignore
-        yield `    let response = ${referenceVendoredHostPath("http", "Response")}::builder()`;
-        yield `      .status(200u16)`;
-        yield `      .header("content-type", "application/json")`;
-        yield `      .body(`;
-        yield `        ${referenceHostPath(
-          "http",
-          "serialize_json_body"
-        )}(&self)`;
-        yield `                .map_err(${referenceHostPath(
-          "http",
-          "ServerError",
-          "Serialize"
-        )})?`;
-        yield `      )`;
-        yield `      .unwrap();`;
-        yield "";
+      const defaultStatusCode = isErrorModel(ctx.program, impl.for)
+        ? "400u16"
+        : "200u16";
 
-        yield "    Ok(response)";
-        yield "  }";
-        yield "}";
+      if (contentTypeProperty) {
+        const propertyTypeReference = emitTypeReference(
+          ctx,
+          contentTypeProperty.type,
+          contentTypeProperty,
+          "owned",
+          cursor,
+          parseCase(impl.for.name).pascalCase +
+            parseCase(contentTypeProperty.name).pascalCase
+        );
+
+        const variants = getContentTypeVariants(
+          ctx,
+          contentTypeProperty.type,
+          propertyTypeReference
+        );
+
+        const offered = variants
+          .map(([contentType]) => JSON.stringify(contentType))
+          .join(", ");
 
-        return;
+        // The content type of the value is preferred, unless the request accepts another one more.
+        // prettier-ignore
+        yield `    let preferred = match self.${parseCase(contentTypeProperty.name).snakeCase} {`;
+        for (const [contentType, variant] of variants) {
+          yield `      ${variant} => ${JSON.stringify(contentType)},`;
+        }
+        yield "    };";
+        yield "";
+        yield `    let content_type = accept`;
+        yield `      .negotiate(&[preferred, ${offered}])`;
+        // prettier-ignore
+        yield `      .ok_or(${referenceHostPath("http", "ServerError", "NotAcceptable")}(&[${offered}]))?;`;
+        yield "";
       }
 
       // prettier-ignore
       yield `    let response = ${referenceVendoredHostPath("http", "Response")}::builder()`;
 
-      const defaultStatusCode = isErrorModel(ctx.program, impl.for)
-        ? "400u16"
-        : "200u16";
-
       const responseData: {
         body?: string[];
         headers: [string, string][];
         status: string;
       } = { headers: [], status: defaultStatusCode };
 
+      // A literal `Content-Type` header of the model is sent like any other header, and picks the codec of the body.
+      const declaredContentType = getDeclaredContentType(ctx, impl.for);
+
+      // A choice of content types is sent as the negotiated one.
+      const hasContentType = !!(declaredContentType || contentTypeProperty);
+
+      if (typeIsBody) {
+        const contentTypeExpr = contentTypeProperty
+          ? "content_type"
+          : JSON.stringify(declaredContentType ?? DEFAULT_CONTENT_TYPE);
+
+        // The model is serialized as a whole, but its metadata properties still apply to the response.
+        if (!hasContentType) {
+          responseData.headers.push(["content-type", contentTypeExpr]);
+        }
+        responseData.body = [
+          // prettier-ignore
+          `${referenceHostPath("http", "serialize_body")}(${contentTypeExpr}, &self)`,
+          `  .map_err(${referenceHostPath(
+            "http",
+            "ServerError",
+            "Serialize"
+          )})?`,
+        ];
+      }
+
       for (const property of impl.for.properties.values()) {
         const propertyNameCase = parseCase(property.name);
 
@@ -926,31 +1591,61 @@ function* emitResponderImpl(
           } else {
             responseData.status = `self.${propertyNameCase.snakeCase} as u16`;
           }
+        } else if (property === contentTypeProperty) {
+          responseData.headers.push(["content-type", "content_type"]);
         } else if (isHeader(ctx.program, property)) {
           const headerInfo = getHeaderFieldOptions(ctx.program, property);
 
+          const adapter = getEncodingAdapter(ctx, property);
+
           if (isValueLiteralType(property.type)) {
             const [, value] = getRustLiteralTypeAndValue(property.type);
             responseData.headers.push([headerInfo.name, value]);
+          } else if (adapter) {
+            responseData.headers.push([
+              headerInfo.name,
+              // prettier-ignore
+              `${referenceHostPath("http", "encode_header")}::<${adapter}, _>(&self.${propertyNameCase.snakeCase}).map_err(${referenceHostPath("http", "ServerError", "Serialize")})?`,
+            ]);
           } else {
+            // The whole model is serialized as the body in that case, so the header value can't be moved out of it.
             responseData.headers.push([
               headerInfo.name,
-              `self.${propertyNameCase.snakeCase}`,
+              typeIsBody
+                ? `self.${propertyNameCase.snakeCase}.clone()`
+                : `self.${propertyNameCase.snakeCase}`,
             ]);
           }
         } else if (isBody(ctx.program, property)) {
           if (isValueLiteralType(property.type)) {
             throw new UnimplementedError("literal body responder");
           } else {
-            // TODO: assumes JSON
-            responseData.headers.unshift([
-              "content-type",
-              JSON.stringify("application/json"),
-            ]);
+            const contentTypeExpr = contentTypeProperty
+              ? "content_type"
+              : JSON.stringify(
+                  declaredContentType ??
+                    (property.type.kind === "Scalar" &&
+                    getRustScalar(ctx.program, property.type, property)
+                      .owned === "Vec<u8>"
+                      ? DEFAULT_BINARY_CONTENT_TYPE
+                      : DEFAULT_CONTENT_TYPE)
+                );
+
+            if (!hasContentType) {
+              responseData.headers.unshift(["content-type", contentTypeExpr]);
+            }
+
+            if (isStreamedBodyProperty(ctx, property)) {
+              responseData.body = [
+                // prettier-ignore
+                `${referenceHostPath("http", "Body", "from_reader")}(self.${propertyNameCase.snakeCase})`,
+              ];
+              continue;
+            }
+
             responseData.body = [
-              `${referenceHostPath("http", "serialize_json_body")}(&self.${
-                propertyNameCase.snakeCase
-              })`,
+              // prettier-ignore
+              `${referenceHostPath("http", "serialize_body")}(${contentTypeExpr}, &self.${propertyNameCase.snakeCase})`,
               `  .map_err(${referenceHostPath(
                 "http",
                 "ServerError",
@@ -1091,24 +1786,43 @@ function* emitRawServerOperation(
     }
   ) as Extract<HttpOperationParameter, { type: "path" }>[];
 
+  // Operations with a streaming body are given a reader that owns the request body, and which reports its errors as
+  // I/O errors.
+  const streaming =
+    !!operation.parameters.body &&
+    isStreamingBody(ctx, operation.parameters.body);
+
   yield `pub async fn ${operationNameCase.snakeCase}<`;
   yield `  Operations: ${operationTrait},`;
   // prettier-ignore
-  yield `  RequestBody: ${referenceVendoredHostPath("http_body", "Body")} + Send + Sync,`
+  yield `  RequestBody: ${referenceVendoredHostPath("http_body", "Body")} + Send + Sync${streaming ? " + 'static" : ""},`
   yield ">(";
   yield `  mut operations: Operations,`;
   // prettier-ignore
   yield `  request: ${referenceVendoredHostPath("http", "Request")}<RequestBody>,`;
 
+  const principalKind = getPrincipalKind(ctx, op);
+
+  if (principalKind) {
+    yield `  principal: ${getPrincipalType(principalKind, "Operations")},`;
+  }
+
   for (const pathParam of pathParameters) {
-    const typeReference = emitTypeReference(
-      ctx,
-      pathParam.param.type,
-      pathParam.param,
-      "borrowed",
-      cursor,
-      "**unreachable**"
-    );
+    if (pathParam.param.optional) {
+      throw new UnimplementedError("optional path parameters");
+    }
+
+    // Encoded path parameters are given as the segment that they were sent as, and decoded below.
+    const typeReference = getEncodingAdapter(ctx, pathParam.param)
+      ? "&str"
+      : emitTypeReference(
+          ctx,
+          pathParam.param.type,
+          pathParam.param,
+          "borrowed",
+          cursor,
+          "**unreachable**"
+        );
 
     yield `  ${parseCase(pathParam.param.name).snakeCase}: ${typeReference},`;
   }
@@ -1118,12 +1832,33 @@ function* emitRawServerOperation(
   yield `  ${referenceVendoredHostPath("http", "Response")}<${referenceHostPath("http", "Body")}>,`;
   // prettier-ignore
   yield `  ${referenceHostPath("http", "ServerError")}<RequestBody, Operations::Error<${errorType.typeReference}>>`;
-  yield "> {";
+
+  if (streaming) {
+    yield ">";
+    yield "where";
+    // prettier-ignore
+    yield `  <RequestBody as ${referenceVendoredHostPath("http_body", "Body")}>::Error: std::error::Error + Send + Sync + 'static,`;
+    yield "{";
+  } else {
+    yield "> {";
+  }
 
   yield "  #[allow(unused_variables)]";
   yield "  let (parts, body) = request.into_parts();";
   yield "";
 
+  for (const pathParam of pathParameters) {
+    const adapter = getEncodingAdapter(ctx, pathParam.param);
+
+    if (adapter) {
+      const name = parseCase(pathParam.param.name).snakeCase;
+
+      // prettier-ignore
+      yield `  let ${name} = ${referenceHostPath("http", "decode_path_parameter")}::<${adapter}, _, _, _>(${JSON.stringify(pathParam.name)}, ${name})?;`;
+      yield "";
+    }
+  }
+
   const needsContentType =
     (operation.parameters.body?.contentTypes.length ?? 0) > 1;
 
@@ -1135,8 +1870,8 @@ function* emitRawServerOperation(
     yield `    return Err(${referenceHostPath(
       "http",
       "ServerError",
-      "InvalidRequest"
-    )});`;
+      "UnsupportedContentType"
+    )}(None));`;
     yield `};`;
   }
 
@@ -1150,6 +1885,9 @@ function* emitRawServerOperation(
   for (const parameter of operation.parameters.parameters) {
     switch (parameter.type) {
       case "header":
+        // The content type is checked against the body that it describes.
+        if (isContentTypeHeader(ctx.program, parameter.param)) continue;
+
         yield* indent(emitHeaderParamBinding(ctx, parameter, cursor));
         break;
       case "query":
@@ -1211,11 +1949,8 @@ function* emitRawServerOperation(
       "form_urlencoded",
       "parse"
     )}(query.as_bytes()))`;
-    yield `    .ok_or(${referenceHostPath(
-      "http",
-      "ServerError",
-      "InvalidRequest"
-    )})?;`;
+    yield `    .into_iter()`;
+    yield `    .flatten();`;
 
     yield "";
 
@@ -1242,9 +1977,11 @@ function* emitRawServerOperation(
       const qpVarName = parseCase(qp.param.name).snakeCase;
       yield `  let Some(${qpVarName}) = ${qpVarName} else {`;
       // prettier-ignore
-      yield `    return Err(${referenceHostPath("http", "ServerError", "InvalidRequest")});`;
+      yield `    return Err(${referenceHostPath("http", "ServerError", "MissingParameter")}(${JSON.stringify(qp.name)}));`;
       yield "  };";
     }
+
+    yield* indent(emitParameterDecoding(ctx, qp));
   }
 
   yield "";
@@ -1252,65 +1989,122 @@ function* emitRawServerOperation(
   if (operation.parameters.body) {
     const body = operation.parameters.body;
 
-    if (body.contentTypes.length > 1) {
-      throw new UnimplementedError("dynamic request content type");
+    const contentType = getRequestContentType(ctx, body);
+
+    if (!body.parameter) {
+      throw new UnimplementedError("Composite body deserialization.");
     }
 
-    const contentType = body.contentTypes[0] ?? DEFAULT_CONTENT_TYPE;
+    if (body.parameter.optional) {
+      throw new UnimplementedError("optional request bodies");
+    }
 
-    switch (contentType) {
-      case "application/json": {
-        if (!body.parameter) {
-          throw new UnimplementedError("Composite body deserialization.");
-        }
+    const bodyNameCase = parseCase(body.parameter.name);
 
-        const bodyNameCase = parseCase(body.parameter.name);
+    if (needsContentType) {
+      const choice = getContentTypeChoice(ctx, operation, cursor);
 
-        yield `  let ${bodyNameCase.snakeCase} = ${referenceHostPath(
-          "http",
-          "deserialize_body_server"
-        )}(body).await?;`;
+      const annotation =
+        body.type.kind === "Scalar"
+          ? `: (_, ${getRustScalar(ctx.program, body.type, body.parameter).owned})`
+          : "";
 
-        yield "";
-        break;
+      // The body is deserialized by the codec of the content type that it was sent with, which is also passed to the
+      // operation as its choice.
+      // prettier-ignore
+      yield `  let (${choice.name}, ${bodyNameCase.snakeCase})${annotation} = match ${referenceHostPath("http", "codec", "essence")}(content_type).as_str() {`;
+      for (const [contentType, variant] of choice.variants) {
+        // prettier-ignore
+        yield `    ${JSON.stringify(contentType.toLowerCase())} => (${variant}, ${referenceHostPath("http", "deserialize_body_server")}(${JSON.stringify(contentType)}, body).await?),`;
       }
-      case "application/merge-patch+json":
-      case "multipart/form-data":
-        yield `let body = unimplemented!(${JSON.stringify(contentType)});`;
-        break;
-      default:
-        throw new UnimplementedError(
-          `request deserialization for content-type: '${contentType}'`
-        );
+      // prettier-ignore
+      yield `    _ => return Err(${referenceHostPath("http", "ServerError", "UnsupportedContentType")}(Some(content_type.to_string()))),`;
+      yield "  };";
+      // prettier-ignore
+      yield `  ${referenceHostPath("validate", "Validate", "validate")}(&${bodyNameCase.snakeCase}).map_err(${referenceHostPath("http", "ServerError", "Validation")})?;`;
+    } else if (contentType === MULTIPART_CONTENT_TYPE) {
+      impl(ctx, "Multipart", body.parameter.type);
+
+      // prettier-ignore
+      yield `  let ${bodyNameCase.snakeCase} = ${referenceHostPath("http", "multipart", "deserialize_multipart_server")}(&parts, body).await?;`;
+      // prettier-ignore
+      yield `  ${referenceHostPath("validate", "Validate", "validate")}(&${bodyNameCase.snakeCase}).map_err(${referenceHostPath("http", "ServerError", "Validation")})?;`;
+    } else if (isStreamingBody(ctx, body)) {
+      // The operation reads the body as it arrives.
+      // prettier-ignore
+      yield `  ${referenceHostPath("http", "check_content_type")}(&parts, ${JSON.stringify(contentType)})?;`;
+      // prettier-ignore
+      yield `  let ${bodyNameCase.snakeCase} = ${referenceHostPath("http", "stream", "BodyReader", "new")}(body);`;
+    } else {
+      if (isBytesBody(ctx, body)) {
+        throw new UnimplementedError(`bytes bodies sent as '${contentType}'`);
+      }
+
+      // Scalar parameters may be taken through a trait, like `impl AsRef<str>`, so their type can't be inferred.
+      const annotation =
+        body.type.kind === "Scalar"
+          ? `: ${getRustScalar(ctx.program, body.type, body.parameter).owned}`
+          : "";
+
+      // The body is deserialized by the codec registered for its content type.
+      // prettier-ignore
+      yield `  ${referenceHostPath("http", "check_content_type")}(&parts, ${JSON.stringify(contentType)})?;`;
+      // prettier-ignore
+      yield `  let ${bodyNameCase.snakeCase}${annotation} = ${referenceHostPath("http", "deserialize_body_server")}(${JSON.stringify(contentType)}, body).await?;`;
+      // prettier-ignore
+      yield `  ${referenceHostPath("validate", "Validate", "validate")}(&${bodyNameCase.snakeCase}).map_err(${referenceHostPath("http", "ServerError", "Validation")})?;`;
     }
 
     yield "";
   }
 
-  // TODO: map_err is wrong here, and prevents us from running the error variants' Responder impls
+  const toResponse = referenceHostPath("http", "Responder", "to_response");
 
-  if (
-    successType.target &&
-    !Array.isArray(successType.target) &&
-    successType.target.kind === "Intrinsic" &&
-    successType.target.name === "void"
-  ) {
-    yield `  #[allow(clippy::let_unit_value)]`;
+  const argumentNames = parameters
+    .filter((p) => !p.optional)
+    .map((p) => parseCase(p.name).snakeCase);
+
+  if (principalKind) {
+    argumentNames.unshift("principal");
+  }
+
+  const optionalParameters = getOptionalParameters(op);
+
+  let methodName = operationNameCase.snakeCase;
+
+  if (optionalParameters.length > 0) {
+    // prettier-ignore
+    yield `  let options = ${emitOperationOptionsReference(ctx, op, cursor)} {`;
+    for (const param of optionalParameters) {
+      yield `    ${parseCase(param.name).snakeCase},`;
+    }
+    yield "  };";
+    yield "";
+
+    argumentNames.push("options");
+    methodName += "_with_options";
   }
 
   yield `  let result = operations`;
   // prettier-ignore
-  yield `    .${operationNameCase.snakeCase}(${parameters.map((p) => parseCase(p.name).snakeCase)})`;
-  yield "    .await";
-  yield `    .map_err(${referenceHostPath(
-    "http",
-    "ServerError",
-    "Operation"
-  )})?;`;
+  yield `    .${methodName}(${argumentNames.join(", ")})`;
+  yield "    .await;";
+  yield "";
 
+  // prettier-ignore
+  yield `  let accept = ${referenceHostPath("http", "accept", "Accept", "from_parts")}(&parts);`;
   yield "";
 
-  yield `  ${referenceHostPath("http", "Responder", "to_response")}(result)`;
+  // Declared errors are sent as their modeled responses. Anything else the implementation returns is a server failure.
+  yield `  match result {`;
+  yield `    Ok(result) => ${toResponse}(result, &accept),`;
+  // prettier-ignore
+  yield `    Err(error) => match ${referenceHostPath("IntoOperationError", "into_operation_error")}(error) {`;
+  yield `      Ok(error) => ${toResponse}(error, &accept),`;
+  // prettier-ignore
+  yield `      Err(error) => Err(${referenceHostPath("http", "ServerError", "Operation")}(error)),`;
+  yield `    },`;
+  yield `  }`;
 
   yield "}";
 
@@ -1323,7 +2117,13 @@ function* emitRawServerOperation(
     if (split.target) {
       const isBodyType =
         !Array.isArray(split.target) && responsesHaveBodyType(split.target);
-      impl(ctx, "Responder", split.target, isBodyType);
+      impl(
+        ctx,
+        "Responder",
+        split.target,
+        isBodyType,
+        split.kind === "union" ? split.variants : undefined
+      );
     }
     switch (split.kind) {
       case "ordinary":
@@ -1365,8 +2165,84 @@ function* emitHeaderParamBinding(
   if (!parameter.param.optional) {
     yield `let Some(${nameCase.snakeCase}) = ${nameCase.snakeCase} else {`;
     // prettier-ignore
-    yield `  return Err(${referenceHostPath("http", "ServerError", "InvalidRequest")});`;
+    yield `  return Err(${referenceHostPath("http", "ServerError", "MissingParameter")}(${JSON.stringify(parameter.name)}));`;
     yield "};";
     yield "";
   }
+
+  yield* emitParameterDecoding(ctx, parameter);
+}
+
+/**
+ * Decodes the text of a header or query parameter that has an `@encode` encoding into its value.
+ */
+function* emitParameterDecoding(
+  ctx: HttpContext,
+  parameter: Extract<HttpOperationParameter, { type: "header" | "query" }>
+): Iterable<string> {
+  const adapter = getEncodingAdapter(ctx, parameter.param);
+
+  const name = parseCase(parameter.param.name).snakeCase;
+
+  if (!adapter) {
+    yield* emitParameterConversion(ctx, parameter);
+
+    return;
+  }
+
+  // prettier-ignore
+  const decode = `${referenceHostPath("http", "decode_parameter")}::<${adapter}, _, _, _>`;
+
+  if (parameter.param.optional) {
+    // prettier-ignore
+    yield `let ${name} = ${name}.as_deref().map(|${name}| ${decode}(${JSON.stringify(parameter.name)}, ${name})).transpose()?;`;
+  } else {
+    // Header values are bound as `&str`, while query values are bound as the `Cow<str>` that the query parser yields.
+    const text = parameter.type === "header" ? name : `&${name}`;
+
+    // prettier-ignore
+    yield `let ${name} = ${decode}(${JSON.stringify(parameter.name)}, ${text})?;`;
+  }
+  yield "";
+}
+
+/**
+ * Converts the text of a header or query parameter without an encoding into the value that the operation takes,
+ * parsing it unless it is a string. Optional parameters are passed in the options struct of the operation, which owns
+ * their values.
+ */
+function* emitParameterConversion(
+  ctx: HttpContext,
+  parameter: Extract<HttpOperationParameter, { type: "header" | "query" }>
+): Iterable<string> {
+  const { param } = parameter;
+
+  const name = parseCase(param.name).snakeCase;
+
+  if (param.type.kind !== "Scalar") {
+    throw new UnimplementedError(
+      `${parameter.type} parameter of kind '${param.type.kind}'`
+    );
+  }
+
+  const isString =
+    getRustScalar(ctx.program, param.type, param).owned === "String";
+
+  if (param.optional) {
+    if (isString) {
+      yield `let ${name} = ${name}.map(|${name}| ${name}.to_string());`;
+    } else {
+      // prettier-ignore
+      const parse = `${referenceHostPath("http", "parse_parameter")}::<_, _, _>(${JSON.stringify(parameter.name)}, ${JSON.stringify(param.type.name)}, &${name})`;
+
+      yield `let ${name} = ${name}.map(|${name}| ${parse}).transpose()?;`;
+    }
+  } else if (!isString) {
+    // Header values are bound as `&str`, while query values are bound as the `Cow<str>` that the query parser yields.
+    const text = parameter.type === "header" ? name : `&${name}`;
+
+    // prettier-ignore
+    yield `let ${name} = ${referenceHostPath("http", "parse_parameter")}::<_, _, _>(${JSON.stringify(parameter.name)}, ${JSON.stringify(param.type.name)}, ${text})?;`;
+    yield "";
+  }
 }
@@ -0,0 +1,231 @@
+import {
+  Model,
+  ModelProperty,
+  Type,
+  isArrayModelType,
+} from "@typespec/compiler";
+import { getHttpPart } from "@typespec/http";
+import { PathCursor, RustContext } from "../ctx.js";
+import { emitTypeReference, isValueLiteralType } from "../common/reference.js";
+import { getRustScalar } from "../common/scalar.js";
+import { KEYWORDS } from "../common/keywords.js";
+import { parseCase } from "../util/case.js";
+import { UnimplementedError } from "../util/error.js";
+import { getFullyQualifiedTypeName } from "../util/name.js";
+import {
+  referenceHostPath,
+  referenceVendoredHostPath,
+} from "../util/vendored.js";
+
+/**
+ * How the value of a part is encoded in the body.
+ */
+type PartEncoding = "file" | "text" | "binary" | "json";
+
+/**
+ * A part of a multipart body, read from a property of the body model.
+ */
+interface MultipartField {
+  /** The name of the part in the body. */
+  partName: string;
+  /** The name of the Rust field. */
+  rustName: string;
+  optional: boolean;
+  /** Whether the part may be repeated, in which case the field holds every value sent. */
+  multi: boolean;
+  encoding: PartEncoding;
+}
+
+/**
+ * Emits the `ToMultipart` and `FromMultipart` impls of the struct generated for a multipart body model.
+ */
+export function* emitMultipartImpls(
+  ctx: RustContext,
+  model: Model,
+  cursor: PathCursor
+): Iterable<string> {
+  const typeReference = emitTypeReference(
+    ctx,
+    model,
+    model,
+    "owned",
+    cursor,
+    "**unreachable**"
+  );
+
+  const fields = [...model.properties.values()]
+    .filter((property) => !isValueLiteralType(property.type))
+    .map((property) => getMultipartField(ctx, property));
+
+  const multipart = (...path: string[]) =>
+    referenceHostPath("http", "multipart", ...path);
+
+  yield `impl ${multipart("ToMultipart")} for ${typeReference} {`;
+  // prettier-ignore
+  yield `  fn to_multipart(&self, ${fields.length > 0 ? "form" : "_"}: &mut ${multipart("Form")}) -> Result<(), ${referenceHostPath("http", "codec", "CodecError")}> {`;
+  for (const field of fields) {
+    const value = `&self.${field.rustName}`;
+
+    if (field.multi) {
+      yield field.optional
+        ? `    for value in self.${field.rustName}.iter().flatten() {`
+        : `    for value in ${value} {`;
+      yield `      ${emitPartEncoder(field, "value")}`;
+      yield "    }";
+    } else if (field.optional) {
+      yield `    if let Some(value) = ${value} {`;
+      yield `      ${emitPartEncoder(field, "value")}`;
+      yield "    }";
+    } else {
+      yield `    ${emitPartEncoder(field, value)}`;
+    }
+  }
+  if (fields.length > 0) yield "";
+  yield "    Ok(())";
+  yield "  }";
+  yield "}";
+  yield "";
+
+  // Parts may arrive in any order, so each is collected into a local named after its field until the body ends.
+  const locals = new Map(
+    fields.map((field) => [
+      field,
+      field.rustName === "part" || field.rustName === "reader"
+        ? `${field.rustName}_`
+        : field.rustName,
+    ])
+  );
+
+  yield `impl ${multipart("FromMultipart")} for ${typeReference} {`;
+  yield "  async fn from_multipart<B>(";
+  yield `    reader: &mut ${multipart("MultipartReader")}<B>,`;
+  yield `  ) -> Result<Self, ${multipart("ReadError")}<B::Error>>`;
+  yield "  where";
+  yield `    B: ${referenceVendoredHostPath("http_body", "Body")} + Send,`;
+  yield "  {";
+  for (const [field, local] of locals) {
+    yield field.multi
+      ? `    let mut ${local} = Vec::new();`
+      : `    let mut ${local} = None;`;
+  }
+  if (fields.length > 0) yield "";
+  yield "    while let Some(part) = reader.next_part().await? {";
+  if (fields.length > 0) {
+    yield "      match part.name() {";
+    for (const [field, local] of locals) {
+      const decoded = emitPartDecoder(field);
+      // prettier-ignore
+      yield field.multi
+        ? `        Some(${JSON.stringify(field.partName)}) => ${local}.push(${decoded}),`
+        : `        Some(${JSON.stringify(field.partName)}) => ${local} = Some(${decoded}),`;
+    }
+    yield "        _ => {}";
+    yield "      }";
+  } else {
+    yield "      let _ = part;";
+  }
+  yield "    }";
+  yield "";
+  yield "    Ok(Self {";
+  for (const [field, local] of locals) {
+    const name = field.rustName;
+
+    if (field.multi && field.optional) {
+      yield `      ${name}: (!${local}.is_empty()).then_some(${local}),`;
+    } else if (field.multi || field.optional) {
+      yield local === name ? `      ${name},` : `      ${name}: ${local},`;
+    } else {
+      // prettier-ignore
+      yield `      ${name}: ${local}.ok_or(${multipart("MultipartError", "MissingPart")}(${JSON.stringify(field.partName)}))?,`;
+    }
+  }
+  yield "    })";
+  yield "  }";
+  yield "}";
+  yield "";
+}
+
+function getMultipartField(
+  ctx: RustContext,
+  property: ModelProperty
+): MultipartField {
+  let type: Type = property.type;
+  let multi = false;
+
+  if (type.kind === "Model" && isArrayModelType(ctx.program, type)) {
+    const element = type.templateMapper!.args[0];
+
+    // Only arrays of parts are repeated. An array that is the value of a single part is sent as JSON.
+    if (getHttpPart(ctx.program, element)) {
+      type = element;
+      multi = true;
+    }
+  }
+
+  const part = getHttpPart(ctx.program, type);
+
+  // Bodies declared with `@body` rather than `@multipartBody` send each property as a part of the same name.
+  const partName = part?.options.name ?? property.name;
+  const fieldName = parseCase(property.name).snakeCase;
+
+  return {
+    partName,
+    rustName: KEYWORDS.has(fieldName) ? `r#${fieldName}` : fieldName,
+    optional: property.optional,
+    multi,
+    encoding: getPartEncoding(ctx, part?.type ?? type),
+  };
+}
+
+function getPartEncoding(ctx: RustContext, type: Type): PartEncoding {
+  switch (type.kind) {
+    case "Model":
+      if (getFullyQualifiedTypeName(type) === "TypeSpec.Http.File") {
+        return "file";
+      }
+      return "json";
+    case "Scalar": {
+      const { owned } = getRustScalar(ctx.program, type, type);
+
+      if (owned === "String") return "text";
+      if (owned === "Vec<u8>") return "binary";
+
+      return "json";
+    }
+    case "Union":
+    case "Enum":
+    case "Intrinsic":
+      return "json";
+    default:
+      throw new UnimplementedError(`multipart part of kind '${type.kind}'`);
+  }
+}
+
+function emitPartEncoder(field: MultipartField, value: string): string {
+  const name = JSON.stringify(field.partName);
+
+  switch (field.encoding) {
+    case "file":
+      return `form.file(${name}, ${value})?;`;
+    case "text":
+      return `form.text(${name}, ${value});`;
+    case "binary":
+      // prettier-ignore
+      return `form.binary(${name}, ${referenceVendoredHostPath("bytes", "Bytes", "copy_from_slice")}(${value}));`;
+    case "json":
+      return `form.json(${name}, ${value})?;`;
+  }
+}
+
+function emitPartDecoder(field: MultipartField): string {
+  switch (field.encoding) {
+    case "file":
+      return "part.into_file()";
+    case "text":
+      return "part.text()?";
+    case "binary":
+      return "part.into_contents().to_vec()";
+    case "json":
+      return "part.json()?";
+  }
+}
@@ -12,6 +12,11 @@ import {
 } from "../util/vendored.js";
 import { indent } from "../util/indent.js";
 import { utf8Length } from "../util/utf8.js";
+import {
+  formatAuthOptions,
+  getAuthChallenges,
+  getPrincipalKind,
+} from "./auth.js";
 
 export function emitRouter(ctx: HttpContext, service: HttpService) {
   const routerModule: Module = {
@@ -47,6 +52,11 @@ function* emitRouterDefinition(
 
   const backends = new Map<OperationContainer, [ReCase, string]>();
 
+  const authenticatorTrait = cursor.pathTo(
+    ctx.httpModule.cursor.enter("auth"),
+    "Authenticator"
+  );
+
   for (const container of uniqueContainers) {
     const param = parseCase(container.name);
 
@@ -65,44 +75,84 @@ function* emitRouterDefinition(
             "**unreachable**"
           );
 
-    backends.set(container, [param, traitConstraint]);
+    // Backends receive the principal that the authenticator identified, so their principal types must agree.
+    const receivesPrincipal = service.operations.some(
+      (operation) =>
+        operation.container === container &&
+        getPrincipalKind(ctx, operation.operation)
+    );
+
+    backends.set(container, [
+      param,
+      ctx.auth && receivesPrincipal
+        ? `${traitConstraint}<Principal = <Authenticator as ${authenticatorTrait}>::Principal>`
+        : traitConstraint,
+    ]);
+  }
+
+  // The router holds the authenticator alongside the backends, so it is threaded through the same generic parameters.
+  const members = [...backends.values()];
+
+  if (ctx.auth) {
+    members.push([parseCase("Authenticator"), authenticatorTrait]);
   }
 
-  const genericParams = [...backends.values()]
+  const genericParams = members
     .map(
       ([param, traitConstraint]) =>
         `${param.pascalCase}: ${traitConstraint} + Clone`
     )
     .join(", ");
 
-  const genericArgs = [...backends.values()]
+  const genericArgs = members
     .map(([param]) => param.pascalCase)
     .join(", ");
 
-  const fieldNames = [...backends.values()].map(([param]) => param.snakeCase);
+  const fieldNames = members.map(([param]) => param.snakeCase);
 
-  const instantiationParams = [...backends.values()]
+  const instantiationParams = members
     .map(([param]) => `${param.snakeCase}: ${param.pascalCase}`)
     .join(", ");
 
+  const errorHandlerTrait = referenceHostPath("http", "ErrorHandler");
+  const defaultErrorHandler = referenceHostPath("http", "DefaultErrorHandler");
+
   yield "#[derive(Clone)]";
-  yield `pub struct ${routerName}<${genericParams}> {`;
+  yield `pub struct ${routerName}<${genericParams}, ErrorHandler: ${errorHandlerTrait} = ${defaultErrorHandler}> {`;
 
-  for (const [param] of backends.values()) {
+  for (const [param] of members) {
     yield `  ${param.snakeCase}: ${param.pascalCase},`;
   }
 
+  yield `  error_handler: ErrorHandler,`;
+
   yield "}";
   yield "";
 
   yield `impl<${genericParams}> ${routerName}<${genericArgs}> {`;
   yield `  pub fn new(${instantiationParams}) -> Self {`;
-  yield `    Self { ${fieldNames.join(", ")} }`;
+  yield `    Self { ${fieldNames.join(", ")}, error_handler: ${defaultErrorHandler} }`;
   yield "  }";
   yield "}";
   yield "";
 
-  const serviceGenericParams = [...backends.values()]
+  yield `impl<${genericParams}, ErrorHandler: ${errorHandlerTrait}> ${routerName}<${genericArgs}, ErrorHandler> {`;
+  yield `  /// Replaces the handler that converts errors raised while serving a request into responses.`;
+  yield `  pub fn with_error_handler<NewErrorHandler: ${errorHandlerTrait}>(`;
+  yield `    self,`;
+  yield `    error_handler: NewErrorHandler,`;
+  yield `  ) -> ${routerName}<${genericArgs}, NewErrorHandler> {`;
+  yield `    ${routerName} {`;
+  for (const fieldName of fieldNames) {
+    yield `      ${fieldName}: self.${fieldName},`;
+  }
+  yield `      error_handler,`;
+  yield `    }`;
+  yield "  }";
+  yield "}";
+  yield "";
+
+  const serviceGenericParams = members
     .map(
       ([param, traitConstraint]) =>
         `${param.pascalCase}: ${traitConstraint} + Clone + Send`
@@ -111,6 +161,7 @@ function* emitRouterDefinition(
 
   yield "impl<";
   yield `  ${serviceGenericParams},`;
+  yield `  ErrorHandler: ${errorHandlerTrait},`;
   yield `  RequestBody: ${referenceVendoredHostPath(
     "http_body",
     "Body"
@@ -121,12 +172,12 @@ function* emitRouterDefinition(
   )}<${referenceVendoredHostPath(
     "http",
     "Request"
-  )}<RequestBody>> for ${routerName}<${genericArgs}>`;
+  )}<RequestBody>> for ${routerName}<${genericArgs}, ErrorHandler>`;
   yield `where`;
   yield `  <RequestBody as ${referenceVendoredHostPath(
     "http_body",
     "Body"
-  )}>::Error: std::error::Error + Send + Sync,`;
+  )}>::Error: std::error::Error + Send + Sync + 'static,`;
   yield `  <RequestBody as ${referenceVendoredHostPath(
     "http_body",
     "Body"
@@ -137,8 +188,7 @@ function* emitRouterDefinition(
     "Response"
   )}<${referenceHostPath("http", "Body")}>;`;
   yield "";
-  // TODO: obviously wrong. need to figure out an error type for the router impl and remove the subsequent panics
-  yield `  type Error = core::convert::Infallible;`;
+  yield `  type Error = ${referenceHostPath("http", "RouterError")};`;
   yield "";
   yield `  type Future = impl core::future::Future<Output = Result<Self::Response, Self::Error>> + Send;`;
   yield "";
@@ -175,13 +225,21 @@ function* emitRouterDefinition(
 
   yield "";
 
-  yield `      panic!("Not found.");`;
+  yield `      Ok(${notFound()})`;
 
   yield "    }";
   yield "  }";
   yield "}";
 }
 
+function notFound(): string {
+  return `${referenceHostPath(
+    "http",
+    "RouterError",
+    "NotFound"
+  )}.into_response()`;
+}
+
 function* emitRouteHandler(
   ctx: HttpContext,
   routeTree: RouteTree,
@@ -196,15 +254,13 @@ function* emitRouteHandler(
       emitRouteOperationDispatch(ctx, routeTree.operations, backends, cursor)
     );
   } else {
-    // Not found
-    yield `  panic!("Not found.");`;
+    yield `  return Ok(${notFound()});`;
   }
   yield `}`;
 
   if (mustTerminate) {
-    // Not found
     yield "else {";
-    yield `  panic!("Not found.");`;
+    yield `  return Ok(${notFound()});`;
     yield `}`;
     return;
   }
@@ -248,27 +304,62 @@ function* emitRouteOperationDispatch(
 
     const backendMemberName = backend.snakeCase;
 
+    const principalKind = getPrincipalKind(ctx, operation.operation);
+
+    const argumentNames = operation.parameters.map(
+      (param) => parseCase(param.name).snakeCase
+    );
+
+    if (principalKind) {
+      argumentNames.unshift("principal");
+    }
+
     const parameters =
-      operation.parameters.length > 0
-        ? ", " +
-          operation.parameters
-            .map((param) => parseCase(param.name).snakeCase)
-            .join(", ")
-        : "";
+      argumentNames.length > 0 ? ", " + argumentNames.join(", ") : "";
 
     yield `  ${referenceVendoredHostPath(
       "http",
       "Method",
       operation.verb.toUpperCase()
     )} => {`;
-    // TODO: unwrap bad :(
-    yield `    return Ok(server_raw::${operationName}(router.${backendMemberName}, req${parameters}).await.unwrap())`;
+    if (principalKind) {
+      const authModule = ctx.httpModule.cursor.enter("auth");
+      const options = formatAuthOptions(
+        ctx,
+        operation.operation,
+        cursor.pathTo(authModule, "Scheme")
+      );
+
+      // prettier-ignore
+      const authenticate = `${cursor.pathTo(authModule, "authenticate")}(&router.authenticator, &req, ${options}).await.map(|authenticated| authenticated.principal)`;
+
+      if (principalKind === "required") {
+        const challenges = getAuthChallenges(ctx, operation.operation)
+          .map((challenge) => JSON.stringify(challenge))
+          .join(", ");
+
+        yield `    let Some(principal) = ${authenticate} else {`;
+        // prettier-ignore
+        yield `      return Ok(${referenceHostPath("http", "RouterError", "Unauthorized")}(vec![${challenges}]).into_response());`;
+        yield "    };";
+      } else {
+        yield `    let principal = ${authenticate};`;
+      }
+      yield "";
+    }
+    yield `    return server_raw::${operationName}(router.${backendMemberName}, req${parameters})`;
+    yield `      .await`;
+    yield `      .or_else(|e| router.error_handler.handle_error(e));`;
     yield `  },`;
   }
 
-  // TODO: panic bad :(
+  const allowedMethods = operations.map((operation) =>
+    referenceVendoredHostPath("http", "Method", operation.verb.toUpperCase())
+  );
+
   yield `  _ => {`;
-  yield `    panic!("No such method.");`;
+  // prettier-ignore
+  yield `    return Ok(${referenceHostPath("http", "RouterError", "MethodNotAllowed")}(vec![${allowedMethods.join(", ")}]).into_response());`;
   yield `  },`;
 
   yield "}";
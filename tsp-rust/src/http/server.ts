@@ -1,97 +1,211 @@
 import { HttpServer } from "@typespec/http";
-import { RustContext, createPathCursor } from "../ctx.js";
-import { Type } from "@typespec/compiler";
+import { PathCursor, RustContext } from "../ctx.js";
+import { IntrinsicType, ModelProperty } from "@typespec/compiler";
 import { parseCase } from "../util/case.js";
-import { emitTypeReference } from "../common/reference.js";
-import { referenceVendoredHostPath } from "../util/vendored.js";
-import { AuthCode } from "./auth.js";
+import {
+  RustTypeSpecLiteralType,
+  emitTypeReference,
+  isValueLiteralType,
+} from "../common/reference.js";
+import { referenceHostPath } from "../util/vendored.js";
+import { emitDocumentation } from "../common/documentation.js";
+import { getRustLiteralTypeAndValue } from "../common/model.js";
+import { KEYWORDS } from "../common/keywords.js";
+import { indent } from "../util/indent.js";
 
-// TODO: whole module is dead code
+/**
+ * A server declared with `@server`, along with the name of the struct that configures it.
+ */
+export interface ServerConfig {
+  server: HttpServer;
+  name: string;
+}
+
+const TEMPLATE_PARAMETER = /{([^}]+)}/g;
 
-export interface ServerDescription {
-  ctxLines: string[];
+/**
+ * Gets the configuration structs for the servers of a service. A service with a single server gets a struct named
+ * after the service. Otherwise, the structs are numbered in declaration order.
+ */
+export function getServerConfigs(
+  servers: HttpServer[],
+  serviceName: string
+): ServerConfig[] {
+  return servers.map((server, index) => ({
+    server,
+    name:
+      servers.length === 1
+        ? serviceName + "Server"
+        : serviceName + "Server" + (index + 1),
+  }));
 }
 
-export interface ServerQueryParam {
-  name: string;
-  rustExpression: string;
-  type: Type;
+/**
+ * Gets the URL of the first server that can be resolved without any arguments, if there is one.
+ */
+export function getDefaultServerUrl(servers: HttpServer[]): string | undefined {
+  for (const server of servers) {
+    const defaults = new Map<string, string>();
+
+    for (const [name, param] of server.parameters) {
+      const defaultValue = getParameterDefault(param);
+
+      if (defaultValue === undefined) break;
+
+      defaults.set(name, String(defaultValue.value));
+    }
+
+    if (defaults.size === server.parameters.size) {
+      return server.url.replace(
+        TEMPLATE_PARAMETER,
+        (_, name: string, offset: number) =>
+          isPathParameter(server.url.slice(0, offset))
+            ? encodeURIComponent(defaults.get(name)!)
+            : defaults.get(name)!
+      );
+    }
+  }
+
+  return undefined;
 }
 
-export function getServerDescription(
+/**
+ * Emits a struct with one field per template parameter of a server, and its impl of `tsp_rust::http::ServerConfig`.
+ */
+export function* emitServerConfig(
   ctx: RustContext,
-  serverInfo: HttpServer | undefined,
-  auth: AuthCode
-): ServerDescription {
-  const [baseUrlTemplate, queryTemplate] = serverInfo?.url?.split("?", 2) ?? [
-    "{base_url}",
-  ];
-
-  const baseUrlArgSequence = (baseUrlTemplate.match(/{([^}]+)}/g) ?? []).map(
-    function (part) {
-      return `self.${part.slice(1, -1)}`;
+  { server, name: structName }: ServerConfig,
+  cursor: PathCursor
+): Iterable<string> {
+  const params = [...server.parameters.values()].map((param) => {
+    const nameCase = parseCase(param.name);
+
+    const typeReference = emitTypeReference(
+      ctx,
+      param.type,
+      param,
+      "owned",
+      cursor,
+      structName + nameCase.pascalCase
+    );
+
+    const defaultValue = getParameterDefault(param);
+
+    return {
+      param,
+      name: getFieldName(param.name),
+      typeReference,
+      defaultValue:
+        defaultValue && getRustLiteralTypeAndValue(defaultValue)[1],
+      isString: defaultValue?.kind === "String",
+    };
+  });
+
+  const required = params.filter((param) => param.defaultValue === undefined);
+
+  if (server.description) {
+    yield `/// ${server.description}`;
+    yield "///";
+  }
+  yield `/// Resolves the server URL \`${server.url}\`.`;
+
+  if (params.length === 0) {
+    yield "#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]";
+    yield `pub struct ${structName};`;
+  } else {
+    yield "#[derive(Debug, Clone, PartialEq)]";
+    yield `pub struct ${structName} {`;
+    for (const { param, name, typeReference } of params) {
+      yield* indent(emitDocumentation(ctx, param));
+      yield `  pub ${name}: ${typeReference},`;
     }
-  );
+    yield "}";
+    yield "";
+
+    const constructorParams = required
+      .map(({ name, typeReference }) => `${name}: ${typeReference}`)
+      .join(", ");
 
-  const queryArgSequence = (queryTemplate?.match(/{([^}]+)}/g) ?? []).map(
-    function (part) {
-      return `self.${part.slice(1, -1)}`;
+    yield `impl ${structName} {`;
+    yield `  pub fn new(${constructorParams}) -> Self {`;
+    yield "    Self {";
+    for (const { name, defaultValue, isString } of params) {
+      if (defaultValue === undefined) {
+        yield `      ${name},`;
+      } else {
+        yield `      ${name}: ${defaultValue}${isString ? ".into()" : ""},`;
+      }
+    }
+    yield "    }";
+    yield "  }";
+    yield "}";
+
+    if (required.length === 0) {
+      yield "";
+      yield `impl Default for ${structName} {`;
+      yield "  fn default() -> Self {";
+      yield "    Self::new()";
+      yield "  }";
+      yield "}";
+    }
+  }
+
+  yield "";
+
+  const formatArgs = [...server.url.matchAll(TEMPLATE_PARAMETER)].map(
+    ({ 1: paramName, index }) => {
+      const formatter = isPathParameter(server.url.slice(0, index))
+        ? "format_server_path_parameter"
+        : "format_server_parameter";
+
+      // prettier-ignore
+      return `${referenceHostPath("http", formatter)}(&self.${getFieldName(paramName)})`;
     }
   );
 
-  const cursor = createPathCursor("http");
-
-  const ctxParams: { name: string; type: string }[] =
-    serverInfo !== undefined
-      ? [...serverInfo.parameters.values()].map(function (param) {
-          const nameCase = parseCase(param.name);
-
-          return {
-            name: nameCase.snakeCase,
-            type: emitTypeReference(
-              ctx,
-              param.type,
-              param,
-              "owned",
-              cursor,
-              ctx.contextTypeName + nameCase.pascalCase
-            ),
-          };
-        })
-      : [{ name: "base_url", type: "String" }];
-
-  const ctxLines = [
-    `pub struct ${ctx.contextTypeName} {`,
-    `  client: ${referenceVendoredHostPath("reqwest", "Client")}`,
-    ...ctxParams.map(function ({ name, type }) {
-      return `  ${name}: ${type},`;
-    }),
-    "}",
-    "",
-    `impl ${ctx.contextTypeName} {`,
-    // prettier-ignore
-    `  pub fn new(${ctxParams.map(({ name, type }) => `${name}: ${type}`).join(", ")}) -> Self {`,
-    `    Self {`,
-    `      client: ${referenceVendoredHostPath("reqwest", "Client")}::new(),`,
-    ...ctxParams.map(function ({ name }) {
-      return `      ${name},`;
-    }),
-    `    }`,
-    `  }`,
-    "",
-    "  pub fn base_url(&self) -> String {",
-    // prettier-ignore
-    `    format!(${JSON.stringify(baseUrlTemplate)}, ${baseUrlArgSequence.join(", ")})`,
-    "  }",
-    "",
-    "  pub fn query(&self) -> String {",
+  const formatString = server.url.replace(TEMPLATE_PARAMETER, "{}");
+
+  yield `impl ${referenceHostPath("http", "ServerConfig")} for ${structName} {`;
+  yield "  fn url(&self) -> String {";
+  if (formatArgs.length === 0) {
+    yield `    String::from(${JSON.stringify(server.url)})`;
+  } else {
     // prettier-ignore
-    `    format!(${JSON.stringify(queryTemplate)}, ${queryArgSequence.join(", ")})`,
-    "  }",
-    "}",
-  ];
-
-  return {
-    ctxLines,
-  };
+    yield `    format!(${JSON.stringify(formatString)}, ${formatArgs.join(", ")})`;
+  }
+  yield "  }";
+  yield "}";
+  yield "";
+}
+
+/**
+ * Determines whether a template parameter is part of the path of a server URL, given the part of the URL before it.
+ * Parameters in the scheme or authority, like the `{endpoint}` of `{endpoint}/api`, may hold a whole base URL, so only
+ * those in the path are percent-encoded.
+ */
+function isPathParameter(prefix: string): boolean {
+  const authorityStart = prefix.indexOf("://");
+
+  const afterScheme =
+    authorityStart === -1 ? prefix : prefix.slice(authorityStart + 3);
+
+  return afterScheme.includes("/");
+}
+
+function getFieldName(paramName: string): string {
+  const name = parseCase(paramName).snakeCase;
+
+  return KEYWORDS.has(name) ? `r#${name}` : name;
+}
+
+function getParameterDefault(
+  param: ModelProperty
+): Exclude<RustTypeSpecLiteralType, IntrinsicType> | undefined {
+  const defaultValue = param.default;
+
+  return defaultValue &&
+    isValueLiteralType(defaultValue) &&
+    defaultValue.kind !== "Intrinsic"
+    ? defaultValue
+    : undefined;
 }
@@ -0,0 +1,91 @@
+import {
+  DiagnosticTarget,
+  Model,
+  ModelProperty,
+  NoTarget,
+} from "@typespec/compiler";
+import { isBody, isContentTypeHeader, isHeader } from "@typespec/http";
+import { RustContext } from "../ctx.js";
+import { getRustScalar } from "../common/scalar.js";
+
+export const DEFAULT_CONTENT_TYPE = "application/json";
+export const DEFAULT_BINARY_CONTENT_TYPE = "application/octet-stream";
+export const MULTIPART_CONTENT_TYPE = "multipart/form-data";
+
+/**
+ * The content types that bodies are serialized with, rather than streamed when they are `bytes`.
+ */
+export const SERIALIZED_CONTENT_TYPES = new Set([
+  DEFAULT_CONTENT_TYPE,
+  "application/merge-patch+json",
+  MULTIPART_CONTENT_TYPE,
+]);
+
+/**
+ * Gets the content type that a response model declares with a literal `Content-Type` header, if any.
+ */
+export function getDeclaredContentType(
+  ctx: RustContext,
+  model: Model
+): string | undefined {
+  for (const property of model.properties.values()) {
+    if (
+      isHeader(ctx.program, property) &&
+      isContentTypeHeader(ctx.program, property) &&
+      property.type.kind === "String"
+    ) {
+      return property.type.value;
+    }
+  }
+
+  return undefined;
+}
+
+/**
+ * Whether a property is the `bytes` body of a model sent with a binary content type, which is held as a `ByteStream`
+ * so that responses are streamed like request bodies are.
+ *
+ * A model that declares a choice of content types serializes its body with the negotiated one, so its body is not
+ * streamed.
+ */
+export function isStreamedBodyProperty(
+  ctx: RustContext,
+  position: DiagnosticTarget | typeof NoTarget
+): position is ModelProperty {
+  if (
+    typeof position !== "object" ||
+    !("kind" in position) ||
+    position.kind !== "ModelProperty" ||
+    position.optional ||
+    position.type.kind !== "Scalar" ||
+    !position.model ||
+    !isBody(ctx.program, position) ||
+    getRustScalar(ctx.program, position.type, position).owned !== "Vec<u8>"
+  ) {
+    return false;
+  }
+
+  const contentTypeProperty = [...position.model.properties.values()].find(
+    (property) =>
+      isHeader(ctx.program, property) &&
+      isContentTypeHeader(ctx.program, property)
+  );
+
+  if (contentTypeProperty && contentTypeProperty.type.kind !== "String") {
+    return false;
+  }
+
+  const contentType =
+    getDeclaredContentType(ctx, position.model) ?? DEFAULT_BINARY_CONTENT_TYPE;
+
+  return !SERIALIZED_CONTENT_TYPES.has(contentType);
+}
+
+/**
+ * Whether a model holds a streamed body, which can be neither cloned nor compared.
+ */
+export function hasStreamedBody(ctx: RustContext, model: Model): boolean {
+  return [...model.properties.values()].some((property) =>
+    isStreamedBodyProperty(ctx, property)
+  );
+}
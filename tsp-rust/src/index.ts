@@ -103,7 +103,6 @@ export async function $onEmit(context: EmitContext<RustEmitterOptions>) {
     service,
     // old http stuff
     httpService: undefined as any,
-    authenticationInfo: undefined as any,
     serviceTitle: service.title,
     serviceVersion: service.version,
 
@@ -111,11 +110,10 @@ export async function $onEmit(context: EmitContext<RustEmitterOptions>) {
     errorTypeName: "Error",
 
     typeQueue: createOnceQueue(),
+    patchQueue: createOnceQueue(),
     synthetics: [],
     syntheticNames: new Map(),
 
-    options: [],
-
     rootModule,
     baseNamespace: service.type,
     namespaceModules: new Map([[globalNamespace, allModule]]),
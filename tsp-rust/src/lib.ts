@@ -54,6 +54,36 @@ export const $lib = createTypeSpecLibrary({
         default: paramMessage`unrecognized scalar '${"scalar"}'`,
       },
     },
+    "unsupported-auth-scheme": {
+      severity: "warning",
+      messages: {
+        default: paramMessage`authentication scheme '${"scheme"}' of type '${"type"}' is not supported, so requests to operations that require it are rejected`,
+      },
+    },
+    "invalid-token-url": {
+      severity: "error",
+      messages: {
+        default: paramMessage`token URL '${"url"}' of authentication scheme '${"scheme"}' is not an absolute http or https URL`,
+      },
+    },
+    "invalid-constraint": {
+      severity: "error",
+      messages: {
+        default: paramMessage`constraint value '${"value"}' cannot be represented by type '${"type"}'`,
+      },
+    },
+    "invalid-enum-value": {
+      severity: "error",
+      messages: {
+        default: paramMessage`enum value '${"value"}' cannot be represented by type '${"type"}'`,
+      },
+    },
+    "unsupported-pattern": {
+      severity: "error",
+      messages: {
+        default: paramMessage`pattern '${"pattern"}' is not supported by the Rust regex crate`,
+      },
+    },
     "unrecognized-encoding": {
       severity: "error",
       messages: {
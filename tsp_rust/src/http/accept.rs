@@ -0,0 +1,220 @@
+//! Response content negotiation.
+//!
+//! Operations whose responses declare several content types, with a `Content-Type` header that is a union of literal
+//! media types, send the one that the request's `Accept` header prefers. Generated servers parse the header into an
+//! [`Accept`] and pass it to [`Responder::to_response`](super::Responder::to_response), and generated clients send an
+//! `Accept` header listing every content type that they can decode.
+
+use super::codec;
+
+/// The media ranges that a request accepts, with their quality values.
+///
+/// A request without an `Accept` header accepts any media type.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Accept {
+    ranges: Vec<MediaRange>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct MediaRange {
+    /// The essence of the range, which may be `*/*` or have a `*` subtype.
+    essence: String,
+    /// The quality value, between 0 and 1. A quality of 0 means that the range is not acceptable.
+    quality: f32,
+}
+
+impl MediaRange {
+    fn parse(range: &str) -> Option<Self> {
+        let mut parameters = range.split(';');
+
+        let essence = codec::essence(parameters.next()?);
+
+        let (kind, subtype) = essence.split_once('/')?;
+
+        if kind.is_empty() || subtype.is_empty() || (kind == "*" && subtype != "*") {
+            return None;
+        }
+
+        let mut quality = 1.0;
+
+        for parameter in parameters {
+            if let Some((name, value)) = parameter.split_once('=')
+                && name.trim().eq_ignore_ascii_case("q")
+            {
+                quality = value.trim().parse::<f32>().ok()?.clamp(0.0, 1.0);
+            }
+        }
+
+        Some(Self { essence, quality })
+    }
+
+    /// How specifically this range matches a media type essence, if it does: 2 for the exact type, 1 for a subtype
+    /// wildcard and 0 for `*/*`.
+    fn specificity(&self, essence: &str) -> Option<u8> {
+        if self.essence == essence {
+            return Some(2);
+        }
+
+        match self.essence.split_once('/') {
+            Some(("*", "*")) => Some(0),
+            Some((kind, "*")) if essence.split_once('/').is_some_and(|(k, _)| k == kind) => Some(1),
+            _ => None,
+        }
+    }
+}
+
+impl Accept {
+    /// Parses the `Accept` headers of a request. Media ranges that can't be parsed are ignored.
+    pub fn from_headers(headers: &http::HeaderMap) -> Self {
+        let ranges = headers
+            .get_all(http::header::ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(MediaRange::parse)
+            .collect();
+
+        Self { ranges }
+    }
+
+    /// Parses the `Accept` headers of the request with the given metadata.
+    pub fn from_parts(parts: &http::request::Parts) -> Self {
+        Self::from_headers(&parts.headers)
+    }
+
+    /// The quality with which a media type is accepted, taken from the most specific range that matches it.
+    ///
+    /// A media type that no range matches has a quality of 0.
+    pub fn quality(&self, media_type: &str) -> f32 {
+        if self.ranges.is_empty() {
+            return 1.0;
+        }
+
+        let essence = codec::essence(media_type);
+
+        self.ranges
+            .iter()
+            .filter_map(|range| Some((range.specificity(&essence)?, range.quality)))
+            .max_by_key(|(specificity, _)| *specificity)
+            .map_or(0.0, |(_, quality)| quality)
+    }
+
+    /// Chooses the offered media type with the highest quality, if any of them is acceptable.
+    ///
+    /// Media types offered earlier win ties, so offer them in order of preference.
+    pub fn negotiate<'a>(&self, offered: &[&'a str]) -> Option<&'a str> {
+        let mut best = None;
+
+        for &media_type in offered {
+            let quality = self.quality(media_type);
+
+            if quality > 0.0 && best.is_none_or(|(_, best_quality)| quality > best_quality) {
+                best = Some((media_type, quality));
+            }
+        }
+
+        best.map(|(media_type, _)| media_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accept(values: &[&'static str]) -> Accept {
+        let mut headers = http::HeaderMap::new();
+
+        for value in values {
+            headers.append(http::header::ACCEPT, http::HeaderValue::from_static(value));
+        }
+
+        Accept::from_headers(&headers)
+    }
+
+    #[test]
+    fn missing_or_empty_header_accepts_anything() {
+        for accept in [accept(&[]), accept(&[""])] {
+            assert_eq!(accept.quality("application/json"), 1.0);
+            assert_eq!(
+                accept.negotiate(&["text/plain", "application/json"]),
+                Some("text/plain")
+            );
+        }
+    }
+
+    #[test]
+    fn parses_quality_values() {
+        let accept = accept(&["text/plain;q=0.5, application/json; Q=0.8", "image/png;q=2"]);
+
+        assert_eq!(accept.quality("text/plain; charset=utf-8"), 0.5);
+        assert_eq!(accept.quality("Application/JSON"), 0.8);
+        assert_eq!(accept.quality("image/png"), 1.0);
+        assert_eq!(accept.quality("image/gif"), 0.0);
+    }
+
+    #[test]
+    fn invalid_quality_drops_range() {
+        let accept = accept(&["application/json;q=high, text/plain;q=0.1"]);
+
+        assert_eq!(accept.quality("application/json"), 0.0);
+        assert_eq!(
+            accept.negotiate(&["application/json", "text/plain"]),
+            Some("text/plain")
+        );
+    }
+
+    #[test]
+    fn invalid_ranges_are_ignored() {
+        let accept = accept(&["json, */json, text/, application/json;q=0.3"]);
+
+        assert_eq!(accept.quality("application/json"), 0.3);
+        assert_eq!(accept.quality("text/plain"), 0.0);
+    }
+
+    #[test]
+    fn most_specific_range_wins() {
+        let accept = accept(&["*/*;q=0.1, text/*;q=0.5, text/plain;q=0.9, image/*;q=0"]);
+
+        assert_eq!(accept.quality("text/plain"), 0.9);
+        assert_eq!(accept.quality("text/html"), 0.5);
+        assert_eq!(accept.quality("application/json"), 0.1);
+        assert_eq!(accept.quality("image/png"), 0.0);
+
+        assert_eq!(
+            accept.negotiate(&["image/png", "application/json", "text/html"]),
+            Some("text/html")
+        );
+    }
+
+    #[test]
+    fn negotiation_prefers_higher_quality() {
+        let accept = accept(&["text/plain;q=0.5, application/json"]);
+
+        assert_eq!(
+            accept.negotiate(&["text/plain", "application/json"]),
+            Some("application/json")
+        );
+    }
+
+    #[test]
+    fn ties_resolve_to_earlier_offer() {
+        let accept = accept(&["application/json, text/plain"]);
+
+        assert_eq!(
+            accept.negotiate(&["text/plain", "application/json"]),
+            Some("text/plain")
+        );
+        assert_eq!(
+            accept.negotiate(&["application/json", "text/plain"]),
+            Some("application/json")
+        );
+    }
+
+    #[test]
+    fn nothing_acceptable() {
+        let accept = accept(&["application/xml, text/plain;q=0"]);
+
+        assert_eq!(accept.negotiate(&["text/plain", "application/json"]), None);
+        assert_eq!(accept.negotiate(&[]), None);
+    }
+}
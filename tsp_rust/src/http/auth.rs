@@ -0,0 +1,680 @@
+//! Authentication of requests with the schemes declared by `@useAuth`.
+//!
+//! Clients authenticate their requests by wrapping their service in [`Authenticate`], which adds a [`Credential`] to
+//! every request before it is sent. Servers extract the credentials of incoming requests with the functions in this
+//! module and verify them with the generated `Authenticator` trait, which resolves them to the principal that
+//! operations are performed on behalf of.
+
+use std::{
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use futures::future::BoxFuture;
+
+use super::Body;
+
+pub mod oauth2;
+
+/// The location of an API key in a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiKeyLocation {
+    Header,
+    Query,
+    Cookie,
+}
+
+/// An error that occurs while adding a credential to a request.
+#[derive(Debug, thiserror::Error)]
+pub enum AuthError {
+    #[error("credential cannot be sent in header '{0}'")]
+    InvalidHeader(&'static str),
+    #[error("credential cannot be sent in cookie '{0}'")]
+    InvalidCookie(&'static str),
+    #[error("credential cannot be added to the request URI: {0}")]
+    InvalidUri(#[from] http::Error),
+    #[error("failed to obtain a token: {0}")]
+    Token(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error("no configured credential satisfies the authentication requirements of the operation")]
+    Unsatisfied(AuthRequirements),
+}
+
+/// The authentication requirements of the operation that a request is sent for.
+///
+/// Generated clients attach the requirements to their requests as an extension, so that a [`CredentialSet`] can choose
+/// the credentials that satisfy the operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuthRequirements {
+    /// The alternative sets of schemes that can authenticate the operation, identified by their TypeSpec names. The
+    /// schemes of a set must be used together.
+    pub options: &'static [&'static [&'static str]],
+    /// Whether the operation can also be called without any credential.
+    pub optional: bool,
+}
+
+/// A credential that authenticates requests with one of the schemes declared by a service.
+pub trait Credential: Send + Sync + 'static {
+    /// Adds this credential to a request.
+    fn apply<'a>(
+        &'a self,
+        request: &'a mut http::Request<Body>,
+    ) -> BoxFuture<'a, Result<(), AuthError>>;
+}
+
+impl<C: Credential + ?Sized> Credential for Arc<C> {
+    fn apply<'a>(
+        &'a self,
+        request: &'a mut http::Request<Body>,
+    ) -> BoxFuture<'a, Result<(), AuthError>> {
+        (**self).apply(request)
+    }
+}
+
+/// Credentials for several schemes, of which each request uses the ones that satisfy its operation.
+///
+/// Each request is sent with the credentials of the first option of its [`AuthRequirements`] that every scheme of
+/// which has a credential in the set. Requests without requirements are sent with the credential that was set first,
+/// since the credentials of different schemes can occupy the same header, such as Bearer and Basic credentials in
+/// `Authorization`, and would overwrite each other.
+#[derive(Clone, Default)]
+pub struct CredentialSet {
+    credentials: Vec<(&'static str, Arc<dyn Credential>)>,
+}
+
+impl CredentialSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the credential used for a scheme, identified by its TypeSpec name.
+    ///
+    /// Replacing the credential of a scheme keeps its position, so it does not change which credential is used for
+    /// requests without requirements.
+    pub fn insert(&mut self, scheme: &'static str, credential: impl Credential) {
+        let credential: Arc<dyn Credential> = Arc::new(credential);
+
+        match self
+            .credentials
+            .iter_mut()
+            .find(|(existing, _)| *existing == scheme)
+        {
+            Some((_, existing)) => *existing = credential,
+            None => self.credentials.push((scheme, credential)),
+        }
+    }
+
+    /// Sets the credential used for a scheme, identified by its TypeSpec name.
+    pub fn with(mut self, scheme: &'static str, credential: impl Credential) -> Self {
+        self.insert(scheme, credential);
+        self
+    }
+
+    fn get(&self, scheme: &str) -> Option<&Arc<dyn Credential>> {
+        self.credentials
+            .iter()
+            .find(|(existing, _)| *existing == scheme)
+            .map(|(_, credential)| credential)
+    }
+}
+
+impl std::fmt::Debug for CredentialSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set()
+            .entries(self.credentials.iter().map(|(scheme, _)| scheme))
+            .finish()
+    }
+}
+
+impl Credential for CredentialSet {
+    fn apply<'a>(
+        &'a self,
+        request: &'a mut http::Request<Body>,
+    ) -> BoxFuture<'a, Result<(), AuthError>> {
+        Box::pin(async move {
+            let Some(requirements) = request.extensions().get::<AuthRequirements>().copied() else {
+                if let Some((_, credential)) = self.credentials.first() {
+                    credential.apply(request).await?;
+                }
+
+                return Ok(());
+            };
+
+            let satisfied = requirements
+                .options
+                .iter()
+                .find(|schemes| schemes.iter().all(|scheme| self.get(scheme).is_some()));
+
+            match satisfied {
+                Some(schemes) => {
+                    for scheme in schemes.iter() {
+                        if let Some(credential) = self.get(scheme) {
+                            credential.apply(request).await?;
+                        }
+                    }
+
+                    Ok(())
+                }
+                None if requirements.optional => Ok(()),
+                None => Err(AuthError::Unsatisfied(requirements)),
+            }
+        })
+    }
+}
+
+/// An API key sent in a header, query parameter, or cookie.
+#[derive(Clone)]
+pub struct ApiKey {
+    location: ApiKeyLocation,
+    name: &'static str,
+    key: String,
+}
+
+impl ApiKey {
+    pub fn new(location: ApiKeyLocation, name: &'static str, key: impl Into<String>) -> Self {
+        Self {
+            location,
+            name,
+            key: key.into(),
+        }
+    }
+
+    fn insert(&self, request: &mut http::Request<Body>) -> Result<(), AuthError> {
+        match self.location {
+            ApiKeyLocation::Header => {
+                let name = http::HeaderName::from_bytes(self.name.as_bytes())
+                    .map_err(|_| AuthError::InvalidHeader(self.name))?;
+                let value = http::HeaderValue::from_str(&self.key)
+                    .map_err(|_| AuthError::InvalidHeader(self.name))?;
+
+                request.headers_mut().insert(name, value);
+            }
+            ApiKeyLocation::Query => {
+                let mut parts = request.uri().clone().into_parts();
+
+                let (path, query) = match &parts.path_and_query {
+                    Some(path_and_query) => (path_and_query.path(), path_and_query.query()),
+                    None => ("/", None),
+                };
+
+                let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+                serializer.append_pair(self.name, &self.key);
+                let parameter = serializer.finish();
+
+                let path_and_query = match query {
+                    Some(query) if !query.is_empty() => format!("{}?{}&{}", path, query, parameter),
+                    _ => format!("{}?{}", path, parameter),
+                };
+
+                parts.path_and_query = Some(
+                    http::uri::PathAndQuery::try_from(path_and_query).map_err(http::Error::from)?,
+                );
+
+                *request.uri_mut() = http::Uri::from_parts(parts).map_err(http::Error::from)?;
+            }
+            ApiKeyLocation::Cookie => {
+                // Cookies are not escaped, so a key that could end the cookie early or smuggle in another is rejected.
+                if !is_cookie_name(self.name) || !self.key.bytes().all(is_cookie_octet) {
+                    return Err(AuthError::InvalidCookie(self.name));
+                }
+
+                let cookie = match request.headers().get(http::header::COOKIE) {
+                    Some(existing) => format!(
+                        "{}; {}={}",
+                        existing
+                            .to_str()
+                            .map_err(|_| AuthError::InvalidHeader("cookie"))?,
+                        self.name,
+                        self.key
+                    ),
+                    None => format!("{}={}", self.name, self.key),
+                };
+
+                let value = http::HeaderValue::from_str(&cookie)
+                    .map_err(|_| AuthError::InvalidHeader("cookie"))?;
+
+                request.headers_mut().insert(http::header::COOKIE, value);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Determines whether a cookie name is a `token` of RFC 9110, as RFC 6265 requires.
+fn is_cookie_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+/// Determines whether a byte is a `cookie-octet` of RFC 6265, which excludes controls, whitespace, `"`, `,`, `;`
+/// and `\`.
+fn is_cookie_octet(b: u8) -> bool {
+    matches!(b, 0x21 | 0x23..=0x2B | 0x2D..=0x3A | 0x3C..=0x5B | 0x5D..=0x7E)
+}
+
+impl std::fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiKey")
+            .field("location", &self.location)
+            .field("name", &self.name)
+            .field("key", &"<redacted>")
+            .finish()
+    }
+}
+
+impl Credential for ApiKey {
+    fn apply<'a>(
+        &'a self,
+        request: &'a mut http::Request<Body>,
+    ) -> BoxFuture<'a, Result<(), AuthError>> {
+        Box::pin(futures::future::ready(self.insert(request)))
+    }
+}
+
+/// A source of bearer tokens.
+///
+/// Providers are asked for a token every time a request is sent, so they can fetch or refresh tokens asynchronously
+/// as they expire.
+pub trait TokenProvider: Send + Sync + 'static {
+    /// Gets the token to send with the next request.
+    fn token(&self) -> BoxFuture<'_, Result<String, AuthError>>;
+}
+
+impl TokenProvider for String {
+    fn token(&self) -> BoxFuture<'_, Result<String, AuthError>> {
+        Box::pin(futures::future::ready(Ok(self.clone())))
+    }
+}
+
+impl<P: TokenProvider + ?Sized> TokenProvider for Arc<P> {
+    fn token(&self) -> BoxFuture<'_, Result<String, AuthError>> {
+        (**self).token()
+    }
+}
+
+/// A bearer token sent in the `Authorization` header.
+#[derive(Clone)]
+pub struct BearerToken<P> {
+    provider: P,
+}
+
+impl<P> BearerToken<P> {
+    pub fn new(provider: P) -> Self {
+        Self { provider }
+    }
+}
+
+impl<P> std::fmt::Debug for BearerToken<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BearerToken")
+            .field("provider", &"<redacted>")
+            .finish()
+    }
+}
+
+impl<P: TokenProvider> Credential for BearerToken<P> {
+    fn apply<'a>(
+        &'a self,
+        request: &'a mut http::Request<Body>,
+    ) -> BoxFuture<'a, Result<(), AuthError>> {
+        Box::pin(async move {
+            let token = self.provider.token().await?;
+
+            insert_authorization(request, format!("Bearer {}", token))
+        })
+    }
+}
+
+/// A username and password sent in the `Authorization` header.
+#[derive(Clone, PartialEq, Eq)]
+pub struct BasicCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+impl BasicCredentials {
+    pub fn new(username: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            username: username.into(),
+            password: password.into(),
+        }
+    }
+}
+
+impl std::fmt::Debug for BasicCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BasicCredentials")
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
+
+impl Credential for BasicCredentials {
+    fn apply<'a>(
+        &'a self,
+        request: &'a mut http::Request<Body>,
+    ) -> BoxFuture<'a, Result<(), AuthError>> {
+        use base64::Engine;
+
+        let encoded = base64::engine::general_purpose::STANDARD
+            .encode(format!("{}:{}", self.username, self.password));
+
+        Box::pin(futures::future::ready(insert_authorization(
+            request,
+            format!("Basic {}", encoded),
+        )))
+    }
+}
+
+fn insert_authorization(
+    request: &mut http::Request<Body>,
+    authorization: String,
+) -> Result<(), AuthError> {
+    let mut value = http::HeaderValue::try_from(authorization)
+        .map_err(|_| AuthError::InvalidHeader("authorization"))?;
+
+    value.set_sensitive(true);
+
+    request
+        .headers_mut()
+        .insert(http::header::AUTHORIZATION, value);
+
+    Ok(())
+}
+
+/// Gets the API key sent with a request, if there is one.
+pub fn extract_api_key<B>(
+    request: &http::Request<B>,
+    location: ApiKeyLocation,
+    name: &str,
+) -> Option<String> {
+    match location {
+        ApiKeyLocation::Header => request
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from),
+        ApiKeyLocation::Query => request.uri().query().and_then(|query| {
+            url::form_urlencoded::parse(query.as_bytes())
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+        }),
+        ApiKeyLocation::Cookie => request
+            .headers()
+            .get_all(http::header::COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|cookies| cookies.split(';'))
+            .filter_map(|cookie| cookie.trim().split_once('='))
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.to_string()),
+    }
+}
+
+/// Gets the bearer token sent in the `Authorization` header of a request, if there is one.
+pub fn extract_bearer_token<B>(request: &http::Request<B>) -> Option<String> {
+    extract_authorization(request, "Bearer").map(String::from)
+}
+
+/// Gets the username and password sent in the `Authorization` header of a request, if there are any.
+pub fn extract_basic_credentials<B>(request: &http::Request<B>) -> Option<BasicCredentials> {
+    use base64::Engine;
+
+    let encoded = extract_authorization(request, "Basic")?;
+
+    let decoded = base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .ok()?;
+
+    let (username, password) = std::str::from_utf8(&decoded).ok()?.split_once(':')?;
+
+    Some(BasicCredentials::new(username, password))
+}
+
+/// Gets the parameters of the `Authorization` header of a request if it uses the given scheme.
+fn extract_authorization<'a, B>(request: &'a http::Request<B>, scheme: &str) -> Option<&'a str> {
+    let (name, parameters) = request
+        .headers()
+        .get(http::header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .split_once(' ')?;
+
+    name.eq_ignore_ascii_case(scheme).then(|| parameters.trim())
+}
+
+/// An error returned by [`Authenticate`].
+#[derive(Debug, thiserror::Error)]
+pub enum AuthenticateError<E> {
+    #[error("failed to authenticate request: {0}")]
+    Auth(#[source] AuthError),
+    #[error(transparent)]
+    Service(E),
+}
+
+/// A service that adds a credential to every request before sending it.
+pub struct Authenticate<S, C> {
+    inner: S,
+    credential: Arc<C>,
+}
+
+impl<S, C> Authenticate<S, C> {
+    pub fn new(inner: S, credential: C) -> Self {
+        Self {
+            inner,
+            credential: Arc::new(credential),
+        }
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: Clone, C> Clone for Authenticate<S, C> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            credential: self.credential.clone(),
+        }
+    }
+}
+
+impl<S: std::fmt::Debug, C: std::fmt::Debug> std::fmt::Debug for Authenticate<S, C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Authenticate")
+            .field("inner", &self.inner)
+            .field("credential", &self.credential)
+            .finish()
+    }
+}
+
+impl<S, C> tower::Service<http::Request<Body>> for Authenticate<S, C>
+where
+    S: tower::Service<http::Request<Body>> + Clone + Send + 'static,
+    S::Future: Send,
+    C: Credential,
+{
+    type Response = S::Response;
+    type Error = AuthenticateError<S::Error>;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner
+            .poll_ready(cx)
+            .map_err(AuthenticateError::Service)
+    }
+
+    fn call(&mut self, mut request: http::Request<Body>) -> Self::Future {
+        // The service that was driven to readiness must be the one that sends the request.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let credential = self.credential.clone();
+
+        Box::pin(async move {
+            credential
+                .apply(&mut request)
+                .await
+                .map_err(AuthenticateError::Auth)?;
+
+            inner
+                .call(request)
+                .await
+                .map_err(AuthenticateError::Service)
+        })
+    }
+}
+
+/// A layer that wraps services in [`Authenticate`].
+pub struct AuthLayer<C> {
+    credential: Arc<C>,
+}
+
+impl<C> AuthLayer<C> {
+    pub fn new(credential: C) -> Self {
+        Self {
+            credential: Arc::new(credential),
+        }
+    }
+}
+
+impl<C> Clone for AuthLayer<C> {
+    fn clone(&self) -> Self {
+        Self {
+            credential: self.credential.clone(),
+        }
+    }
+}
+
+impl<S, C> tower::Layer<S> for AuthLayer<C> {
+    type Service = Authenticate<S, C>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Authenticate {
+            inner,
+            credential: self.credential.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> http::Request<Body> {
+        http::Request::builder()
+            .uri("http://api/pets")
+            .body(Body::new(Box::pin(futures::stream::empty())))
+            .unwrap()
+    }
+
+    #[test]
+    fn credential_set_without_requirements_applies_first_credential() {
+        let credentials = CredentialSet::new()
+            .with("BearerAuth", BearerToken::new("token".to_string()))
+            .with("BasicAuth", BasicCredentials::new("user", "password"));
+
+        let mut request = request();
+
+        futures::executor::block_on(credentials.apply(&mut request)).unwrap();
+
+        assert_eq!(extract_bearer_token(&request).as_deref(), Some("token"));
+    }
+
+    #[test]
+    fn credential_set_replaces_credentials_in_place() {
+        let credentials = CredentialSet::new()
+            .with("BearerAuth", BearerToken::new("old".to_string()))
+            .with("BasicAuth", BasicCredentials::new("user", "password"))
+            .with("BearerAuth", BearerToken::new("new".to_string()));
+
+        assert_eq!(
+            format!("{:?}", credentials),
+            r#"{"BearerAuth", "BasicAuth"}"#
+        );
+
+        let mut request = request();
+
+        futures::executor::block_on(credentials.apply(&mut request)).unwrap();
+
+        assert_eq!(extract_bearer_token(&request).as_deref(), Some("new"));
+    }
+
+    #[test]
+    fn credential_set_applies_satisfied_option() {
+        let credentials = CredentialSet::new()
+            .with("BearerAuth", BearerToken::new("token".to_string()))
+            .with("BasicAuth", BasicCredentials::new("user", "password"));
+
+        let mut request = request();
+        request.extensions_mut().insert(AuthRequirements {
+            options: &[&["ApiKeyAuth"], &["BasicAuth"]],
+            optional: false,
+        });
+
+        futures::executor::block_on(credentials.apply(&mut request)).unwrap();
+
+        assert_eq!(
+            extract_basic_credentials(&request),
+            Some(BasicCredentials::new("user", "password"))
+        );
+    }
+
+    #[test]
+    fn api_key_cookie_is_appended_to_existing_cookies() {
+        let key = ApiKey::new(ApiKeyLocation::Cookie, "session", "abc=123");
+
+        let mut request = request();
+        request.headers_mut().insert(
+            http::header::COOKIE,
+            http::HeaderValue::from_static("theme=dark"),
+        );
+
+        futures::executor::block_on(key.apply(&mut request)).unwrap();
+
+        assert_eq!(
+            request.headers()[http::header::COOKIE],
+            "theme=dark; session=abc=123"
+        );
+        assert_eq!(
+            extract_api_key(&request, ApiKeyLocation::Cookie, "session").as_deref(),
+            Some("abc=123")
+        );
+    }
+
+    #[test]
+    fn api_key_cookie_rejects_characters_outside_cookie_octets() {
+        for key in [
+            "a;admin=1",
+            "a b",
+            "a,b",
+            "\"a\"",
+            "a\\b",
+            "a\tb",
+            "caf\u{e9}",
+        ] {
+            let key = ApiKey::new(ApiKeyLocation::Cookie, "session", key);
+
+            let result = futures::executor::block_on(key.apply(&mut request()));
+
+            assert!(matches!(result, Err(AuthError::InvalidCookie("session"))));
+        }
+
+        let key = ApiKey::new(ApiKeyLocation::Cookie, "ses=sion", "abc");
+
+        let result = futures::executor::block_on(key.apply(&mut request()));
+
+        assert!(matches!(result, Err(AuthError::InvalidCookie("ses=sion"))));
+    }
+}
@@ -0,0 +1,464 @@
+//! The OAuth2 client credentials flow.
+//!
+//! [`ClientCredentialsLayer`] wraps a service so that every request carries an access token obtained from a token
+//! endpoint. Tokens are requested through the wrapped service itself, so the token endpoint can be any service that
+//! the client can reach, including a local stand-in built from a generated router.
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use futures::{future::BoxFuture, lock::Mutex};
+use http_body_util::BodyExt;
+
+use super::{AuthError, Authenticate, BasicCredentials, BearerToken, Credential, TokenProvider};
+use crate::http::Body;
+
+/// How long before a token expires it is refreshed, unless configured otherwise.
+const DEFAULT_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// The configuration of the client credentials flow.
+#[derive(Debug, Clone)]
+pub struct ClientCredentials {
+    token_url: http::Uri,
+    client: BasicCredentials,
+    scopes: Vec<String>,
+    refresh_margin: Duration,
+}
+
+impl ClientCredentials {
+    pub fn new(
+        token_url: http::Uri,
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+    ) -> Self {
+        Self {
+            token_url,
+            client: BasicCredentials::new(client_id, client_secret),
+            scopes: Vec::new(),
+            refresh_margin: DEFAULT_REFRESH_MARGIN,
+        }
+    }
+
+    /// Sets the scopes that are requested for access tokens.
+    pub fn with_scopes(mut self, scopes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.scopes = scopes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets how long before a token expires a new one is requested.
+    ///
+    /// The margin is capped at half the lifetime of each token, so short-lived tokens are still reused.
+    pub fn with_refresh_margin(mut self, refresh_margin: Duration) -> Self {
+        self.refresh_margin = refresh_margin;
+        self
+    }
+
+    pub fn token_url(&self) -> &http::Uri {
+        &self.token_url
+    }
+
+    pub fn scopes(&self) -> &[String] {
+        &self.scopes
+    }
+
+    fn token_request(&self) -> Result<http::Request<Body>, TokenError> {
+        let mut form = url::form_urlencoded::Serializer::new(String::new());
+
+        form.append_pair("grant_type", "client_credentials");
+
+        if !self.scopes.is_empty() {
+            form.append_pair("scope", &self.scopes.join(" "));
+        }
+
+        let data = form.finish();
+        let stream = futures::stream::once(futures::future::ready(Ok(http_body::Frame::data(
+            data.into(),
+        ))));
+
+        Ok(http::Request::builder()
+            .method(http::Method::POST)
+            .uri(self.token_url.clone())
+            .header(
+                http::header::CONTENT_TYPE,
+                "application/x-www-form-urlencoded",
+            )
+            .header(http::header::ACCEPT, "application/json")
+            .body(Body::new(Box::pin(stream)))?)
+    }
+}
+
+/// An error that occurs while requesting an access token.
+#[derive(Debug, thiserror::Error)]
+pub enum TokenError {
+    #[error("failed to build the token request: {0}")]
+    Request(#[from] http::Error),
+    #[error("failed to authenticate the token request: {0}")]
+    Auth(#[from] AuthError),
+    #[error("failed to send the token request: {0}")]
+    Service(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error("failed to read the token response: {0}")]
+    Body(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error("token endpoint responded with status {0}: {1}")]
+    Status(http::StatusCode, String),
+    #[error("invalid token response: {0}")]
+    Deserialize(#[from] serde_json::Error),
+}
+
+#[derive(serde::Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: Option<u64>,
+}
+
+struct CachedToken {
+    access_token: String,
+    /// When the token should be replaced, which is some time before it expires.
+    refresh_at: Option<Instant>,
+    expires_at: Option<Instant>,
+}
+
+struct State<S> {
+    service: S,
+    token: Option<CachedToken>,
+}
+
+/// A token provider that obtains tokens with the client credentials flow.
+///
+/// Tokens are cached until they are due to be refreshed. Only one token request is in flight at a time; concurrent
+/// requests wait for it and share its token. If a refresh fails while the cached token has not yet expired, the cached
+/// token is used until it does.
+pub struct ClientCredentialsProvider<S> {
+    credentials: ClientCredentials,
+    state: Mutex<State<S>>,
+}
+
+impl<S> ClientCredentialsProvider<S> {
+    /// Creates a provider that requests tokens through a service.
+    pub fn new(service: S, credentials: ClientCredentials) -> Self {
+        Self {
+            credentials,
+            state: Mutex::new(State {
+                service,
+                token: None,
+            }),
+        }
+    }
+}
+
+impl<S, ResponseBody> ClientCredentialsProvider<S>
+where
+    S: tower::Service<http::Request<Body>, Response = http::Response<ResponseBody>> + Send,
+    S::Future: Send,
+    S::Error: std::error::Error + Send + Sync + 'static,
+    ResponseBody: http_body::Body + Send,
+    ResponseBody::Data: Send,
+    ResponseBody::Error: std::error::Error + Send + Sync + 'static,
+{
+    async fn fetch(&self, service: &mut S) -> Result<CachedToken, TokenError> {
+        let mut request = self.credentials.token_request()?;
+
+        self.credentials.client.apply(&mut request).await?;
+
+        futures::future::poll_fn(|cx| service.poll_ready(cx))
+            .await
+            .map_err(|e| TokenError::Service(Box::new(e)))?;
+
+        let response = service
+            .call(request)
+            .await
+            .map_err(|e| TokenError::Service(Box::new(e)))?;
+
+        let (parts, body) = response.into_parts();
+
+        let data = body
+            .collect()
+            .await
+            .map_err(|e| TokenError::Body(Box::new(e)))?
+            .to_bytes();
+
+        if !parts.status.is_success() {
+            return Err(TokenError::Status(
+                parts.status,
+                String::from_utf8_lossy(&data).into_owned(),
+            ));
+        }
+
+        let response: TokenResponse = serde_json::from_slice(&data)?;
+
+        let issued_at = Instant::now();
+        let lifetime = response.expires_in.map(Duration::from_secs);
+
+        Ok(CachedToken {
+            access_token: response.access_token,
+            refresh_at: lifetime.map(|lifetime| {
+                issued_at + lifetime - self.credentials.refresh_margin.min(lifetime / 2)
+            }),
+            expires_at: lifetime.map(|lifetime| issued_at + lifetime),
+        })
+    }
+}
+
+impl<S, ResponseBody> TokenProvider for ClientCredentialsProvider<S>
+where
+    S: tower::Service<http::Request<Body>, Response = http::Response<ResponseBody>>
+        + Send
+        + 'static,
+    S::Future: Send,
+    S::Error: std::error::Error + Send + Sync + 'static,
+    ResponseBody: http_body::Body + Send + 'static,
+    ResponseBody::Data: Send,
+    ResponseBody::Error: std::error::Error + Send + Sync + 'static,
+{
+    fn token(&self) -> BoxFuture<'_, Result<String, AuthError>> {
+        Box::pin(async move {
+            let mut state = self.state.lock().await;
+            let now = Instant::now();
+
+            if let Some(token) = &state.token
+                && token.refresh_at.is_none_or(|refresh_at| now < refresh_at)
+            {
+                return Ok(token.access_token.clone());
+            }
+
+            let State { service, token } = &mut *state;
+
+            match self.fetch(service).await {
+                Ok(fetched) => {
+                    let access_token = fetched.access_token.clone();
+                    *token = Some(fetched);
+                    Ok(access_token)
+                }
+                Err(error) => match token {
+                    Some(token) if token.expires_at.is_some_and(|expires_at| now < expires_at) => {
+                        log::warn!(
+                            "failed to refresh access token, using cached token: {}",
+                            error
+                        );
+                        Ok(token.access_token.clone())
+                    }
+                    _ => Err(AuthError::Token(Box::new(error))),
+                },
+            }
+        })
+    }
+}
+
+/// The service produced by [`ClientCredentialsLayer`].
+pub type ClientCredentialsService<S> = Authenticate<S, BearerToken<ClientCredentialsProvider<S>>>;
+
+/// A layer that authenticates requests with access tokens obtained with the client credentials flow.
+///
+/// Tokens are requested through a clone of the wrapped service, and the token cache is shared by all clones of the
+/// resulting service.
+#[derive(Debug, Clone)]
+pub struct ClientCredentialsLayer {
+    credentials: Arc<ClientCredentials>,
+}
+
+impl ClientCredentialsLayer {
+    pub fn new(credentials: ClientCredentials) -> Self {
+        Self {
+            credentials: Arc::new(credentials),
+        }
+    }
+}
+
+impl<S: Clone> tower::Layer<S> for ClientCredentialsLayer {
+    type Service = ClientCredentialsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        let provider = ClientCredentialsProvider::new(inner.clone(), (*self.credentials).clone());
+
+        Authenticate::new(inner, BearerToken::new(provider))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::VecDeque, convert::Infallible, sync::Mutex as StdMutex};
+
+    use bytes::Bytes;
+    use http_body_util::Full;
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+
+    /// The state of a stand-in service that serves both the token endpoint, at `/token`, and the API.
+    #[derive(Default)]
+    struct Endpoint {
+        /// The form bodies of the token requests, with their `Authorization` headers.
+        token_requests: Vec<(String, String)>,
+        /// The `Authorization` headers of the API requests.
+        api_requests: Vec<String>,
+        /// The responses of the token endpoint, in order. Once they run out, it fails.
+        token_responses: VecDeque<&'static str>,
+    }
+
+    fn endpoint(
+        state: Arc<StdMutex<Endpoint>>,
+    ) -> impl tower::Service<
+        http::Request<Body>,
+        Response = http::Response<Full<Bytes>>,
+        Error = Infallible,
+        Future = impl Send,
+    > + Clone
+    + Send
+    + 'static {
+        tower::service_fn(move |request: http::Request<Body>| {
+            let state = state.clone();
+
+            async move {
+                let (parts, body) = request.into_parts();
+                let authorization = parts
+                    .headers
+                    .get(http::header::AUTHORIZATION)
+                    .map(|value| value.to_str().unwrap().to_string())
+                    .unwrap_or_default();
+
+                if parts.uri.path() != "/token" {
+                    state.lock().unwrap().api_requests.push(authorization);
+                    return Ok(http::Response::new(Full::default()));
+                }
+
+                let data = body.collect().await.unwrap().to_bytes();
+                let form = String::from_utf8(data.to_vec()).unwrap();
+
+                let mut state = state.lock().unwrap();
+                state.token_requests.push((form, authorization));
+
+                Ok(match state.token_responses.pop_front() {
+                    Some(response) => http::Response::new(Full::from(response)),
+                    None => http::Response::builder()
+                        .status(http::StatusCode::SERVICE_UNAVAILABLE)
+                        .body(Full::default())
+                        .unwrap(),
+                })
+            }
+        })
+    }
+
+    fn credentials() -> ClientCredentials {
+        ClientCredentials::new(
+            http::Uri::from_static("http://auth/token"),
+            "client",
+            "secret",
+        )
+        .with_scopes(["pets.read", "pets.write"])
+    }
+
+    fn api_request() -> http::Request<Body> {
+        http::Request::builder()
+            .uri("http://api/pets")
+            .body(Body::new(Box::pin(futures::stream::empty())))
+            .unwrap()
+    }
+
+    async fn send<S>(service: &mut S) -> Result<(), S::Error>
+    where
+        S: tower::Service<http::Request<Body>>,
+    {
+        service.ready().await?.call(api_request()).await?;
+        Ok(())
+    }
+
+    /// Makes the token cached by a service due to be refreshed, without expiring it.
+    async fn reach_refresh<S>(service: &ClientCredentialsService<S>) {
+        let mut state = service.credential.provider.state.lock().await;
+        let token = state.token.as_mut().unwrap();
+
+        token.refresh_at = Some(Instant::now());
+    }
+
+    #[test]
+    fn requests_token_with_form_body() {
+        let state = Arc::new(StdMutex::new(Endpoint::default()));
+        state
+            .lock()
+            .unwrap()
+            .token_responses
+            .push_back(r#"{"access_token":"first","expires_in":3600}"#);
+
+        let mut service = ClientCredentialsLayer::new(credentials()).layer(endpoint(state.clone()));
+
+        futures::executor::block_on(send(&mut service)).unwrap();
+
+        let state = state.lock().unwrap();
+
+        assert_eq!(
+            state.token_requests,
+            [(
+                "grant_type=client_credentials&scope=pets.read+pets.write".to_string(),
+                "Basic Y2xpZW50OnNlY3JldA==".to_string(),
+            )]
+        );
+        assert_eq!(state.api_requests, ["Bearer first"]);
+    }
+
+    #[test]
+    fn reuses_token_before_refresh() {
+        let state = Arc::new(StdMutex::new(Endpoint::default()));
+        state.lock().unwrap().token_responses.extend([
+            r#"{"access_token":"first","expires_in":3600}"#,
+            r#"{"access_token":"second","expires_in":3600}"#,
+        ]);
+
+        let mut service = ClientCredentialsLayer::new(credentials()).layer(endpoint(state.clone()));
+
+        futures::executor::block_on(async {
+            send(&mut service).await.unwrap();
+            send(&mut service.clone()).await.unwrap();
+        });
+
+        let state = state.lock().unwrap();
+
+        assert_eq!(state.token_requests.len(), 1);
+        assert_eq!(state.api_requests, ["Bearer first", "Bearer first"]);
+    }
+
+    #[test]
+    fn refreshes_token_after_refresh_at() {
+        let state = Arc::new(StdMutex::new(Endpoint::default()));
+        state.lock().unwrap().token_responses.extend([
+            r#"{"access_token":"first","expires_in":3600}"#,
+            r#"{"access_token":"second","expires_in":3600}"#,
+        ]);
+
+        let mut service = ClientCredentialsLayer::new(credentials()).layer(endpoint(state.clone()));
+
+        futures::executor::block_on(async {
+            send(&mut service).await.unwrap();
+            reach_refresh(&service).await;
+            send(&mut service).await.unwrap();
+        });
+
+        let state = state.lock().unwrap();
+
+        assert_eq!(state.token_requests.len(), 2);
+        assert_eq!(state.api_requests, ["Bearer first", "Bearer second"]);
+    }
+
+    #[test]
+    fn uses_cached_token_when_refresh_fails() {
+        let state = Arc::new(StdMutex::new(Endpoint::default()));
+        state
+            .lock()
+            .unwrap()
+            .token_responses
+            .push_back(r#"{"access_token":"first","expires_in":3600}"#);
+
+        let mut service = ClientCredentialsLayer::new(credentials()).layer(endpoint(state.clone()));
+
+        futures::executor::block_on(async {
+            send(&mut service).await.unwrap();
+            reach_refresh(&service).await;
+            send(&mut service).await.unwrap();
+        });
+
+        let state = state.lock().unwrap();
+
+        assert_eq!(state.token_requests.len(), 2);
+        assert_eq!(state.api_requests, ["Bearer first", "Bearer first"]);
+    }
+}
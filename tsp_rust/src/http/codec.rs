@@ -0,0 +1,749 @@
+//! Body codecs, keyed by media type.
+//!
+//! Generated code serializes and deserializes bodies through the codec registered for their content type rather than
+//! calling a serialization library directly. The runtime provides codecs for JSON (including any `+json` media type),
+//! `text/plain` and `application/octet-stream`. Other codecs are registered by adding them to [`CODECS`]:
+//!
+//! ```ignore
+//! #[linkme::distributed_slice(tsp_rust::http::codec::CODECS)]
+//! static YAML: &'static dyn tsp_rust::http::codec::Codec = &YamlCodec;
+//! ```
+//!
+//! Registered codecs take precedence over the built-in ones, so they may also replace them.
+
+use std::fmt::Display;
+
+use linkme::distributed_slice;
+use serde::{
+    Serialize,
+    de::{self, DeserializeOwned, IntoDeserializer, Visitor},
+    forward_to_deserialize_any,
+    ser::{self, Impossible},
+};
+
+/// The codecs registered by applications, which are consulted before the built-in codecs.
+#[distributed_slice]
+pub static CODECS: [&'static dyn Codec];
+
+static BUILTIN_CODECS: [&dyn Codec; 3] = [&JsonCodec, &TextCodec, &OctetStreamCodec];
+
+/// Converts values to and from the bodies of a media type.
+///
+/// Codecs are object safe, so they work with type-erased values. Use [`encode`] and [`decode`] to convert values of a
+/// concrete type.
+pub trait Codec: Sync {
+    /// Whether this codec handles the given media type, which is lowercase and has no parameters.
+    fn handles(&self, media_type: &str) -> bool;
+
+    /// Encodes a value as the data of a body.
+    fn encode(&self, value: &dyn erased_serde::Serialize) -> Result<Vec<u8>, CodecError>;
+
+    /// Decodes the data of a body, passing a deserializer over it to `visit`.
+    ///
+    /// The value is produced by `visit`, and its errors are returned by this method as [`CodecError::Decode`].
+    fn decode(
+        &self,
+        data: &[u8],
+        visit: &mut dyn FnMut(
+            &mut dyn erased_serde::Deserializer,
+        ) -> Result<(), erased_serde::Error>,
+    ) -> Result<(), CodecError>;
+}
+
+/// An error that occurs while encoding or decoding a body.
+///
+/// Headers and parameters with an `@encode` encoding that cannot represent their value also fail with an `Encode`
+/// error, as they are encoded along with the body.
+#[derive(Debug, thiserror::Error)]
+pub enum CodecError {
+    #[error("no codec is registered for media type '{0}'")]
+    UnsupportedMediaType(String),
+    #[error("failed to encode value: {0}")]
+    Encode(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error("failed to decode body: {0}")]
+    Decode(#[source] Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl CodecError {
+    pub fn encode(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        Self::Encode(error.into())
+    }
+
+    pub fn decode(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        Self::Decode(error.into())
+    }
+}
+
+/// Returns the essence of a media type: its type and subtype, in lowercase and without parameters.
+pub fn essence(media_type: &str) -> String {
+    media_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+/// Finds the codec for a media type, which may have parameters.
+pub fn find(media_type: &str) -> Result<&'static dyn Codec, CodecError> {
+    let essence = essence(media_type);
+
+    CODECS
+        .iter()
+        .chain(BUILTIN_CODECS.iter())
+        .find(|codec| codec.handles(&essence))
+        .copied()
+        .ok_or(CodecError::UnsupportedMediaType(essence))
+}
+
+/// Encodes a value as a body of the given media type.
+pub fn encode<T: Serialize + ?Sized>(media_type: &str, value: &T) -> Result<Vec<u8>, CodecError> {
+    find(media_type)?.encode(&value)
+}
+
+/// Decodes a value from a body of the given media type.
+pub fn decode<T: DeserializeOwned>(media_type: &str, data: &[u8]) -> Result<T, CodecError> {
+    let mut value = None;
+
+    find(media_type)?.decode(data, &mut |deserializer| {
+        value = Some(erased_serde::deserialize(deserializer)?);
+        Ok(())
+    })?;
+
+    value.ok_or_else(|| CodecError::decode("the codec did not produce a value"))
+}
+
+/// The codec for `application/json` and any other media type with the `+json` structured syntax suffix.
+pub struct JsonCodec;
+
+impl Codec for JsonCodec {
+    fn handles(&self, media_type: &str) -> bool {
+        media_type == "application/json" || media_type.ends_with("+json")
+    }
+
+    fn encode(&self, value: &dyn erased_serde::Serialize) -> Result<Vec<u8>, CodecError> {
+        serde_json::to_vec(value).map_err(CodecError::encode)
+    }
+
+    fn decode(
+        &self,
+        data: &[u8],
+        visit: &mut dyn FnMut(
+            &mut dyn erased_serde::Deserializer,
+        ) -> Result<(), erased_serde::Error>,
+    ) -> Result<(), CodecError> {
+        let mut deserializer = serde_json::Deserializer::from_slice(data);
+
+        visit(&mut <dyn erased_serde::Deserializer>::erase(
+            &mut deserializer,
+        ))
+        .map_err(CodecError::decode)?;
+
+        // Trailing data is not part of the value, but still makes the body invalid.
+        deserializer.end().map_err(CodecError::decode)
+    }
+}
+
+/// The codec for `text/plain`, which carries strings and numeric scalars as their text.
+///
+/// Unit enum variants are carried as their names, so that string enums can be sent as text as well.
+pub struct TextCodec;
+
+impl Codec for TextCodec {
+    fn handles(&self, media_type: &str) -> bool {
+        media_type == "text/plain"
+    }
+
+    fn encode(&self, value: &dyn erased_serde::Serialize) -> Result<Vec<u8>, CodecError> {
+        erased_serde::serialize(value, TextSerializer)
+            .map(String::into_bytes)
+            .map_err(CodecError::encode)
+    }
+
+    fn decode(
+        &self,
+        data: &[u8],
+        visit: &mut dyn FnMut(
+            &mut dyn erased_serde::Deserializer,
+        ) -> Result<(), erased_serde::Error>,
+    ) -> Result<(), CodecError> {
+        let text = std::str::from_utf8(data).map_err(CodecError::decode)?;
+
+        visit(&mut <dyn erased_serde::Deserializer>::erase(
+            TextDeserializer(text),
+        ))
+        .map_err(CodecError::decode)
+    }
+}
+
+/// The codec for `application/octet-stream`, which carries `bytes` values as they are.
+pub struct OctetStreamCodec;
+
+impl Codec for OctetStreamCodec {
+    fn handles(&self, media_type: &str) -> bool {
+        media_type == "application/octet-stream"
+    }
+
+    fn encode(&self, value: &dyn erased_serde::Serialize) -> Result<Vec<u8>, CodecError> {
+        erased_serde::serialize(value, BytesSerializer).map_err(CodecError::encode)
+    }
+
+    fn decode(
+        &self,
+        data: &[u8],
+        visit: &mut dyn FnMut(
+            &mut dyn erased_serde::Deserializer,
+        ) -> Result<(), erased_serde::Error>,
+    ) -> Result<(), CodecError> {
+        visit(&mut <dyn erased_serde::Deserializer>::erase(
+            BytesDeserializer(data),
+        ))
+        .map_err(CodecError::decode)
+    }
+}
+
+/// The error of the serializers of the built-in codecs.
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct SerializeError(String);
+
+impl ser::Error for SerializeError {
+    fn custom<T: Display>(message: T) -> Self {
+        Self(message.to_string())
+    }
+}
+
+/// Rejects the values that a serializer has no representation for.
+macro_rules! unsupported {
+    ($($method:ident($($arg:ty),*) -> $ok:ty),* $(,)?) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<$ok, Self::Error> {
+                Err(ser::Error::custom(concat!(
+                    "unsupported value for this media type (",
+                    stringify!($method),
+                    ")"
+                )))
+            }
+        )*
+    };
+}
+
+/// Serializes the value of a `text/plain` body.
+struct TextSerializer;
+
+macro_rules! text_display {
+    ($($method:ident($ty:ty)),* $(,)?) => {
+        $(
+            fn $method(self, value: $ty) -> Result<String, Self::Error> {
+                Ok(value.to_string())
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for TextSerializer {
+    type Ok = String;
+    type Error = SerializeError;
+
+    type SerializeSeq = Impossible<String, SerializeError>;
+    type SerializeTuple = Impossible<String, SerializeError>;
+    type SerializeTupleStruct = Impossible<String, SerializeError>;
+    type SerializeTupleVariant = Impossible<String, SerializeError>;
+    type SerializeMap = Impossible<String, SerializeError>;
+    type SerializeStruct = Impossible<String, SerializeError>;
+    type SerializeStructVariant = Impossible<String, SerializeError>;
+
+    text_display! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_i128(i128),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_u128(u128),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<String, Self::Error> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<String, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<String, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        value: &T,
+    ) -> Result<String, Self::Error> {
+        value.serialize(self)
+    }
+
+    unsupported! {
+        serialize_bytes(&[u8]) -> String,
+        serialize_none() -> String,
+        serialize_unit() -> String,
+        serialize_unit_struct(&'static str) -> String,
+        serialize_seq(Option<usize>) -> Self::SerializeSeq,
+        serialize_tuple(usize) -> Self::SerializeTuple,
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct,
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant,
+        serialize_map(Option<usize>) -> Self::SerializeMap,
+        serialize_struct(&'static str, usize) -> Self::SerializeStruct,
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant,
+    }
+}
+
+/// Deserializes the value of a `text/plain` body, parsing the text of numeric scalars.
+struct TextDeserializer<'a>(&'a str);
+
+macro_rules! text_parse {
+    ($($method:ident => $visit:ident),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                visitor.$visit(self.0.trim().parse().map_err(de::Error::custom)?)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for TextDeserializer<'_> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_str(self.0)
+    }
+
+    text_parse! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        IntoDeserializer::<Self::Error>::into_deserializer(self.0)
+            .deserialize_enum(name, variants, visitor)
+    }
+
+    forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// Deserializes the value of an `application/octet-stream` body.
+struct BytesDeserializer<'a>(&'a [u8]);
+
+impl<'de> de::Deserializer<'de> for BytesDeserializer<'_> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_bytes(self.0)
+    }
+
+    // `Vec<u8>` deserializes from a sequence of bytes rather than through `visit_bytes`.
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(de::value::SeqDeserializer::new(self.0.iter().copied()))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit unit_struct tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Serializes the value of an `application/octet-stream` body, which must be bytes.
+struct BytesSerializer;
+
+impl ser::Serializer for BytesSerializer {
+    type Ok = Vec<u8>;
+    type Error = SerializeError;
+
+    type SerializeSeq = BytesSeq;
+    type SerializeTuple = Impossible<Vec<u8>, SerializeError>;
+    type SerializeTupleStruct = Impossible<Vec<u8>, SerializeError>;
+    type SerializeTupleVariant = Impossible<Vec<u8>, SerializeError>;
+    type SerializeMap = Impossible<Vec<u8>, SerializeError>;
+    type SerializeStruct = Impossible<Vec<u8>, SerializeError>;
+    type SerializeStructVariant = Impossible<Vec<u8>, SerializeError>;
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<Vec<u8>, Self::Error> {
+        Ok(value.to_vec())
+    }
+
+    // `Vec<u8>` serializes as a sequence of bytes rather than through `serialize_bytes`.
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(BytesSeq(Vec::with_capacity(len.unwrap_or_default())))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Vec<u8>, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Vec<u8>, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Vec<u8>, Self::Error> {
+        value.serialize(self)
+    }
+
+    unsupported! {
+        serialize_bool(bool) -> Vec<u8>,
+        serialize_i8(i8) -> Vec<u8>,
+        serialize_i16(i16) -> Vec<u8>,
+        serialize_i32(i32) -> Vec<u8>,
+        serialize_i64(i64) -> Vec<u8>,
+        serialize_u8(u8) -> Vec<u8>,
+        serialize_u16(u16) -> Vec<u8>,
+        serialize_u32(u32) -> Vec<u8>,
+        serialize_u64(u64) -> Vec<u8>,
+        serialize_f32(f32) -> Vec<u8>,
+        serialize_f64(f64) -> Vec<u8>,
+        serialize_char(char) -> Vec<u8>,
+        serialize_str(&str) -> Vec<u8>,
+        serialize_none() -> Vec<u8>,
+        serialize_unit() -> Vec<u8>,
+        serialize_unit_struct(&'static str) -> Vec<u8>,
+        serialize_unit_variant(&'static str, u32, &'static str) -> Vec<u8>,
+        serialize_tuple(usize) -> Self::SerializeTuple,
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct,
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant,
+        serialize_map(Option<usize>) -> Self::SerializeMap,
+        serialize_struct(&'static str, usize) -> Self::SerializeStruct,
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant,
+    }
+}
+
+/// Collects the elements of a byte sequence.
+struct BytesSeq(Vec<u8>);
+
+impl ser::SerializeSeq for BytesSeq {
+    type Ok = Vec<u8>;
+    type Error = SerializeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.0.push(value.serialize(ByteSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Vec<u8>, Self::Error> {
+        Ok(self.0)
+    }
+}
+
+/// Serializes an element of a byte sequence.
+struct ByteSerializer;
+
+impl ser::Serializer for ByteSerializer {
+    type Ok = u8;
+    type Error = SerializeError;
+
+    type SerializeSeq = Impossible<u8, SerializeError>;
+    type SerializeTuple = Impossible<u8, SerializeError>;
+    type SerializeTupleStruct = Impossible<u8, SerializeError>;
+    type SerializeTupleVariant = Impossible<u8, SerializeError>;
+    type SerializeMap = Impossible<u8, SerializeError>;
+    type SerializeStruct = Impossible<u8, SerializeError>;
+    type SerializeStructVariant = Impossible<u8, SerializeError>;
+
+    fn serialize_u8(self, value: u8) -> Result<u8, Self::Error> {
+        Ok(value)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<u8, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<u8, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        value: &T,
+    ) -> Result<u8, Self::Error> {
+        value.serialize(self)
+    }
+
+    unsupported! {
+        serialize_bool(bool) -> u8,
+        serialize_i8(i8) -> u8,
+        serialize_i16(i16) -> u8,
+        serialize_i32(i32) -> u8,
+        serialize_i64(i64) -> u8,
+        serialize_u16(u16) -> u8,
+        serialize_u32(u32) -> u8,
+        serialize_u64(u64) -> u8,
+        serialize_f32(f32) -> u8,
+        serialize_f64(f64) -> u8,
+        serialize_char(char) -> u8,
+        serialize_str(&str) -> u8,
+        serialize_bytes(&[u8]) -> u8,
+        serialize_none() -> u8,
+        serialize_unit() -> u8,
+        serialize_unit_struct(&'static str) -> u8,
+        serialize_unit_variant(&'static str, u32, &'static str) -> u8,
+        serialize_seq(Option<usize>) -> Self::SerializeSeq,
+        serialize_tuple(usize) -> Self::SerializeTuple,
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct,
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant,
+        serialize_map(Option<usize>) -> Self::SerializeMap,
+        serialize_struct(&'static str, usize) -> Self::SerializeStruct,
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use serde::Deserialize;
+
+    use super::*;
+
+    /// A codec registered by the tests, which claims a `+json` media type ahead of [`JsonCodec`].
+    struct TestCodec;
+
+    #[distributed_slice(CODECS)]
+    static TEST_CODEC: &'static dyn Codec = &TestCodec;
+
+    impl Codec for TestCodec {
+        fn handles(&self, media_type: &str) -> bool {
+            media_type == "application/vnd.tsp-rust-test+json"
+        }
+
+        fn encode(&self, _: &dyn erased_serde::Serialize) -> Result<Vec<u8>, CodecError> {
+            Ok(b"test".to_vec())
+        }
+
+        fn decode(
+            &self,
+            _: &[u8],
+            visit: &mut dyn FnMut(
+                &mut dyn erased_serde::Deserializer,
+            ) -> Result<(), erased_serde::Error>,
+        ) -> Result<(), CodecError> {
+            visit(&mut <dyn erased_serde::Deserializer>::erase(
+                IntoDeserializer::<de::value::Error>::into_deserializer("test"),
+            ))
+            .map_err(CodecError::decode)
+        }
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Pet {
+        name: String,
+        age: u8,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Kind {
+        Dog,
+        Cat,
+    }
+
+    fn round_trip<T>(media_type: &str, value: &T) -> T
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        decode(media_type, &encode(media_type, value).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn essence_strips_parameters_and_case() {
+        assert_eq!(
+            essence("Application/JSON; charset=utf-8"),
+            "application/json"
+        );
+        assert_eq!(essence(" text/plain "), "text/plain");
+        assert_eq!(essence(""), "");
+    }
+
+    #[test]
+    fn registered_codecs_take_precedence() {
+        let media_type = "application/vnd.tsp-rust-test+json; charset=utf-8";
+
+        assert_eq!(encode(media_type, &1).unwrap(), b"test");
+        assert_eq!(decode::<String>(media_type, b"1").unwrap(), "test");
+    }
+
+    #[test]
+    fn json_handles_suffix() {
+        let pet = Pet {
+            name: "Fido".to_string(),
+            age: 3,
+        };
+
+        assert_eq!(
+            encode("application/problem+json", &pet).unwrap(),
+            encode("application/json", &pet).unwrap()
+        );
+        assert_eq!(round_trip("application/merge-patch+json", &pet), pet);
+    }
+
+    #[test]
+    fn json_rejects_trailing_data() {
+        assert!(matches!(
+            decode::<u32>("application/json", b"1 2"),
+            Err(CodecError::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn unsupported_media_type() {
+        assert!(matches!(
+            find("Application/XML; charset=utf-8"),
+            Err(CodecError::UnsupportedMediaType(media_type)) if media_type == "application/xml"
+        ));
+    }
+
+    #[test]
+    fn text_round_trips_scalars() {
+        assert_eq!(round_trip("text/plain", &"Fido".to_string()), "Fido");
+        assert_eq!(round_trip("text/plain", &-42i32), -42);
+        assert_eq!(round_trip("text/plain", &u64::MAX), u64::MAX);
+        assert_eq!(round_trip("text/plain", &1.5f64), 1.5);
+        assert!(round_trip::<bool>("text/plain", &true));
+        assert_eq!(round_trip("text/plain", &Kind::Cat), Kind::Cat);
+        assert_eq!(round_trip("text/plain", &Some(7u8)), Some(7));
+
+        assert_eq!(encode("text/plain", &-42i32).unwrap(), b"-42");
+        assert_eq!(decode::<i32>("text/plain", b" 42\r\n").unwrap(), 42);
+    }
+
+    #[test]
+    fn text_rejects_unsupported_values() {
+        assert!(matches!(
+            encode("text/plain", &vec![1, 2]),
+            Err(CodecError::Encode(_))
+        ));
+        assert!(matches!(
+            encode("text/plain", &None::<u8>),
+            Err(CodecError::Encode(_))
+        ));
+        assert!(matches!(
+            encode(
+                "text/plain",
+                &Pet {
+                    name: "Fido".to_string(),
+                    age: 3
+                }
+            ),
+            Err(CodecError::Encode(_))
+        ));
+        assert!(matches!(
+            decode::<i32>("text/plain", b"forty-two"),
+            Err(CodecError::Decode(_))
+        ));
+        assert!(matches!(
+            decode::<String>("text/plain", b"\xff"),
+            Err(CodecError::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn octet_stream_round_trips_bytes() {
+        let data = vec![0u8, 1, 0xfe, 0xff];
+
+        assert_eq!(round_trip("application/octet-stream", &data), data);
+        assert_eq!(
+            round_trip("application/octet-stream", &Bytes::from(data.clone())),
+            data
+        );
+        assert_eq!(encode("application/octet-stream", &data).unwrap(), data);
+    }
+
+    #[test]
+    fn octet_stream_rejects_unsupported_values() {
+        assert!(matches!(
+            encode("application/octet-stream", "text"),
+            Err(CodecError::Encode(_))
+        ));
+        assert!(matches!(
+            encode("application/octet-stream", &vec![256u16]),
+            Err(CodecError::Encode(_))
+        ));
+        assert!(matches!(
+            decode::<u32>("application/octet-stream", b"1"),
+            Err(CodecError::Decode(_))
+        ));
+    }
+}
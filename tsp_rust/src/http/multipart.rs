@@ -0,0 +1,918 @@
+//! `multipart/form-data` bodies.
+//!
+//! Operations with multipart bodies take a generated struct with a field for each `HttpPart` of their body model.
+//! Generated clients encode the struct into a [`Form`] with [`ToMultipart`], and generated servers decode it from a
+//! [`MultipartReader`] with [`FromMultipart`].
+//!
+//! Parts are encoded by the type of their values:
+//!
+//! - `File` parts are sent with their file name and content type.
+//! - `string` parts are sent as `text/plain`.
+//! - `bytes` parts are sent as `application/octet-stream`.
+//! - All other parts are sent as `application/json`.
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    pin::Pin,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bytes::{Bytes, BytesMut};
+use http_body::Frame;
+use http_body_util::BodyExt;
+
+use super::{
+    Body, ServerError,
+    codec::{self, CodecError},
+};
+use crate::validate::{Validate, Validator};
+
+/// The content type of the parts that are neither files nor text nor bytes.
+const JSON: &str = "application/json";
+
+/// The size of the largest part that a [`MultipartReader`] reads unless it is given another limit, 16 MiB.
+pub const DEFAULT_PART_SIZE_LIMIT: usize = 16 * 1024 * 1024;
+
+/// The size of the largest part that a server reads from a multipart request body.
+///
+/// Servers read the limit from the extensions of the request, so that a middleware layer can raise or lower it for the
+/// operations that it wraps. Requests without this extension use [`DEFAULT_PART_SIZE_LIMIT`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartSizeLimit(pub usize);
+
+/// A file sent as a part of a multipart body, which corresponds to the TypeSpec `Http.File` model.
+#[derive(Debug, Clone, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub struct File {
+    pub filename: Option<String>,
+    pub content_type: Option<String>,
+    pub contents: Bytes,
+}
+
+impl File {
+    pub fn new(contents: impl Into<Bytes>) -> Self {
+        Self {
+            filename: None,
+            content_type: None,
+            contents: contents.into(),
+        }
+    }
+
+    pub fn with_filename(mut self, filename: impl Into<String>) -> Self {
+        self.filename = Some(filename.into());
+        self
+    }
+
+    pub fn with_content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = Some(content_type.into());
+        self
+    }
+}
+
+impl Validate for File {
+    fn validate_into(&self, _: &mut Validator) {}
+}
+
+/// A value that can be sent as a multipart body.
+pub trait ToMultipart {
+    /// Adds the parts of this value to a form.
+    fn to_multipart(&self, form: &mut Form) -> Result<(), CodecError>;
+}
+
+/// A value that can be received as a multipart body.
+pub trait FromMultipart: Sized {
+    /// Reads the parts of a multipart body, ignoring parts that are not recognized.
+    fn from_multipart<B>(
+        reader: &mut MultipartReader<B>,
+    ) -> impl std::future::Future<Output = Result<Self, ReadError<B::Error>>> + Send
+    where
+        B: http_body::Body + Send;
+}
+
+/// An encoder for a `multipart/form-data` body.
+#[derive(Debug, Clone)]
+pub struct Form {
+    boundary: String,
+    chunks: Vec<Bytes>,
+}
+
+impl Default for Form {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Form {
+    /// Creates an empty form with a random boundary.
+    pub fn new() -> Self {
+        Self::with_boundary(generate_boundary())
+    }
+
+    pub fn with_boundary(boundary: impl Into<String>) -> Self {
+        Self {
+            boundary: boundary.into(),
+            chunks: Vec::new(),
+        }
+    }
+
+    /// Encodes a value as a form.
+    pub fn encode<T: ToMultipart + ?Sized>(value: &T) -> Result<Self, CodecError> {
+        let mut form = Self::new();
+
+        value.to_multipart(&mut form)?;
+
+        Ok(form)
+    }
+
+    pub fn boundary(&self) -> &str {
+        &self.boundary
+    }
+
+    /// Gets the value of the `Content-Type` header of the body of the form.
+    pub fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary)
+    }
+
+    /// Adds a part with the given file name, content type, and contents.
+    ///
+    /// Fails if the content type is not a valid header value, such as one that contains a line break.
+    pub fn part(
+        &mut self,
+        name: &str,
+        filename: Option<&str>,
+        content_type: Option<&str>,
+        contents: Bytes,
+    ) -> Result<(), CodecError> {
+        if let Some(content_type) = content_type {
+            http::HeaderValue::from_str(content_type).map_err(|_| {
+                CodecError::encode(MultipartError::InvalidContentType(content_type.to_string()))
+            })?;
+        }
+
+        self.push_part(name, filename, content_type, contents);
+
+        Ok(())
+    }
+
+    /// Adds a part whose content type is known to be a valid header value.
+    fn push_part(
+        &mut self,
+        name: &str,
+        filename: Option<&str>,
+        content_type: Option<&str>,
+        contents: Bytes,
+    ) {
+        let mut head = format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"{}\"",
+            self.boundary,
+            escape_parameter(name)
+        );
+
+        if let Some(filename) = filename {
+            head.push_str(&format!("; filename=\"{}\"", escape_parameter(filename)));
+        }
+
+        if let Some(content_type) = content_type {
+            head.push_str(&format!("\r\nContent-Type: {}", content_type));
+        }
+
+        head.push_str("\r\n\r\n");
+
+        self.chunks.push(head.into());
+        self.chunks.push(contents);
+        self.chunks.push(Bytes::from_static(b"\r\n"));
+    }
+
+    /// Adds a `text/plain` part.
+    pub fn text(&mut self, name: &str, value: &str) {
+        self.push_part(
+            name,
+            None,
+            Some("text/plain; charset=utf-8"),
+            Bytes::copy_from_slice(value.as_bytes()),
+        );
+    }
+
+    /// Adds an `application/octet-stream` part.
+    pub fn binary(&mut self, name: &str, contents: impl Into<Bytes>) {
+        self.push_part(
+            name,
+            None,
+            Some("application/octet-stream"),
+            contents.into(),
+        );
+    }
+
+    /// Adds an `application/json` part.
+    pub fn json<T: serde::Serialize + ?Sized>(
+        &mut self,
+        name: &str,
+        value: &T,
+    ) -> Result<(), CodecError> {
+        let contents = codec::encode(JSON, value)?;
+
+        self.push_part(name, None, Some(JSON), contents.into());
+
+        Ok(())
+    }
+
+    /// Adds a file part.
+    ///
+    /// Files without a content type are sent as `application/octet-stream`. Fails if the content type of the file is
+    /// not a valid header value.
+    pub fn file(&mut self, name: &str, file: &File) -> Result<(), CodecError> {
+        self.part(
+            name,
+            file.filename.as_deref(),
+            Some(
+                file.content_type
+                    .as_deref()
+                    .unwrap_or("application/octet-stream"),
+            ),
+            file.contents.clone(),
+        )
+    }
+
+    /// Converts the form into a body, which yields each part as it is sent.
+    pub fn into_body(mut self) -> Body {
+        self.chunks
+            .push(format!("--{}--\r\n", self.boundary).into());
+
+        let stream =
+            futures::stream::iter(self.chunks.into_iter().map(|chunk| Ok(Frame::data(chunk))));
+
+        Body::new(Box::pin(stream))
+    }
+}
+
+fn generate_boundary() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos())
+        .unwrap_or_default();
+
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(nanos);
+    let high = hasher.finish();
+    hasher.write_u64(high);
+    let low = hasher.finish();
+
+    format!("tsp-rust-{:016x}{:016x}", high, low)
+}
+
+/// Escapes a quoted parameter of the `Content-Disposition` header of a part, as browsers do.
+fn escape_parameter(value: &str) -> String {
+    value
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// An error in the contents of a multipart body.
+#[derive(Debug, thiserror::Error)]
+pub enum MultipartError {
+    #[error("the content type of the request does not declare a multipart boundary")]
+    MissingBoundary,
+    #[error("malformed multipart body: {0}")]
+    Malformed(&'static str),
+    #[error("missing required part '{0}'")]
+    MissingPart(&'static str),
+    #[error("invalid part '{0}': {1}")]
+    InvalidPart(String, #[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error("part content type '{0}' is not a valid header value")]
+    InvalidContentType(String),
+    #[error("a part is larger than the limit of {0} bytes")]
+    PartTooLarge(usize),
+}
+
+/// An error that occurs while reading a multipart body.
+#[derive(Debug, thiserror::Error)]
+pub enum ReadError<E> {
+    #[error("failed to read multipart body: {0}")]
+    Body(#[source] E),
+    #[error(transparent)]
+    Multipart(#[from] MultipartError),
+}
+
+/// A part of a multipart body.
+#[derive(Debug, Clone)]
+pub struct Part {
+    headers: http::HeaderMap,
+    name: Option<String>,
+    filename: Option<String>,
+    contents: Bytes,
+}
+
+impl Part {
+    /// Gets the name of the part from its `Content-Disposition` header.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Gets the file name of the part from its `Content-Disposition` header.
+    pub fn filename(&self) -> Option<&str> {
+        self.filename.as_deref()
+    }
+
+    pub fn content_type(&self) -> Option<&str> {
+        self.headers
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+    }
+
+    pub fn headers(&self) -> &http::HeaderMap {
+        &self.headers
+    }
+
+    pub fn contents(&self) -> &Bytes {
+        &self.contents
+    }
+
+    pub fn into_contents(self) -> Bytes {
+        self.contents
+    }
+
+    /// Reads the part as UTF-8 text.
+    pub fn text(self) -> Result<String, MultipartError> {
+        let name = self.name.unwrap_or_default();
+
+        String::from_utf8(self.contents.into())
+            .map_err(|error| MultipartError::InvalidPart(name, Box::new(error)))
+    }
+
+    /// Deserializes the part as JSON.
+    pub fn json<T: serde::de::DeserializeOwned>(self) -> Result<T, MultipartError> {
+        codec::decode(JSON, &self.contents).map_err(|error| {
+            MultipartError::InvalidPart(self.name.unwrap_or_default(), Box::new(error))
+        })
+    }
+
+    /// Reads the part as a file.
+    pub fn into_file(self) -> File {
+        File {
+            content_type: self.content_type().map(str::to_string),
+            filename: self.filename,
+            contents: self.contents,
+        }
+    }
+}
+
+/// A decoder for a `multipart/form-data` body.
+///
+/// The body is read as parts are requested, so only the part being read is held in memory. Parts, and the headers and
+/// preamble before them, are limited to [`DEFAULT_PART_SIZE_LIMIT`] bytes unless another limit is set with
+/// [`MultipartReader::with_part_size_limit`].
+pub struct MultipartReader<B> {
+    body: Pin<Box<B>>,
+    /// The delimiter that precedes each part, including the line break that ends the preceding part.
+    delimiter: Vec<u8>,
+    buffer: BytesMut,
+    state: ReaderState,
+    part_size_limit: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReaderState {
+    Preamble,
+    Delimiter,
+    Done,
+}
+
+impl<B: http_body::Body> MultipartReader<B> {
+    pub fn new(body: B, boundary: &str) -> Self {
+        Self {
+            body: Box::pin(body),
+            delimiter: format!("\r\n--{}", boundary).into_bytes(),
+            buffer: BytesMut::new(),
+            state: ReaderState::Preamble,
+            part_size_limit: DEFAULT_PART_SIZE_LIMIT,
+        }
+    }
+
+    /// Sets the size of the largest part that the reader accepts, in bytes.
+    pub fn with_part_size_limit(mut self, limit: usize) -> Self {
+        self.part_size_limit = limit;
+        self
+    }
+
+    /// Creates a reader for the body of a request, using the boundary declared by its `Content-Type` header, and the
+    /// [`PartSizeLimit`] in its extensions if it has one.
+    pub fn from_request(parts: &http::request::Parts, body: B) -> Result<Self, MultipartError> {
+        let boundary = parts
+            .headers
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_boundary)
+            .ok_or(MultipartError::MissingBoundary)?;
+
+        let limit = parts
+            .extensions
+            .get::<PartSizeLimit>()
+            .map_or(DEFAULT_PART_SIZE_LIMIT, |limit| limit.0);
+
+        Ok(Self::new(body, &boundary).with_part_size_limit(limit))
+    }
+
+    /// Reads the next part of the body, or returns `None` once every part has been read.
+    pub async fn next_part(&mut self) -> Result<Option<Part>, ReadError<B::Error>> {
+        if self.state == ReaderState::Preamble {
+            // The first delimiter may start the body, without a preceding line break.
+            self.buffer.extend_from_slice(b"\r\n");
+
+            let start = self
+                .read_until(0, &self.delimiter.clone())
+                .await?
+                .ok_or(MultipartError::Malformed("missing opening boundary"))?;
+
+            let _ = self.buffer.split_to(start);
+            self.state = ReaderState::Delimiter;
+        }
+
+        if self.state == ReaderState::Done {
+            return Ok(None);
+        }
+
+        // The buffer starts with a delimiter, which is followed either by `--` to close the body or by a line break.
+        let after_delimiter = self.delimiter.len();
+
+        let line_end = self
+            .read_until(after_delimiter, b"\r\n")
+            .await?
+            .ok_or(MultipartError::Malformed("unterminated boundary"))?;
+
+        if self.buffer[after_delimiter..].starts_with(b"--") {
+            self.state = ReaderState::Done;
+            return Ok(None);
+        }
+
+        let _ = self.buffer.split_to(line_end + 2);
+
+        let headers_end = self
+            .read_until(0, b"\r\n\r\n")
+            .await?
+            .ok_or(MultipartError::Malformed("unterminated part headers"))?;
+
+        let headers = parse_headers(&self.buffer[..headers_end])?;
+        let _ = self.buffer.split_to(headers_end + 4);
+
+        let contents_end = self
+            .read_until(0, &self.delimiter.clone())
+            .await?
+            .ok_or(MultipartError::Malformed("missing closing boundary"))?;
+
+        let contents = self.buffer.split_to(contents_end).freeze();
+
+        let (name, filename) = headers
+            .get(http::header::CONTENT_DISPOSITION)
+            .and_then(|value| value.to_str().ok())
+            .map(parse_content_disposition)
+            .unwrap_or_default();
+
+        Ok(Some(Part {
+            headers,
+            name,
+            filename,
+            contents,
+        }))
+    }
+
+    /// Reads the body until the buffer contains a pattern at or after an offset, returning where it starts.
+    ///
+    /// Fails once more than the part size limit has been read past the offset without finding the pattern, so that a
+    /// body without delimiters is not buffered in full.
+    async fn read_until(
+        &mut self,
+        offset: usize,
+        pattern: &[u8],
+    ) -> Result<Option<usize>, ReadError<B::Error>> {
+        let mut searched = offset;
+
+        loop {
+            if let Some(position) = find(&self.buffer[searched.min(self.buffer.len())..], pattern) {
+                let start = searched + position;
+
+                if start - offset > self.part_size_limit {
+                    return Err(MultipartError::PartTooLarge(self.part_size_limit).into());
+                }
+
+                return Ok(Some(start));
+            }
+
+            if self.buffer.len() - offset.min(self.buffer.len())
+                > self.part_size_limit + pattern.len()
+            {
+                return Err(MultipartError::PartTooLarge(self.part_size_limit).into());
+            }
+
+            // A match may straddle the end of the buffer, so the tail is searched again once more data arrives.
+            searched = offset.max((self.buffer.len() + 1).saturating_sub(pattern.len()));
+
+            if !self.fill().await? {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Appends the next data frame of the body to the buffer, returning false at the end of the body.
+    async fn fill(&mut self) -> Result<bool, ReadError<B::Error>> {
+        use bytes::Buf;
+
+        loop {
+            match self.body.frame().await {
+                None => return Ok(false),
+                Some(Err(error)) => return Err(ReadError::Body(error)),
+                Some(Ok(frame)) => {
+                    if let Ok(mut data) = frame.into_data() {
+                        while data.has_remaining() {
+                            let chunk = data.chunk();
+                            let len = chunk.len();
+
+                            self.buffer.extend_from_slice(chunk);
+                            data.advance(len);
+                        }
+
+                        return Ok(true);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn parse_headers(data: &[u8]) -> Result<http::HeaderMap, MultipartError> {
+    let mut headers = http::HeaderMap::new();
+
+    for line in data.split(|&b| b == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+
+        if line.is_empty() {
+            continue;
+        }
+
+        let colon = line
+            .iter()
+            .position(|&b| b == b':')
+            .ok_or(MultipartError::Malformed("invalid part header"))?;
+
+        let name = http::HeaderName::from_bytes(&line[..colon])
+            .map_err(|_| MultipartError::Malformed("invalid part header name"))?;
+        let value = http::HeaderValue::from_bytes(line[colon + 1..].trim_ascii())
+            .map_err(|_| MultipartError::Malformed("invalid part header value"))?;
+
+        headers.append(name, value);
+    }
+
+    Ok(headers)
+}
+
+/// Gets the boundary parameter of a `multipart/form-data` content type.
+pub fn parse_boundary(content_type: &str) -> Option<String> {
+    let mut params = content_type.split(';');
+
+    let essence = params.next()?.trim();
+
+    if !essence.eq_ignore_ascii_case("multipart/form-data") {
+        return None;
+    }
+
+    params.find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+
+        name.trim()
+            .eq_ignore_ascii_case("boundary")
+            .then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+/// Gets the `name` and `filename` parameters of a `Content-Disposition` header.
+fn parse_content_disposition(value: &str) -> (Option<String>, Option<String>) {
+    let mut name = None;
+    let mut filename = None;
+
+    let mut rest = value;
+
+    // Skip the disposition type.
+    if let Some(index) = rest.find(';') {
+        rest = &rest[index + 1..];
+    } else {
+        return (None, None);
+    }
+
+    loop {
+        rest = rest.trim_start();
+
+        let Some((param, after)) = rest.split_once('=') else {
+            break;
+        };
+
+        let param = param.trim().to_ascii_lowercase();
+        let after = after.trim_start();
+
+        let (value, remaining) = if let Some(quoted) = after.strip_prefix('"') {
+            let mut value = String::new();
+            let mut chars = quoted.char_indices();
+            let mut end = quoted.len();
+
+            while let Some((index, c)) = chars.next() {
+                match c {
+                    '\\' => {
+                        if let Some((_, escaped)) = chars.next() {
+                            value.push(escaped);
+                        }
+                    }
+                    '"' => {
+                        end = index + 1;
+                        break;
+                    }
+                    c => value.push(c),
+                }
+            }
+
+            (value, &quoted[end..])
+        } else {
+            let end = after.find(';').unwrap_or(after.len());
+
+            (after[..end].trim().to_string(), &after[end..])
+        };
+
+        match param.as_str() {
+            "name" => name = Some(value),
+            "filename" => filename = Some(value),
+            _ => {}
+        }
+
+        match remaining.find(';') {
+            Some(index) => rest = &remaining[index + 1..],
+            None => break,
+        }
+    }
+
+    (name, filename)
+}
+
+/// Reads a multipart request body on the server.
+pub async fn deserialize_multipart_server<
+    T: FromMultipart,
+    B: http_body::Body + Send,
+    OperationError: std::error::Error,
+>(
+    parts: &http::request::Parts,
+    body: B,
+) -> Result<T, ServerError<B, OperationError>> {
+    let content_type = parts
+        .headers
+        .get(http::header::CONTENT_TYPE)
+        .map(|h| h.to_str().map(str::to_string).unwrap_or_default());
+
+    let mut reader = MultipartReader::from_request(parts, body)
+        .map_err(|_| ServerError::UnsupportedContentType(content_type))?;
+
+    T::from_multipart(&mut reader)
+        .await
+        .map_err(|error| match error {
+            ReadError::Body(error) => ServerError::Body(error),
+            ReadError::Multipart(error) => ServerError::Multipart(error),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &[u8] = b"--b\r\n\
+        Content-Disposition: form-data; name=\"name\"\r\n\
+        \r\n\
+        Fido\r\n\
+        --b\r\n\
+        Content-Disposition: form-data; name=\"photo\"; filename=\"fido.png\"\r\n\
+        Content-Type: image/png\r\n\
+        \r\n\
+        \x89PNG\r\n--not-a-delimiter\r\n\
+        --b--\r\n";
+
+    fn body(frames: &[&[u8]]) -> Body {
+        let frames: Vec<_> = frames
+            .iter()
+            .map(|frame| Ok(Frame::data(Bytes::copy_from_slice(frame))))
+            .collect();
+
+        Body::new(Box::pin(futures::stream::iter(frames)))
+    }
+
+    fn read_all(body: Body, boundary: &str) -> Result<Vec<Part>, ReadError<std::io::Error>> {
+        futures::executor::block_on(async {
+            let mut reader = MultipartReader::new(body, boundary);
+            let mut parts = Vec::new();
+
+            while let Some(part) = reader.next_part().await? {
+                parts.push(part);
+            }
+
+            Ok(parts)
+        })
+    }
+
+    fn assert_parts(parts: &[Part]) {
+        assert_eq!(parts.len(), 2);
+
+        assert_eq!(parts[0].name(), Some("name"));
+        assert_eq!(parts[0].filename(), None);
+        assert_eq!(parts[0].contents().as_ref(), b"Fido");
+
+        assert_eq!(parts[1].name(), Some("photo"));
+        assert_eq!(parts[1].filename(), Some("fido.png"));
+        assert_eq!(parts[1].content_type(), Some("image/png"));
+        assert_eq!(
+            parts[1].contents().as_ref(),
+            b"\x89PNG\r\n--not-a-delimiter"
+        );
+    }
+
+    #[test]
+    fn reads_body_without_preamble() {
+        assert_parts(&read_all(body(&[BODY]), "b").unwrap());
+    }
+
+    #[test]
+    fn reads_body_with_preamble() {
+        let parts = read_all(body(&[b"This is a preamble.\r\n", BODY]), "b").unwrap();
+
+        assert_parts(&parts);
+    }
+
+    #[test]
+    fn reads_delimiters_split_across_frames() {
+        for split in 0..=BODY.len() {
+            let (first, second) = BODY.split_at(split);
+
+            assert_parts(&read_all(body(&[first, second]), "b").unwrap());
+        }
+    }
+
+    #[test]
+    fn reads_body_one_byte_at_a_time() {
+        let frames: Vec<_> = BODY.chunks(1).collect();
+
+        assert_parts(&read_all(body(&frames), "b").unwrap());
+    }
+
+    #[test]
+    fn rejects_missing_closing_boundary() {
+        let truncated = b"--b\r\nContent-Disposition: form-data; name=\"name\"\r\n\r\nFido";
+
+        assert!(matches!(
+            read_all(body(&[truncated]), "b"),
+            Err(ReadError::Multipart(MultipartError::Malformed(_)))
+        ));
+
+        let unterminated =
+            b"--b\r\nContent-Disposition: form-data; name=\"name\"\r\n\r\nFido\r\n--b";
+
+        assert!(matches!(
+            read_all(body(&[unterminated]), "b"),
+            Err(ReadError::Multipart(MultipartError::Malformed(_)))
+        ));
+    }
+
+    #[test]
+    fn rejects_missing_opening_boundary() {
+        assert!(matches!(
+            read_all(body(&[b"no parts here"]), "b"),
+            Err(ReadError::Multipart(MultipartError::Malformed(_)))
+        ));
+    }
+
+    #[test]
+    fn parses_quoted_and_escaped_filenames() {
+        assert_eq!(
+            parse_content_disposition(r#"form-data; name="photo"; filename="a \"b\"; c.png""#),
+            (
+                Some("photo".to_string()),
+                Some(r#"a "b"; c.png"#.to_string())
+            )
+        );
+
+        assert_eq!(
+            parse_content_disposition("form-data; name=photo; filename=fido.png"),
+            (Some("photo".to_string()), Some("fido.png".to_string()))
+        );
+
+        assert_eq!(parse_content_disposition("form-data"), (None, None));
+    }
+
+    #[test]
+    fn parses_boundary() {
+        assert_eq!(
+            parse_boundary("multipart/form-data; boundary=abc").as_deref(),
+            Some("abc")
+        );
+        assert_eq!(
+            parse_boundary("Multipart/Form-Data; charset=utf-8; Boundary=\"a b\"").as_deref(),
+            Some("a b")
+        );
+        assert_eq!(parse_boundary("multipart/form-data"), None);
+        assert_eq!(parse_boundary("application/json; boundary=abc"), None);
+    }
+
+    #[test]
+    fn round_trips_form() {
+        let mut form = Form::with_boundary("tsp-rust-boundary");
+
+        form.text("name", "Fido \"the dog\"");
+        form.binary("tag", &b"\r\n--tsp-rust\r\n"[..]);
+        form.json("age", &3).unwrap();
+        form.file(
+            "photo",
+            &File::new(&b"\x89PNG"[..])
+                .with_filename("fido \"1\".png")
+                .with_content_type("image/png"),
+        )
+        .unwrap();
+
+        let content_type = form.content_type();
+        let boundary = parse_boundary(&content_type).unwrap();
+
+        let parts = read_all(form.into_body(), &boundary).unwrap();
+
+        assert_eq!(parts.len(), 4);
+
+        assert_eq!(parts[0].name(), Some("name"));
+        assert_eq!(parts[0].content_type(), Some("text/plain; charset=utf-8"));
+        assert_eq!(parts[0].clone().text().unwrap(), "Fido \"the dog\"");
+
+        assert_eq!(parts[1].name(), Some("tag"));
+        assert_eq!(parts[1].content_type(), Some("application/octet-stream"));
+        assert_eq!(parts[1].contents().as_ref(), b"\r\n--tsp-rust\r\n");
+
+        assert_eq!(parts[2].name(), Some("age"));
+        assert_eq!(parts[2].clone().json::<u32>().unwrap(), 3);
+
+        assert_eq!(parts[3].name(), Some("photo"));
+        assert_eq!(
+            parts[3].clone().into_file(),
+            File::new(&b"\x89PNG"[..])
+                .with_filename("fido %221%22.png")
+                .with_content_type("image/png")
+        );
+    }
+
+    #[test]
+    fn rejects_content_types_that_are_not_header_values() {
+        let mut form = Form::with_boundary("b");
+
+        let file = File::new(&b"x"[..]).with_content_type("text/plain\r\nX-Injected: 1");
+
+        assert!(matches!(
+            form.file("photo", &file),
+            Err(CodecError::Encode(_))
+        ));
+        assert!(
+            form.part("name", None, Some("text/plain\n"), Bytes::new())
+                .is_err()
+        );
+        assert!(form.chunks.is_empty());
+    }
+
+    #[test]
+    fn rejects_parts_over_the_size_limit() {
+        let read = |frames: &[&[u8]], limit| {
+            futures::executor::block_on(async {
+                let mut reader =
+                    MultipartReader::new(body(frames), "b").with_part_size_limit(limit);
+                let mut parts = Vec::new();
+
+                while let Some(part) = reader.next_part().await? {
+                    parts.push(part);
+                }
+
+                Ok::<_, ReadError<std::io::Error>>(parts)
+            })
+        };
+
+        let bounded = b"--b\r\nA: 1\r\n\r\n0123456789\r\n--b--\r\n";
+
+        assert_eq!(
+            read(&[bounded], 10).unwrap()[0].contents().as_ref(),
+            b"0123456789"
+        );
+
+        assert!(matches!(
+            read(&[bounded], 9),
+            Err(ReadError::Multipart(MultipartError::PartTooLarge(9)))
+        ));
+
+        // A body that never delimits its part fails once the limit is exceeded, rather than at its end.
+        let unbounded = [&b"--b\r\nA: 1\r\n\r\n"[..], &[b'x'; 64], &[b'x'; 64]];
+
+        assert!(matches!(
+            read(&unbounded, 16),
+            Err(ReadError::Multipart(MultipartError::PartTooLarge(16)))
+        ));
+    }
+}
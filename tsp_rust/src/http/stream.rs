@@ -0,0 +1,335 @@
+//! Streaming `bytes` bodies.
+//!
+//! Operations whose body is `bytes` sent with a binary content type, such as `application/octet-stream`, take an
+//! [`AsyncRead`] rather than a buffer. Generated clients send the data as it is read with [`Body::from_reader`], and
+//! generated servers pass the body to operations as a [`BodyReader`], so neither end holds the whole payload in memory.
+//!
+//! Responses are streamed the same way: a response model whose body is such a `bytes` property holds a [`ByteStream`],
+//! which servers send with [`Body::from_reader`] and clients read from the response body as it arrives.
+
+use std::{
+    fmt, io,
+    pin::Pin,
+    sync::{Mutex, PoisonError},
+    task::{Context, Poll, ready},
+};
+
+use bytes::{Buf, Bytes};
+use futures::{AsyncRead, AsyncReadExt, Stream};
+use http_body::Frame;
+
+use super::{Body, Responder, ServerError, accept};
+
+/// The size of the chunks that a reader body is read in.
+const CHUNK_SIZE: usize = 16 * 1024;
+
+impl Body {
+    /// Creates a body that streams the data read from a reader.
+    ///
+    /// A read error ends the body with that error, which aborts the request or response that carries it.
+    pub fn from_reader(reader: impl AsyncRead + Send + Sync + 'static) -> Self {
+        let stream = futures::stream::try_unfold(Box::pin(reader), |mut reader| async move {
+            let mut buffer = vec![0; CHUNK_SIZE];
+
+            let read = reader.read(&mut buffer).await?;
+
+            if read == 0 {
+                return Ok(None);
+            }
+
+            buffer.truncate(read);
+
+            Ok(Some((Frame::data(Bytes::from(buffer)), reader)))
+        });
+
+        Self::new(Box::pin(stream))
+    }
+}
+
+/// An [`AsyncRead`] over the data of an HTTP body.
+///
+/// The reader is also a [`Stream`] of the chunks of data that make up the body, for operations that forward them
+/// without copying.
+pub struct BodyReader<B> {
+    body: Pin<Box<B>>,
+    chunk: Bytes,
+}
+
+impl<B: http_body::Body> BodyReader<B> {
+    pub fn new(body: B) -> Self {
+        Self {
+            body: Box::pin(body),
+            chunk: Bytes::new(),
+        }
+    }
+}
+
+impl<B> BodyReader<B>
+where
+    B: http_body::Body,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    /// Polls the body for the next chunk of data, skipping trailers and empty frames.
+    fn poll_chunk(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<Option<Bytes>>> {
+        if !self.chunk.is_empty() {
+            return Poll::Ready(Ok(Some(std::mem::take(&mut self.chunk))));
+        }
+
+        loop {
+            match ready!(self.body.as_mut().poll_frame(cx)) {
+                None => return Poll::Ready(Ok(None)),
+                Some(Err(error)) => return Poll::Ready(Err(io::Error::other(error))),
+                Some(Ok(frame)) => {
+                    if let Ok(mut data) = frame.into_data()
+                        && data.has_remaining()
+                    {
+                        let len = data.remaining();
+                        return Poll::Ready(Ok(Some(data.copy_to_bytes(len))));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<B> AsyncRead for BodyReader<B>
+where
+    B: http_body::Body,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+
+        let Some(mut chunk) = ready!(this.poll_chunk(cx))? else {
+            return Poll::Ready(Ok(0));
+        };
+
+        let len = chunk.len().min(buf.len());
+        chunk.copy_to_slice(&mut buf[..len]);
+
+        // Whatever did not fit is kept for the next read.
+        this.chunk = chunk;
+
+        Poll::Ready(Ok(len))
+    }
+}
+
+impl<B> Stream for BodyReader<B>
+where
+    B: http_body::Body,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    type Item = io::Result<Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_chunk(cx).map(Result::transpose)
+    }
+}
+
+/// The data of a streamed `bytes` response body.
+///
+/// Servers build one from any reader with [`ByteStream::new`] and send it as it is read. Clients receive one that reads
+/// the response body as it arrives.
+pub struct ByteStream {
+    // The mutex is never locked: it only makes the stream `Sync` without requiring the same of its reader, since the
+    // reader is only ever reached through `&mut self`.
+    reader: Mutex<Pin<Box<dyn AsyncRead + Send>>>,
+}
+
+impl ByteStream {
+    /// Creates a stream of the data read from a reader.
+    pub fn new(reader: impl AsyncRead + Send + 'static) -> Self {
+        Self {
+            reader: Mutex::new(Box::pin(reader)),
+        }
+    }
+
+    /// Creates a stream of the data of an HTTP body.
+    pub fn from_body<B>(body: B) -> Self
+    where
+        B: http_body::Body + Send + 'static,
+        B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        Self::new(BodyReader::new(body))
+    }
+}
+
+impl Default for ByteStream {
+    /// Creates an empty stream.
+    fn default() -> Self {
+        Self::new(futures::io::empty())
+    }
+}
+
+impl fmt::Debug for ByteStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ByteStream").finish_non_exhaustive()
+    }
+}
+
+impl AsyncRead for ByteStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let reader = self
+            .get_mut()
+            .reader
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        reader.as_mut().poll_read(cx, buf)
+    }
+}
+
+impl Responder for ByteStream {
+    fn to_response<B: http_body::Body, E: std::error::Error>(
+        self,
+        _: &accept::Accept,
+    ) -> Result<http::Response<Body>, ServerError<B, E>> {
+        Ok(http::Response::builder()
+            .status(http::StatusCode::OK)
+            .header(http::header::CONTENT_TYPE, "application/octet-stream")
+            .body(Body::from_reader(self))
+            .unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{StreamExt, executor::block_on};
+    use http_body_util::{BodyExt, StreamBody};
+
+    use super::*;
+
+    /// A body made of the given frames, which ends with an error if `error` is set.
+    fn body(
+        chunks: &[&'static [u8]],
+        error: bool,
+    ) -> StreamBody<impl Stream<Item = io::Result<Frame<Bytes>>>> {
+        let frames = chunks
+            .iter()
+            .map(|chunk| Ok(Frame::data(Bytes::from_static(chunk))))
+            .chain(error.then(|| Err(io::Error::other("connection reset"))));
+
+        StreamBody::new(futures::stream::iter(frames.collect::<Vec<_>>()))
+    }
+
+    /// A reader that yields some data and then fails.
+    struct FailingReader(bool);
+
+    impl AsyncRead for FailingReader {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            let this = self.get_mut();
+
+            if std::mem::replace(&mut this.0, true) {
+                return Poll::Ready(Err(io::Error::other("disk unplugged")));
+            }
+
+            buf[..4].copy_from_slice(b"data");
+
+            Poll::Ready(Ok(4))
+        }
+    }
+
+    #[test]
+    fn from_reader_streams_data_in_chunks() {
+        let data = (0..CHUNK_SIZE * 2 + 100)
+            .map(|i| i as u8)
+            .collect::<Vec<_>>();
+
+        let mut body = Body::from_reader(futures::io::Cursor::new(data.clone()));
+
+        let mut chunks = Vec::new();
+
+        while let Some(frame) = block_on(body.frame()) {
+            chunks.push(frame.unwrap().into_data().unwrap());
+        }
+
+        assert_eq!(
+            chunks.iter().map(Bytes::len).collect::<Vec<_>>(),
+            [CHUNK_SIZE, CHUNK_SIZE, 100]
+        );
+        assert_eq!(chunks.concat(), data);
+    }
+
+    #[test]
+    fn from_reader_ends_the_body_with_a_read_error() {
+        let mut body = Body::from_reader(FailingReader(false));
+
+        let first = block_on(body.frame()).unwrap().unwrap();
+        assert_eq!(first.into_data().unwrap(), "data");
+
+        let error = block_on(body.frame()).unwrap().unwrap_err();
+        assert_eq!(error.to_string(), "disk unplugged");
+    }
+
+    #[test]
+    fn body_reader_carries_over_what_does_not_fit() {
+        let mut reader = BodyReader::new(body(&[b"hello", b"", b"world"], false));
+
+        let mut reads = Vec::new();
+        let mut buffer = [0; 3];
+
+        loop {
+            let read = block_on(reader.read(&mut buffer)).unwrap();
+
+            if read == 0 {
+                break;
+            }
+
+            reads.push(buffer[..read].to_vec());
+        }
+
+        assert_eq!(reads, [&b"hel"[..], b"lo", b"wor", b"ld"]);
+    }
+
+    #[test]
+    fn body_reader_streams_the_rest_of_a_partly_read_chunk() {
+        let mut reader = BodyReader::new(body(&[b"hello", b"world"], false));
+
+        let mut buffer = [0; 2];
+        block_on(reader.read(&mut buffer)).unwrap();
+        assert_eq!(&buffer, b"he");
+
+        let chunks = block_on(reader.map(Result::unwrap).collect::<Vec<_>>());
+        assert_eq!(chunks, ["llo", "world"]);
+    }
+
+    #[test]
+    fn body_reader_reports_body_errors() {
+        let mut reader = BodyReader::new(body(&[b"hello"], true));
+
+        let mut data = Vec::new();
+        let error = block_on(reader.read_to_end(&mut data)).unwrap_err();
+
+        assert_eq!(data, b"hello");
+        assert_eq!(error.to_string(), "connection reset");
+    }
+
+    #[test]
+    fn byte_stream_reads_a_body_and_responds_with_it() {
+        let stream = ByteStream::from_body(body(&[b"hello", b"world"], false));
+
+        let response = stream
+            .to_response::<Body, io::Error>(&accept::Accept::default())
+            .unwrap();
+
+        assert_eq!(
+            response.headers()[http::header::CONTENT_TYPE],
+            "application/octet-stream"
+        );
+
+        let data = block_on(response.into_body().collect()).unwrap().to_bytes();
+        assert_eq!(data, "helloworld");
+    }
+}
@@ -12,16 +12,23 @@ static FEATURES: [&str];
 #[distributed_slice(FEATURES)]
 static HTTP_FEATURE: &str = "http";
 
+pub mod patch;
+pub mod serialize;
+pub mod validate;
+
 pub mod vendored {
     pub use bigdecimal;
     pub use chrono;
     pub use futures;
     pub use itertools;
     pub use log;
+    pub use num_bigint;
+    pub use regex;
     pub use serde;
     pub use serde_json;
     pub use serde_with;
     pub use thiserror;
+    pub use url;
 
     #[cfg(feature = "http")]
     pub use super::http::vendored::*;
@@ -30,6 +37,78 @@ pub mod vendored {
 pub trait OperationFuture<T, E>: Future<Output = Result<T, E>> + Send {}
 impl<R: Future<Output = Result<T, E>> + Send, T, E> OperationFuture<T, E> for R {}
 
+/// Recovers an operation's declared error from the error type of a service trait.
+///
+/// Servers use this to send declared errors to the client as their modeled responses. Any other error is reported as
+/// a server failure.
+pub trait IntoOperationError<E>: Sized {
+    /// Returns the declared error carried by `self`, or `self` unchanged if it does not carry one.
+    fn into_operation_error(self) -> Result<E, Self>;
+}
+
+impl<E> IntoOperationError<E> for std::convert::Infallible {
+    fn into_operation_error(self) -> Result<E, Self> {
+        match self {}
+    }
+}
+
+/// A general-purpose error type for implementations of service traits.
+///
+/// `Operation` carries one of the errors declared by an operation, while `Service` carries any other failure.
+pub enum Error<E> {
+    Operation(E),
+    Service(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl<E> Error<E> {
+    /// Wraps an arbitrary error as a service failure.
+    pub fn service(error: impl std::error::Error + Send + Sync + 'static) -> Self {
+        Self::Service(Box::new(error))
+    }
+}
+
+impl<E> From<E> for Error<E> {
+    fn from(error: E) -> Self {
+        Self::Operation(error)
+    }
+}
+
+impl<E> IntoOperationError<E> for Error<E> {
+    fn into_operation_error(self) -> Result<E, Self> {
+        match self {
+            Self::Operation(error) => Ok(error),
+            error => Err(error),
+        }
+    }
+}
+
+impl<E: std::fmt::Debug> std::fmt::Debug for Error<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Operation(arg0) => f.debug_tuple("Operation").field(arg0).finish(),
+            Self::Service(arg0) => f.debug_tuple("Service").field(arg0).finish(),
+        }
+    }
+}
+
+impl<E: std::fmt::Debug> std::fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Operation(err) => write!(f, "Operation error: {:?}", err),
+            Error::Service(err) => write!(f, "Service error: {}", err),
+        }
+    }
+}
+
+impl<E: std::fmt::Debug> std::error::Error for Error<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Service(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(feature = "http")]
 pub mod http {
     use std::{convert::Infallible, pin::Pin};
@@ -38,8 +117,15 @@ pub mod http {
     use futures::Stream;
     use http_body::Frame;
     use http_body_util::StreamBody;
+    use itertools::Itertools;
     use serde::{Deserialize, Serialize};
 
+    pub mod accept;
+    pub mod auth;
+    pub mod codec;
+    pub mod multipart;
+    pub mod stream;
+
     pub mod vendored {
         pub use bytes;
         pub use eyes;
@@ -48,7 +134,6 @@ pub mod http {
         pub use http_body_util;
         pub use reqwest;
         pub use tower;
-        pub use url;
     }
 
     pub trait Service<ResponseBody: http_body::Body>:
@@ -60,20 +145,167 @@ pub mod http {
     {
     }
 
-    pub type Body =
-        StreamBody<Pin<Box<dyn Stream<Item = Result<Frame<Bytes>, Infallible>> + Send + Sync>>>;
+    /// The stream of frames carried by a [`Body`].
+    ///
+    /// Bodies that are already in memory never fail. Streamed bodies fail with the error of their source.
+    pub type BodyStream =
+        Pin<Box<dyn Stream<Item = Result<Frame<Bytes>, std::io::Error>> + Send + Sync>>;
+
+    /// The body of the requests and responses produced by generated code.
+    ///
+    /// This is a distinct type rather than an alias of `StreamBody` so that it carries no lifetimes. Generic code that
+    /// holds a request or a service future across an await point (such as an `async fn` implementing a service trait
+    /// for a client) can then be proven `Send`.
+    pub struct Body(StreamBody<BodyStream>);
+
+    impl Body {
+        pub fn new(stream: BodyStream) -> Self {
+            Self(StreamBody::new(stream))
+        }
+    }
+
+    impl http_body::Body for Body {
+        type Data = Bytes;
+        type Error = std::io::Error;
+
+        fn poll_frame(
+            mut self: Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+            Pin::new(&mut self.0).poll_frame(cx)
+        }
+
+        fn is_end_stream(&self) -> bool {
+            http_body::Body::is_end_stream(&self.0)
+        }
+
+        fn size_hint(&self) -> http_body::SizeHint {
+            http_body::Body::size_hint(&self.0)
+        }
+    }
 
-    #[derive(Debug)]
     pub enum Error<Body: http_body::Body, ServiceError, OperationError> {
-        Serialize(serde_json::Error),
-        Deserialize(serde_json::Error),
+        Serialize(codec::CodecError),
+        Deserialize(codec::CodecError),
         Body(Body::Error),
         Service(ServiceError),
         Operation(OperationError),
+        Parts(PartsError),
+        Validation(crate::validate::ValidationError),
         UnexpectedStatus(u16, http::response::Parts),
         UnexpectedContentType(Option<String>, http::response::Parts),
     }
 
+    impl<B: http_body::Body, ServiceError, OperationError> crate::IntoOperationError<OperationError>
+        for Error<B, ServiceError, OperationError>
+    {
+        fn into_operation_error(self) -> Result<OperationError, Self> {
+            match self {
+                Self::Operation(error) => Ok(error),
+                error => Err(error),
+            }
+        }
+    }
+
+    impl<B: http_body::Body, ServiceError: std::fmt::Debug, OperationError: std::fmt::Debug>
+        std::fmt::Debug for Error<B, ServiceError, OperationError>
+    where
+        B::Error: std::fmt::Debug,
+    {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Self::Serialize(arg0) => f.debug_tuple("Serialize").field(arg0).finish(),
+                Self::Deserialize(arg0) => f.debug_tuple("Deserialize").field(arg0).finish(),
+                Self::Body(arg0) => f.debug_tuple("Body").field(arg0).finish(),
+                Self::Service(arg0) => f.debug_tuple("Service").field(arg0).finish(),
+                Self::Operation(arg0) => f.debug_tuple("Operation").field(arg0).finish(),
+                Self::Parts(arg0) => f.debug_tuple("Parts").field(arg0).finish(),
+                Self::Validation(arg0) => f.debug_tuple("Validation").field(arg0).finish(),
+                Self::UnexpectedStatus(arg0, arg1) => f
+                    .debug_tuple("UnexpectedStatus")
+                    .field(arg0)
+                    .field(arg1)
+                    .finish(),
+                Self::UnexpectedContentType(arg0, arg1) => f
+                    .debug_tuple("UnexpectedContentType")
+                    .field(arg0)
+                    .field(arg1)
+                    .finish(),
+            }
+        }
+    }
+
+    impl<B: http_body::Body, ServiceError: std::fmt::Display, OperationError: std::fmt::Debug>
+        std::fmt::Display for Error<B, ServiceError, OperationError>
+    where
+        B::Error: std::fmt::Display,
+    {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Error::Serialize(err) => write!(f, "Failed to serialize request: {}", err),
+                Error::Deserialize(err) => write!(f, "Failed to deserialize response: {}", err),
+                Error::Body(err) => write!(f, "Failed to read response body: {}", err),
+                Error::Service(err) => write!(f, "Service error: {}", err),
+                Error::Operation(err) => write!(f, "Operation error: {:?}", err),
+                Error::Parts(err) => write!(f, "Invalid response: {}", err),
+                Error::Validation(err) => write!(f, "Invalid request: {}", err),
+                Error::UnexpectedStatus(status, _) => {
+                    write!(f, "Unexpected response status {}", status)
+                }
+                Error::UnexpectedContentType(Some(content_type), _) => {
+                    write!(f, "Unexpected response content type '{}'", content_type)
+                }
+                Error::UnexpectedContentType(None, _) => write!(f, "Missing response content type"),
+            }
+        }
+    }
+
+    impl<
+        B: http_body::Body,
+        ServiceError: std::error::Error + 'static,
+        OperationError: std::fmt::Debug,
+    > std::error::Error for Error<B, ServiceError, OperationError>
+    where
+        B::Error: std::error::Error + 'static,
+    {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            match self {
+                Self::Serialize(err) | Self::Deserialize(err) => Some(err),
+                Self::Body(err) => Some(err),
+                Self::Service(err) => Some(err),
+                Self::Parts(err) => Some(err),
+                Self::Validation(err) => Some(err),
+                _ => None,
+            }
+        }
+    }
+
+    /// Options that control how the generated client functions send a request.
+    #[derive(Debug, Clone)]
+    pub struct RequestOptions {
+        /// Whether to check model arguments against their TypeSpec constraints before sending the request.
+        ///
+        /// Enabled by default. Disable it on performance-sensitive paths where the arguments are known to be valid.
+        pub validate: bool,
+    }
+
+    impl Default for RequestOptions {
+        fn default() -> Self {
+            Self { validate: true }
+        }
+    }
+
+    impl RequestOptions {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn with_validation(mut self, validate: bool) -> Self {
+            self.validate = validate;
+            self
+        }
+    }
+
     pub async fn send_request<ResponseBody: http_body::Body, S: Service<ResponseBody>, E>(
         service: &mut S,
         request: http::Request<Body>,
@@ -85,67 +317,526 @@ pub mod http {
         service.call(request).await.map_err(Error::Service)
     }
 
-    pub fn serialize_json_body<T: serde::Serialize>(body: T) -> Result<Body, serde_json::Error> {
-        let data = serde_json::to_vec(&body)?;
+    /// The configuration of a server declared with `@server`, which resolves the server URL from its parameters.
+    pub trait ServerConfig {
+        fn url(&self) -> String;
+
+        fn uri(&self) -> Result<http::Uri, http::uri::InvalidUri> {
+            self.url().parse()
+        }
+    }
+
+    /// The characters that are escaped in a parameter of the path of a server URL: all but the unreserved characters of
+    /// RFC 3986.
+    const SERVER_PATH_PARAMETER: &percent_encoding::AsciiSet = &percent_encoding::NON_ALPHANUMERIC
+        .remove(b'-')
+        .remove(b'.')
+        .remove(b'_')
+        .remove(b'~');
+
+    /// Formats a parameter of a server URL that is part of its scheme or authority, like the `{endpoint}` of
+    /// `{endpoint}/api`, which is inserted as it is.
+    ///
+    /// Strings, and enums and unions of string literals, are inserted as their value. Any other value is inserted as
+    /// its JSON text.
+    pub fn format_server_parameter<T: Serialize + ?Sized>(value: &T) -> String {
+        match serde_json::to_value(value) {
+            Ok(serde_json::Value::String(text)) => text,
+            Ok(value) => value.to_string(),
+            // Generated types are always representable as JSON.
+            Err(e) => unreachable!("server parameter is not representable as JSON: {}", e),
+        }
+    }
+
+    /// Formats a parameter of a server URL that is part of its path, like the `{version}` of `{endpoint}/{version}`,
+    /// escaping the characters that are reserved in a path segment.
+    pub fn format_server_path_parameter<T: Serialize + ?Sized>(value: &T) -> String {
+        percent_encoding::utf8_percent_encode(
+            &format_server_parameter(value),
+            SERVER_PATH_PARAMETER,
+        )
+        .to_string()
+    }
+
+    /// A service that resolves the path-only URIs of generated requests against a base URL.
+    ///
+    /// Requests that already have an absolute URI are passed through unchanged.
+    #[derive(Debug, Clone)]
+    pub struct BaseUrl<S> {
+        base_url: http::Uri,
+        inner: S,
+    }
+
+    impl<S> BaseUrl<S> {
+        pub fn new(base_url: http::Uri, inner: S) -> Self {
+            Self { base_url, inner }
+        }
+
+        pub fn base_url(&self) -> &http::Uri {
+            &self.base_url
+        }
+
+        pub fn get_ref(&self) -> &S {
+            &self.inner
+        }
+
+        pub fn get_mut(&mut self) -> &mut S {
+            &mut self.inner
+        }
+
+        pub fn into_inner(self) -> S {
+            self.inner
+        }
+
+        fn resolve(&self, uri: &http::Uri) -> http::Uri {
+            if uri.scheme().is_some() {
+                return uri.clone();
+            }
+
+            let base_path = self.base_url.path().trim_end_matches('/');
+            let path_and_query = uri.path_and_query().map_or("/", |p| p.as_str());
+
+            let mut builder = http::Uri::builder();
+
+            if let Some(scheme) = self.base_url.scheme() {
+                builder = builder.scheme(scheme.clone());
+            }
+
+            if let Some(authority) = self.base_url.authority() {
+                builder = builder.authority(authority.clone());
+            }
+
+            builder
+                .path_and_query(format!("{}{}", base_path, path_and_query))
+                .build()
+                .expect("a base URL joined with a request path is a valid URI")
+        }
+    }
+
+    impl<S: tower::Service<http::Request<Body>>> tower::Service<http::Request<Body>> for BaseUrl<S> {
+        type Response = S::Response;
+        type Error = S::Error;
+        type Future = S::Future;
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Result<(), Self::Error>> {
+            self.inner.poll_ready(cx)
+        }
+
+        fn call(&mut self, mut request: http::Request<Body>) -> Self::Future {
+            *request.uri_mut() = self.resolve(request.uri());
+
+            self.inner.call(request)
+        }
+    }
+
+    /// Serializes a body with the codec registered for its media type.
+    pub fn serialize_body<T: Serialize + ?Sized>(
+        media_type: &str,
+        body: &T,
+    ) -> Result<Body, codec::CodecError> {
+        let data = codec::encode(media_type, body)?;
         let stream = futures::stream::once(futures::future::ready(Ok(Frame::data(data.into()))));
-        Ok(StreamBody::new(Box::pin(stream)))
+        Ok(Body::new(Box::pin(stream)))
     }
 
+    /// Deserializes a response body with the codec registered for its media type.
     pub async fn deserialize_body<
         T: for<'a> Deserialize<'a>,
         Body: http_body::Body,
         ServiceError,
         OperationError,
     >(
+        media_type: &str,
         body: Body,
     ) -> Result<T, Error<Body, ServiceError, OperationError>> {
         use http_body_util::BodyExt;
 
         let data = body.collect().await.map_err(Error::Body)?.to_bytes();
 
-        serde_json::from_slice(&data).map_err(Error::Deserialize)
+        codec::decode(media_type, &data).map_err(Error::Deserialize)
     }
 
+    /// Deserializes a request body with the codec registered for its media type.
     pub async fn deserialize_body_server<
         T: for<'a> Deserialize<'a>,
         Body: http_body::Body,
         OperationError: std::error::Error,
     >(
+        media_type: &str,
         body: Body,
     ) -> Result<T, ServerError<Body, OperationError>> {
         use http_body_util::BodyExt;
 
         let data = body.collect().await.map_err(ServerError::Body)?.to_bytes();
 
-        serde_json::from_slice(&data).map_err(ServerError::Deserialize)
+        codec::decode(media_type, &data).map_err(ServerError::Deserialize)
+    }
+
+    pub trait FromParts: Sized {
+        fn from_parts(parts: http::response::Parts) -> Result<Self, PartsError>;
+    }
+
+    pub trait FromResponse<Body>: Sized {
+        fn from_response(body: Body, parts: http::response::Parts) -> Result<Self, PartsError>;
+    }
+
+    /// An error that occurs while reconstructing a response value from the response metadata.
+    #[derive(Debug, thiserror::Error)]
+    pub enum PartsError {
+        #[error("missing required header '{0}'")]
+        MissingHeader(&'static str),
+        #[error("header '{0}' does not contain a valid string")]
+        InvalidHeader(&'static str),
+        #[error("failed to parse header '{name}'")]
+        ParseHeader {
+            name: &'static str,
+            #[source]
+            source: Box<dyn std::error::Error + Send + Sync>,
+        },
+    }
+
+    /// Reads and parses a header from the response metadata, if it is present.
+    pub fn parse_header<T: std::str::FromStr>(
+        parts: &http::response::Parts,
+        name: &'static str,
+    ) -> Result<Option<T>, PartsError>
+    where
+        T::Err: std::error::Error + Send + Sync + 'static,
+    {
+        parts
+            .headers
+            .get(name)
+            .map(|value| {
+                let value = value
+                    .to_str()
+                    .map_err(|_| PartsError::InvalidHeader(name))?;
+
+                value.parse().map_err(|e| PartsError::ParseHeader {
+                    name,
+                    source: Box::new(e),
+                })
+            })
+            .transpose()
+    }
+
+    /// Reads the content type of a response as the enum of the content types that it may have.
+    pub fn parse_content_type<T: for<'a> Deserialize<'a>>(
+        parts: &http::response::Parts,
+    ) -> Result<T, PartsError> {
+        use serde::de::IntoDeserializer;
+
+        const NAME: &str = "content-type";
+
+        let value = parts
+            .headers
+            .get(NAME)
+            .ok_or(PartsError::MissingHeader(NAME))?
+            .to_str()
+            .map_err(|_| PartsError::InvalidHeader(NAME))?;
+
+        T::deserialize(codec::essence(value).into_deserializer()).map_err(
+            |e: serde::de::value::Error| PartsError::ParseHeader {
+                name: NAME,
+                source: Box::new(e),
+            },
+        )
+    }
+
+    /// Reads and parses a header from the response metadata, failing if it is not present.
+    pub fn parse_required_header<T: std::str::FromStr>(
+        parts: &http::response::Parts,
+        name: &'static str,
+    ) -> Result<T, PartsError>
+    where
+        T::Err: std::error::Error + Send + Sync + 'static,
+    {
+        parse_header(parts, name)?.ok_or(PartsError::MissingHeader(name))
+    }
+
+    /// Reads and decodes a header with an `@encode` encoding from the response metadata, if it is present.
+    pub fn parse_encoded_header<E: crate::serialize::TextEncoding<T>, T>(
+        parts: &http::response::Parts,
+        name: &'static str,
+    ) -> Result<Option<T>, PartsError> {
+        parts
+            .headers
+            .get(name)
+            .map(|value| {
+                let value = value
+                    .to_str()
+                    .map_err(|_| PartsError::InvalidHeader(name))?;
+
+                crate::serialize::decode::<E, T>(value).map_err(|e| PartsError::ParseHeader {
+                    name,
+                    source: Box::new(e),
+                })
+            })
+            .transpose()
+    }
+
+    /// Reads and decodes a header with an `@encode` encoding from the response metadata, failing if it is not present.
+    pub fn parse_required_encoded_header<E: crate::serialize::TextEncoding<T>, T>(
+        parts: &http::response::Parts,
+        name: &'static str,
+    ) -> Result<T, PartsError> {
+        parse_encoded_header::<E, T>(parts, name)?.ok_or(PartsError::MissingHeader(name))
+    }
+
+    /// Encodes a header of a request or response that has an `@encode` encoding.
+    pub fn encode_header<E: crate::serialize::TextEncoding<T>, T>(
+        value: &T,
+    ) -> Result<String, codec::CodecError> {
+        crate::serialize::encode::<E, T>(value).map_err(codec::CodecError::encode)
+    }
+
+    /// Encodes a query or path parameter of a request, escaping the characters that are reserved in a URI.
+    pub fn encode_uri_parameter<E: crate::serialize::TextEncoding<T>, T>(
+        value: &T,
+    ) -> Result<String, codec::CodecError> {
+        let text = crate::serialize::encode::<E, T>(value).map_err(codec::CodecError::encode)?;
+
+        Ok(
+            percent_encoding::utf8_percent_encode(&text, percent_encoding::NON_ALPHANUMERIC)
+                .to_string(),
+        )
     }
 
-    // TODO: return a result instead of panicking in FromParts/FromResponse
-    pub trait FromParts {
-        fn from_parts(parts: http::response::Parts) -> Self;
+    /// Decodes a header or query parameter of a request that has an `@encode` encoding.
+    pub fn decode_parameter<
+        E: crate::serialize::TextEncoding<T>,
+        T,
+        B: http_body::Body,
+        OperationError: std::error::Error,
+    >(
+        name: &'static str,
+        text: &str,
+    ) -> Result<T, ServerError<B, OperationError>> {
+        crate::serialize::decode::<E, T>(text).map_err(|e| ServerError::InvalidParameter(name, e))
     }
 
-    pub trait FromResponse<Body> {
-        fn from_response(body: Body, parts: http::response::Parts) -> Self;
+    /// Decodes a path parameter of a request that has an `@encode` encoding, unescaping the segment that it was sent
+    /// as first.
+    pub fn decode_path_parameter<
+        E: crate::serialize::TextEncoding<T>,
+        T,
+        B: http_body::Body,
+        OperationError: std::error::Error,
+    >(
+        name: &'static str,
+        segment: &str,
+    ) -> Result<T, ServerError<B, OperationError>> {
+        let text = percent_encoding::percent_decode_str(segment)
+            .decode_utf8()
+            .map_err(|_| {
+                ServerError::InvalidParameter(
+                    name,
+                    crate::serialize::DecodeError {
+                        encoding: E::NAME,
+                        text: segment.to_string(),
+                    },
+                )
+            })?;
+
+        decode_parameter::<E, T, B, OperationError>(name, &text)
+    }
+
+    /// Parses a header or query parameter of a request that has no encoding, such as an `int32`, from its text.
+    /// Failures are reported under the name of the TypeSpec type of the parameter.
+    pub fn parse_parameter<
+        T: std::str::FromStr,
+        B: http_body::Body,
+        OperationError: std::error::Error,
+    >(
+        name: &'static str,
+        type_name: &'static str,
+        text: &str,
+    ) -> Result<T, ServerError<B, OperationError>> {
+        text.parse().map_err(|_| {
+            ServerError::InvalidParameter(
+                name,
+                crate::serialize::DecodeError {
+                    encoding: type_name,
+                    text: text.to_string(),
+                },
+            )
+        })
     }
 
     pub enum ServerError<B: http_body::Body, OperationError: std::error::Error> {
         InvalidRequest,
+        MissingParameter(&'static str),
+        InvalidParameter(&'static str, crate::serialize::DecodeError),
+        UnsupportedContentType(Option<String>),
+        NotAcceptable(&'static [&'static str]),
         Operation(OperationError),
-        Serialize(serde_json::Error),
-        Deserialize(serde_json::Error),
+        Serialize(codec::CodecError),
+        Deserialize(codec::CodecError),
+        Validation(crate::validate::ValidationError),
+        Multipart(multipart::MultipartError),
         Body(B::Error),
     }
 
+    impl<B: http_body::Body, OperationError: std::error::Error> ServerError<B, OperationError>
+    where
+        B::Error: std::fmt::Display,
+    {
+        /// The HTTP status code that best describes this error.
+        pub fn status(&self) -> http::StatusCode {
+            match self {
+                Self::Multipart(multipart::MultipartError::PartTooLarge(_)) => {
+                    http::StatusCode::PAYLOAD_TOO_LARGE
+                }
+                Self::InvalidRequest
+                | Self::MissingParameter(_)
+                | Self::InvalidParameter(..)
+                | Self::Deserialize(_)
+                | Self::Validation(_)
+                | Self::Multipart(_)
+                | Self::Body(_) => http::StatusCode::BAD_REQUEST,
+                Self::UnsupportedContentType(_) => http::StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                Self::NotAcceptable(_) => http::StatusCode::NOT_ACCEPTABLE,
+                Self::Operation(_) | Self::Serialize(_) => http::StatusCode::INTERNAL_SERVER_ERROR,
+            }
+        }
+
+        /// Describes this error as a problem document.
+        ///
+        /// Server-side failures do not include a detail message, so that internal error information is not leaked to
+        /// clients.
+        pub fn to_problem(&self) -> Problem {
+            let problem = Problem::new(self.status());
+
+            match self {
+                Self::InvalidRequest | Self::Operation(_) | Self::Serialize(_) => problem,
+                _ => problem.with_detail(self.to_string()),
+            }
+        }
+
+        /// Converts this error into a response carrying a problem document.
+        pub fn into_response(self) -> http::Response<Body> {
+            self.to_problem().into_response()
+        }
+    }
+
+    /// A structured error payload, as described by RFC 9457 (Problem Details for HTTP APIs).
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct Problem {
+        #[serde(rename = "type")]
+        pub kind: String,
+        pub title: String,
+        pub status: u16,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub detail: Option<String>,
+    }
+
+    impl Problem {
+        pub const CONTENT_TYPE: &'static str = "application/problem+json";
+
+        /// Creates a problem document describing the given status code.
+        pub fn new(status: http::StatusCode) -> Self {
+            Self {
+                kind: "about:blank".to_string(),
+                title: status.canonical_reason().unwrap_or_default().to_string(),
+                status: status.as_u16(),
+                detail: None,
+            }
+        }
+
+        pub fn with_detail(self, detail: impl Into<String>) -> Self {
+            Self {
+                detail: Some(detail.into()),
+                ..self
+            }
+        }
+
+        pub fn into_response(self) -> http::Response<Body> {
+            let body = serialize_body(Self::CONTENT_TYPE, &self)
+                .expect("failed to serialize problem document");
+
+            http::Response::builder()
+                .status(self.status)
+                .header(http::header::CONTENT_TYPE, Self::CONTENT_TYPE)
+                .body(body)
+                .unwrap()
+        }
+    }
+
+    /// Converts the errors raised while serving a routed request into responses.
+    ///
+    /// Generated routers use [`DefaultErrorHandler`] unless another handler is provided, so implement this trait to
+    /// customize the error payloads that a service sends.
+    pub trait ErrorHandler: Clone + Send + 'static {
+        /// Converts a server error into the response that should be sent to the client.
+        ///
+        /// Returning an error instead causes the router to fail the request with that error.
+        fn handle_error<
+            B: http_body::Body + 'static,
+            E: std::error::Error + Send + Sync + 'static,
+        >(
+            &self,
+            error: ServerError<B, E>,
+        ) -> Result<http::Response<Body>, RouterError>
+        where
+            B::Error: std::error::Error + Send + Sync + 'static,
+        {
+            Ok(error.into_response())
+        }
+    }
+
+    /// Responds to every server error with a problem document.
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct DefaultErrorHandler;
+
+    impl ErrorHandler for DefaultErrorHandler {}
+
+    /// Checks that the request declares the expected content type, ignoring any media type parameters.
+    pub fn check_content_type<B: http_body::Body, OperationError: std::error::Error>(
+        parts: &http::request::Parts,
+        expected: &str,
+    ) -> Result<(), ServerError<B, OperationError>> {
+        let content_type = parts
+            .headers
+            .get(http::header::CONTENT_TYPE)
+            .map(|h| h.to_str().map(str::to_string).unwrap_or_default());
+
+        let matches = content_type.as_deref().is_some_and(|content_type| {
+            let essence = content_type.split(';').next().unwrap_or_default().trim();
+            essence.eq_ignore_ascii_case(expected)
+        });
+
+        if matches {
+            Ok(())
+        } else {
+            Err(ServerError::UnsupportedContentType(content_type))
+        }
+    }
+
     impl<B: http_body::Body, OperationError: std::error::Error> std::fmt::Debug
         for ServerError<B, OperationError>
     {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             match self {
                 Self::InvalidRequest => write!(f, "InvalidRequest"),
+                Self::MissingParameter(arg0) => {
+                    f.debug_tuple("MissingParameter").field(arg0).finish()
+                }
+                Self::InvalidParameter(arg0, arg1) => f
+                    .debug_tuple("InvalidParameter")
+                    .field(arg0)
+                    .field(arg1)
+                    .finish(),
+                Self::UnsupportedContentType(arg0) => {
+                    f.debug_tuple("UnsupportedContentType").field(arg0).finish()
+                }
+                Self::NotAcceptable(arg0) => f.debug_tuple("NotAcceptable").field(arg0).finish(),
                 Self::Operation(arg0) => f.debug_tuple("Operation").field(arg0).finish(),
                 Self::Serialize(arg0) => f.debug_tuple("Serialize").field(arg0).finish(),
                 Self::Deserialize(arg0) => f.debug_tuple("Deserialize").field(arg0).finish(),
+                Self::Validation(arg0) => f.debug_tuple("Validation").field(arg0).finish(),
+                Self::Multipart(arg0) => f.debug_tuple("Multipart").field(arg0).finish(),
                 Self::Body(_) => f.debug_tuple("Body").finish(),
             }
         }
@@ -166,41 +857,186 @@ pub mod http {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             match self {
                 ServerError::InvalidRequest => write!(f, "Invalid request"),
+                ServerError::MissingParameter(name) => {
+                    write!(f, "Missing required parameter '{}'", name)
+                }
+                ServerError::InvalidParameter(name, err) => {
+                    write!(f, "Invalid parameter '{}': {}", name, err)
+                }
+                ServerError::UnsupportedContentType(Some(content_type)) => {
+                    write!(f, "Unsupported content type '{}'", content_type)
+                }
+                ServerError::UnsupportedContentType(None) => write!(f, "Missing content type"),
+                ServerError::NotAcceptable(offered) => {
+                    write!(
+                        f,
+                        "None of the content types '{}' is acceptable",
+                        offered.join(", ")
+                    )
+                }
                 ServerError::Operation(err) => write!(f, "Operation error: {}", err),
                 ServerError::Serialize(err) => write!(f, "Serialize error: {}", err),
                 ServerError::Deserialize(err) => write!(f, "Deserialize error: {}", err),
+                ServerError::Validation(err) => write!(f, "{}", err),
+                ServerError::Multipart(err) => write!(f, "{}", err),
                 ServerError::Body(err) => write!(f, "Body error: {}", err),
             }
         }
     }
 
     pub trait Responder {
+        /// Converts this value into a response, in the content type that `accept` prefers if it has a choice of them.
         fn to_response<B: http_body::Body, E: std::error::Error>(
             self,
+            accept: &accept::Accept,
         ) -> Result<http::Response<Body>, ServerError<B, E>>;
     }
 
     impl<T: Responder, E: Responder> Responder for Result<T, E> {
         fn to_response<B: http_body::Body, Err: std::error::Error>(
             self,
+            accept: &accept::Accept,
         ) -> Result<http::Response<Body>, ServerError<B, Err>> {
             match self {
-                Ok(t) => t.to_response(),
-                Err(e) => e.to_response(),
+                Ok(t) => t.to_response(accept),
+                Err(e) => e.to_response(accept),
+            }
+        }
+    }
+
+    /// An error produced by a generated router.
+    ///
+    /// Routing failures (`NotFound` and `MethodNotAllowed`) and missing credentials (`Unauthorized`) are answered by
+    /// the router itself with the corresponding response. Only failures that occur after a request was routed to an
+    /// operation are returned as errors. Every response built by [`RouterError::into_response`] carries the
+    /// [`RouterErrorKind`] of its error as an extension, so that middleware can tell these responses apart.
+    #[derive(Debug)]
+    pub enum RouterError {
+        /// No route matched the request path.
+        NotFound,
+        /// A route matched the request path, but it does not accept the request method.
+        MethodNotAllowed(Vec<http::Method>),
+        /// The request was routed to an operation that requires authentication, but it did not carry a valid
+        /// credential. Holds the `WWW-Authenticate` challenges of the schemes that the operation accepts.
+        Unauthorized(Vec<&'static str>),
+        /// The request was routed to an operation, but handling it failed.
+        Server(Box<dyn std::error::Error + Send + Sync>),
+    }
+
+    /// The kind of a [`RouterError`], attached as an extension to the response that it was converted into.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum RouterErrorKind {
+        NotFound,
+        MethodNotAllowed,
+        Unauthorized,
+        Server,
+    }
+
+    impl RouterErrorKind {
+        /// Returns `true` if the request could not be matched to an operation.
+        pub fn is_routing_error(&self) -> bool {
+            matches!(self, Self::NotFound | Self::MethodNotAllowed)
+        }
+
+        /// Returns the kind of the router error that produced `response`, if any.
+        pub fn of<B>(response: &http::Response<B>) -> Option<Self> {
+            response.extensions().get::<Self>().copied()
+        }
+    }
+
+    impl RouterError {
+        /// Returns the kind of this error.
+        pub fn kind(&self) -> RouterErrorKind {
+            match self {
+                Self::NotFound => RouterErrorKind::NotFound,
+                Self::MethodNotAllowed(_) => RouterErrorKind::MethodNotAllowed,
+                Self::Unauthorized(_) => RouterErrorKind::Unauthorized,
+                Self::Server(_) => RouterErrorKind::Server,
+            }
+        }
+
+        /// Returns `true` if the request could not be matched to an operation.
+        pub fn is_routing_error(&self) -> bool {
+            self.kind().is_routing_error()
+        }
+
+        /// Converts this error into the response that should be sent to the client.
+        ///
+        /// The response carries the [`RouterErrorKind`] of this error as an extension.
+        pub fn into_response(self) -> http::Response<Body> {
+            let kind = self.kind();
+
+            let mut response = match self {
+                Self::NotFound => Problem::new(http::StatusCode::NOT_FOUND).into_response(),
+                Self::MethodNotAllowed(allowed) => {
+                    let allow = allowed.iter().map(http::Method::as_str).join(", ");
+
+                    let mut response =
+                        Problem::new(http::StatusCode::METHOD_NOT_ALLOWED).into_response();
+
+                    response
+                        .headers_mut()
+                        .insert(http::header::ALLOW, allow.parse().unwrap());
+
+                    response
+                }
+                Self::Unauthorized(challenges) => {
+                    let mut response = Problem::new(http::StatusCode::UNAUTHORIZED).into_response();
+
+                    for challenge in challenges {
+                        response.headers_mut().append(
+                            http::header::WWW_AUTHENTICATE,
+                            http::HeaderValue::from_static(challenge),
+                        );
+                    }
+
+                    response
+                }
+                Self::Server(_) => {
+                    Problem::new(http::StatusCode::INTERNAL_SERVER_ERROR).into_response()
+                }
+            };
+
+            response.extensions_mut().insert(kind);
+
+            response
+        }
+    }
+
+    impl std::error::Error for RouterError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            match self {
+                Self::Server(err) => Some(err.as_ref()),
+                _ => None,
+            }
+        }
+    }
+
+    impl core::fmt::Display for RouterError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                RouterError::NotFound => write!(f, "Not found"),
+                RouterError::MethodNotAllowed(allowed) => write!(
+                    f,
+                    "Method not allowed, expected one of: {}",
+                    allowed.iter().map(http::Method::as_str).join(", ")
+                ),
+                RouterError::Unauthorized(_) => write!(f, "Unauthorized"),
+                RouterError::Server(err) => write!(f, "Server error: {}", err),
             }
         }
     }
 
     impl FromParts for () {
-        #[allow(clippy::unused_unit)]
-        fn from_parts(_: http::response::Parts) -> Self {
-            ()
+        fn from_parts(_: http::response::Parts) -> Result<Self, PartsError> {
+            Ok(())
         }
     }
 
     impl Responder for () {
         fn to_response<B: http_body::Body, E: std::error::Error>(
             self,
+            _: &accept::Accept,
         ) -> Result<http::Response<Body>, ServerError<B, E>> {
             Ok(http::Response::builder()
                 .status(http::StatusCode::NO_CONTENT)
@@ -212,54 +1048,82 @@ pub mod http {
     impl<T: Serialize> Responder for Vec<T> {
         fn to_response<B: http_body::Body, E: std::error::Error>(
             self,
+            _: &accept::Accept,
         ) -> Result<http::Response<Body>, ServerError<B, E>> {
             Ok(http::Response::builder()
                 .status(http::StatusCode::OK)
                 .header(http::header::CONTENT_TYPE, "application/json")
-                .body(serialize_json_body(self).map_err(ServerError::Serialize)?)
+                .body(serialize_body("application/json", &self).map_err(ServerError::Serialize)?)
                 .unwrap())
         }
     }
-}
 
-pub mod serialize {
-    pub mod null_variant {
-        pub fn serialize<S>(serializer: S) -> Result<S::Ok, S::Error>
-        where
-            S: serde::Serializer,
-        {
-            serializer.serialize_none()
+    impl Responder for Infallible {
+        fn to_response<B: http_body::Body, E: std::error::Error>(
+            self,
+            _: &accept::Accept,
+        ) -> Result<http::Response<Body>, ServerError<B, E>> {
+            match self {}
         }
+    }
 
-        pub fn deserialize<'de, D>(deserializer: D) -> Result<(), D::Error>
-        where
-            D: serde::Deserializer<'de>,
-        {
-            struct NullVariantVisitor;
-
-            impl<'de> serde::de::Visitor<'de> for NullVariantVisitor {
-                type Value = ();
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn router_error_responses_carry_their_kind() {
+            let response = RouterError::NotFound.into_response();
+            assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+            assert_eq!(
+                RouterErrorKind::of(&response),
+                Some(RouterErrorKind::NotFound)
+            );
+            assert!(RouterErrorKind::of(&response).unwrap().is_routing_error());
 
-                fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                    formatter.write_str("null")
-                }
+            let response = RouterError::MethodNotAllowed(vec![http::Method::GET]).into_response();
+            assert_eq!(response.headers()[http::header::ALLOW], "GET");
+            assert_eq!(
+                RouterErrorKind::of(&response),
+                Some(RouterErrorKind::MethodNotAllowed)
+            );
 
-                fn visit_none<E>(self) -> Result<Self::Value, E>
-                where
-                    E: serde::de::Error,
-                {
-                    Ok(())
-                }
+            let response = RouterError::Unauthorized(vec!["Bearer"]).into_response();
+            let kind = RouterErrorKind::of(&response).unwrap();
+            assert_eq!(kind, RouterErrorKind::Unauthorized);
+            assert!(!kind.is_routing_error());
+        }
 
-                fn visit_unit<E>(self) -> Result<Self::Value, E>
-                where
-                    E: serde::de::Error,
-                {
-                    Ok(())
-                }
+        #[test]
+        fn server_parameters_are_formatted_as_their_values() {
+            #[derive(Serialize)]
+            enum Version {
+                #[serde(rename = "2024-01-01")]
+                V1,
             }
 
-            deserializer.deserialize_option(NullVariantVisitor)
+            assert_eq!(
+                format_server_parameter("https://example.com/a b"),
+                "https://example.com/a b"
+            );
+            assert_eq!(format_server_parameter(&Version::V1), "2024-01-01");
+            assert_eq!(format_server_parameter(&8080), "8080");
+            assert_eq!(format_server_parameter(&true), "true");
+        }
+
+        #[test]
+        fn server_path_parameters_are_escaped() {
+            assert_eq!(format_server_path_parameter("v1.0_beta~2"), "v1.0_beta~2");
+            assert_eq!(
+                format_server_path_parameter("a/b c?d#e"),
+                "a%2Fb%20c%3Fd%23e"
+            );
+        }
+
+        #[test]
+        fn responses_without_a_router_error_have_no_kind() {
+            let response = http::Response::new(());
+            assert_eq!(RouterErrorKind::of(&response), None);
         }
     }
 }
@@ -0,0 +1,486 @@
+//! JSON merge patches, as described by [RFC 7396](https://www.rfc-editor.org/rfc/rfc7396).
+//!
+//! Operations that accept `application/merge-patch+json` bodies take a generated `<Model>Patch` type in place of their
+//! model. Each of its fields is a [`Patch`], which distinguishes a property that is left unchanged from one that is
+//! removed and one that is replaced. Properties that are models are patched recursively, while all other values,
+//! including arrays and records, are replaced as a whole.
+
+use serde::Deserialize;
+use serde_with::{DeserializeAs, SerializeAs, de::DeserializeAsWrap};
+
+use crate::validate::{Validate, Validator};
+
+/// The change that a merge patch makes to a single property.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Patch<T> {
+    /// The property is left unchanged, and is omitted from the serialized patch.
+    #[default]
+    Absent,
+    /// The property is removed, and is serialized as `null`.
+    Null,
+    /// The property is set to a value, or patched with it if it is a model.
+    Value(T),
+}
+
+impl<T> Patch<T> {
+    pub fn is_absent(&self) -> bool {
+        matches!(self, Self::Absent)
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    pub fn as_ref(&self) -> Patch<&T> {
+        match self {
+            Self::Absent => Patch::Absent,
+            Self::Null => Patch::Null,
+            Self::Value(value) => Patch::Value(value),
+        }
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Patch<U> {
+        match self {
+            Self::Absent => Patch::Absent,
+            Self::Null => Patch::Null,
+            Self::Value(value) => Patch::Value(f(value)),
+        }
+    }
+
+    /// Applies the change to a required property, which cannot be removed.
+    pub fn apply(self, target: &mut T, property: &'static str) -> Result<(), PatchError> {
+        match self {
+            Self::Absent => Ok(()),
+            Self::Null => Err(PatchError::Null(property)),
+            Self::Value(value) => {
+                *target = value;
+                Ok(())
+            }
+        }
+    }
+
+    /// Applies the change to an optional property.
+    pub fn apply_optional(self, target: &mut Option<T>) {
+        match self {
+            Self::Absent => {}
+            Self::Null => *target = None,
+            Self::Value(value) => *target = Some(value),
+        }
+    }
+
+    /// Gets the value of a required property of a value created from a patch.
+    pub fn into_required(self, property: &'static str) -> Result<T, PatchError> {
+        match self {
+            Self::Absent => Err(PatchError::Missing(property)),
+            Self::Null => Err(PatchError::Null(property)),
+            Self::Value(value) => Ok(value),
+        }
+    }
+
+    /// Gets the value of an optional property of a value created from a patch.
+    pub fn into_optional(self) -> Option<T> {
+        match self {
+            Self::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl<P: MergePatch> Patch<P> {
+    /// Merges the change into a required property whose value is a model.
+    pub fn merge(self, target: &mut P::Target, property: &'static str) -> Result<(), PatchError> {
+        match self {
+            Self::Absent => Ok(()),
+            Self::Null => Err(PatchError::Null(property)),
+            Self::Value(patch) => patch.apply_to(target),
+        }
+    }
+
+    /// Merges the change into an optional property whose value is a model.
+    ///
+    /// A patch for a property that is not set creates its value, as if it were applied to an empty object.
+    pub fn merge_optional(self, target: &mut Option<P::Target>) -> Result<(), PatchError> {
+        match (self, target.as_mut()) {
+            (Self::Absent, _) => {}
+            (Self::Null, _) => *target = None,
+            (Self::Value(patch), Some(value)) => patch.apply_to(value)?,
+            (Self::Value(patch), None) => *target = Some(patch.into_target()?),
+        }
+
+        Ok(())
+    }
+}
+
+impl<T> From<T> for Patch<T> {
+    fn from(value: T) -> Self {
+        Self::Value(value)
+    }
+}
+
+impl<T: serde::Serialize> serde::Serialize for Patch<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            // Absent properties are skipped by the generated patch types, so they are only serialized when a patch is
+            // nested in another value.
+            Self::Absent | Self::Null => serializer.serialize_none(),
+            Self::Value(value) => value.serialize(serializer),
+        }
+    }
+}
+
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for Patch<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Absent properties are never deserialized, as the generated patch types default them.
+        Ok(match Option::<T>::deserialize(deserializer)? {
+            Some(value) => Self::Value(value),
+            None => Self::Null,
+        })
+    }
+}
+
+/// Patches are adapted like the values that they hold, so that `#[serde_as(as = "Patch<A>")]` serializes the value of a
+/// patched property with the adapter `A` of its encoding.
+impl<T, A: SerializeAs<T>> SerializeAs<Patch<T>> for Patch<A> {
+    fn serialize_as<S: serde::Serializer>(
+        source: &Patch<T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match source {
+            Patch::Absent | Patch::Null => serializer.serialize_none(),
+            Patch::Value(value) => A::serialize_as(value, serializer),
+        }
+    }
+}
+
+impl<'de, T, A: DeserializeAs<'de, T>> DeserializeAs<'de, Patch<T>> for Patch<A> {
+    fn deserialize_as<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Patch<T>, D::Error> {
+        Ok(
+            match Option::<DeserializeAsWrap<T, A>>::deserialize(deserializer)? {
+                Some(value) => Patch::Value(value.into_inner()),
+                None => Patch::Null,
+            },
+        )
+    }
+}
+
+impl<T: Validate> Validate for Patch<T> {
+    fn validate_into(&self, validator: &mut Validator) {
+        if let Self::Value(value) = self {
+            value.validate_into(validator)
+        }
+    }
+}
+
+/// A merge patch for a model, generated as `<Model>Patch`.
+pub trait MergePatch: Sized {
+    /// The model that the patch applies to.
+    type Target;
+
+    /// Applies the patch to a value of the model.
+    fn apply_to(self, target: &mut Self::Target) -> Result<(), PatchError>;
+
+    /// Creates a value of the model from the patch, as if it were applied to an empty object.
+    fn into_target(self) -> Result<Self::Target, PatchError>;
+
+    /// Applies the patch to an owned value of the model, returning the patched value.
+    fn apply(self, mut target: Self::Target) -> Result<Self::Target, PatchError> {
+        self.apply_to(&mut target)?;
+
+        Ok(target)
+    }
+}
+
+impl<P: MergePatch> MergePatch for Box<P> {
+    type Target = Box<P::Target>;
+
+    fn apply_to(self, target: &mut Self::Target) -> Result<(), PatchError> {
+        (*self).apply_to(target)
+    }
+
+    fn into_target(self) -> Result<Self::Target, PatchError> {
+        (*self).into_target().map(Box::new)
+    }
+}
+
+/// An error that occurs when a merge patch cannot be applied to a model.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PatchError {
+    #[error("required property '{0}' cannot be removed")]
+    Null(&'static str),
+    #[error("required property '{0}' is missing")]
+    Missing(&'static str),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    struct Owner {
+        name: String,
+        email: Option<String>,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Pet {
+        name: String,
+        tag: Option<String>,
+        owner: Option<Owner>,
+    }
+
+    #[derive(Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+    struct OwnerPatch {
+        #[serde(default, skip_serializing_if = "Patch::is_absent")]
+        name: Patch<String>,
+        #[serde(default, skip_serializing_if = "Patch::is_absent")]
+        email: Patch<String>,
+    }
+
+    #[derive(Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+    struct PetPatch {
+        #[serde(default, skip_serializing_if = "Patch::is_absent")]
+        name: Patch<String>,
+        #[serde(default, skip_serializing_if = "Patch::is_absent")]
+        tag: Patch<String>,
+        #[serde(default, skip_serializing_if = "Patch::is_absent")]
+        owner: Patch<OwnerPatch>,
+    }
+
+    impl MergePatch for OwnerPatch {
+        type Target = Owner;
+
+        fn apply_to(self, target: &mut Owner) -> Result<(), PatchError> {
+            self.name.apply(&mut target.name, "name")?;
+            self.email.apply_optional(&mut target.email);
+
+            Ok(())
+        }
+
+        fn into_target(self) -> Result<Owner, PatchError> {
+            Ok(Owner {
+                name: self.name.into_required("name")?,
+                email: self.email.into_optional(),
+            })
+        }
+    }
+
+    impl MergePatch for PetPatch {
+        type Target = Pet;
+
+        fn apply_to(self, target: &mut Pet) -> Result<(), PatchError> {
+            self.name.apply(&mut target.name, "name")?;
+            self.tag.apply_optional(&mut target.tag);
+            self.owner.merge_optional(&mut target.owner)?;
+
+            Ok(())
+        }
+
+        fn into_target(self) -> Result<Pet, PatchError> {
+            Ok(Pet {
+                name: self.name.into_required("name")?,
+                tag: self.tag.into_optional(),
+                owner: self
+                    .owner
+                    .into_optional()
+                    .map(MergePatch::into_target)
+                    .transpose()?,
+            })
+        }
+    }
+
+    fn pet() -> Pet {
+        Pet {
+            name: "Rex".to_string(),
+            tag: Some("good".to_string()),
+            owner: None,
+        }
+    }
+
+    #[test]
+    fn absent_fields_are_omitted() {
+        let patch = PetPatch {
+            name: Patch::Value("Max".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(serde_json::to_string(&patch).unwrap(), r#"{"name":"Max"}"#);
+        assert_eq!(serde_json::to_string(&PetPatch::default()).unwrap(), "{}");
+    }
+
+    #[test]
+    fn null_fields_are_serialized() {
+        let patch = PetPatch {
+            tag: Patch::Null,
+            owner: Patch::Value(OwnerPatch {
+                email: Patch::Null,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            serde_json::to_string(&patch).unwrap(),
+            r#"{"tag":null,"owner":{"email":null}}"#
+        );
+    }
+
+    #[test]
+    fn deserialization_distinguishes_absent_null_and_values() {
+        let patch: PetPatch =
+            serde_json::from_str(r#"{"tag":null,"owner":{"name":"Ann"}}"#).unwrap();
+
+        assert_eq!(
+            patch,
+            PetPatch {
+                name: Patch::Absent,
+                tag: Patch::Null,
+                owner: Patch::Value(OwnerPatch {
+                    name: Patch::Value("Ann".to_string()),
+                    email: Patch::Absent,
+                }),
+            }
+        );
+    }
+
+    #[serde_with::serde_as]
+    #[derive(Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+    struct VisitPatch {
+        #[serde(default, skip_serializing_if = "Patch::is_absent")]
+        #[serde_as(as = "Patch<crate::serialize::UnixTimestamp<i64>>")]
+        day: Patch<chrono::DateTime<chrono::Utc>>,
+        #[serde(default, skip_serializing_if = "Patch::is_absent")]
+        #[serde_as(as = "Patch<Vec<crate::serialize::Iso8601>>")]
+        lengths: Patch<Vec<chrono::Duration>>,
+    }
+
+    #[test]
+    fn encoded_fields_are_adapted() {
+        let patch = VisitPatch {
+            day: Patch::Value(chrono::DateTime::from_timestamp(86_400, 0).unwrap()),
+            lengths: Patch::Value(vec![chrono::Duration::minutes(90)]),
+        };
+
+        let json = r#"{"day":86400,"lengths":["PT1H30M"]}"#;
+
+        assert_eq!(serde_json::to_string(&patch).unwrap(), json);
+        assert_eq!(serde_json::from_str::<VisitPatch>(json).unwrap(), patch);
+
+        let removed = VisitPatch {
+            day: Patch::Null,
+            ..Default::default()
+        };
+
+        assert_eq!(serde_json::to_string(&removed).unwrap(), r#"{"day":null}"#);
+        assert_eq!(
+            serde_json::from_str::<VisitPatch>(r#"{"day":null}"#).unwrap(),
+            removed
+        );
+    }
+
+    #[test]
+    fn patches_apply_to_their_target() {
+        let patch = PetPatch {
+            name: Patch::Value("Max".to_string()),
+            tag: Patch::Null,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            patch.apply(pet()),
+            Ok(Pet {
+                name: "Max".to_string(),
+                tag: None,
+                owner: None,
+            })
+        );
+    }
+
+    #[test]
+    fn required_fields_cannot_be_removed() {
+        let patch = PetPatch {
+            name: Patch::Null,
+            ..Default::default()
+        };
+
+        assert_eq!(patch.apply(pet()), Err(PatchError::Null("name")));
+
+        let mut target = pet();
+        target.owner = Some(Owner::default());
+
+        let patch = PetPatch {
+            owner: Patch::Value(OwnerPatch {
+                name: Patch::Null,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(patch.apply(target), Err(PatchError::Null("name")));
+    }
+
+    #[test]
+    fn merge_optional_creates_nested_values() {
+        let patch = PetPatch {
+            owner: Patch::Value(OwnerPatch {
+                name: Patch::Value("Ann".to_string()),
+                email: Patch::Value("ann@example.com".to_string()),
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            patch.apply(pet()).unwrap().owner,
+            Some(Owner {
+                name: "Ann".to_string(),
+                email: Some("ann@example.com".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn merge_optional_requires_the_required_fields_of_created_values() {
+        let patch = PetPatch {
+            owner: Patch::Value(OwnerPatch {
+                email: Patch::Value("ann@example.com".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(patch.apply(pet()), Err(PatchError::Missing("name")));
+    }
+
+    #[test]
+    fn merge_optional_patches_and_removes_existing_values() {
+        let mut target = pet();
+        target.owner = Some(Owner {
+            name: "Ann".to_string(),
+            email: Some("ann@example.com".to_string()),
+        });
+
+        let patch = PetPatch {
+            owner: Patch::Value(OwnerPatch {
+                email: Patch::Null,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let target = patch.apply(target).unwrap();
+
+        assert_eq!(
+            target.owner,
+            Some(Owner {
+                name: "Ann".to_string(),
+                email: None,
+            })
+        );
+
+        let patch = PetPatch {
+            owner: Patch::Null,
+            ..Default::default()
+        };
+
+        assert_eq!(patch.apply(target).unwrap().owner, None);
+    }
+}
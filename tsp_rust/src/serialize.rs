@@ -0,0 +1,1067 @@
+//! Serialization adapters for generated code.
+//!
+//! Scalars with an `@encode` decorator keep their Rust type in generated code, and are converted to and from their
+//! encoded form by the encodings in this module. Model fields use them as `serde_with` adapters, through
+//! `#[serde_as(as = "...")]`, and HTTP headers, query parameters and path parameters use them through [`encode`] and
+//! [`decode`].
+//!
+//! | Scalar           | Encoding        | Adapter                 |
+//! | ---------------- | --------------- | ----------------------- |
+//! | `utcDateTime`    | `rfc3339`       | [`Rfc3339`]             |
+//! | `utcDateTime`    | `rfc7231`       | [`Rfc7231`]             |
+//! | `utcDateTime`    | `unixTimestamp` | [`UnixTimestamp<N>`]    |
+//! | `offsetDateTime` | `rfc3339`       | [`Rfc3339`]             |
+//! | `offsetDateTime` | `rfc7231`       | [`Rfc7231`]             |
+//! | `plainDate`      | `rfc3339`       | [`Rfc3339`]             |
+//! | `plainDate`      | `rfc7231`       | [`Rfc7231`]             |
+//! | `plainTime`      | `rfc3339`       | [`Rfc3339`]             |
+//! | `plainTime`      | `rfc7231`       | [`Rfc7231`]             |
+//! | `duration`       | `ISO8601`       | [`Iso8601`]             |
+//! | `duration`       | `seconds`       | [`Seconds<N>`]          |
+//! | `bytes`          | `base64`        | [`Base64`]              |
+//! | `bytes`          | `base64url`     | [`Base64Url`]           |
+//!
+//! The number encodings take the Rust type of the number that they are encoded as, like `UnixTimestamp<i64>` or
+//! `Seconds<f64>`.
+//!
+//! Enums whose values are numbers are serialized as those values through their `TryFrom` and `Into` conversions, which
+//! reject unknown values with an [`UnknownEnumValue`].
+//!
+//! Arbitrary-precision `integer` values are held as an [`Integer`], which is serialized through [`BigInteger`] so that
+//! they are kept exact in JSON wherever they appear.
+
+use std::{
+    fmt::Display,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+    str::FromStr,
+};
+
+use base64::Engine;
+use chrono::{
+    DateTime, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat, Utc,
+};
+use num_bigint::BigInt;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_with::{DeserializeAs, SerializeAs};
+
+use crate::validate::MAX_SAFE_INTEGER;
+
+pub mod null_variant {
+    pub fn serialize<S>(serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_none()
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<(), D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct NullVariantVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for NullVariantVisitor {
+            type Value = ();
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("null")
+            }
+
+            fn visit_none<E>(self) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(())
+            }
+
+            fn visit_unit<E>(self) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(())
+            }
+        }
+
+        deserializer.deserialize_option(NullVariantVisitor)
+    }
+}
+
+/// An encoding of values of type `T` as text.
+pub trait TextEncoding<T> {
+    /// The name of the encoding, as it is written in TypeSpec.
+    const NAME: &'static str;
+
+    /// Encodes a value, failing if it cannot be represented in the encoding.
+    fn encode(value: &T) -> Result<String, EncodeError>;
+
+    /// Decodes a value, returning `None` if the text is not a valid encoding of one.
+    fn decode(text: &str) -> Option<T>;
+}
+
+/// An error that occurs when a value cannot be represented in an encoding, like a date too far from the epoch for the
+/// number type of a timestamp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("value is out of range for {encoding}")]
+pub struct EncodeError {
+    pub encoding: &'static str,
+}
+
+/// An error that occurs when text is not a valid encoding of a value.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("'{text}' is not a valid {encoding} value")]
+pub struct DecodeError {
+    pub encoding: &'static str,
+    pub text: String,
+}
+
+/// An error that occurs when a number is not the value of any variant of a numeric enum.
+#[derive(Debug, Clone, Copy, PartialEq, thiserror::Error)]
+#[error("{value} is not a value of {enum_name}")]
+pub struct UnknownEnumValue<T: Display> {
+    pub enum_name: &'static str,
+    pub value: T,
+}
+
+/// Encodes a value as text with the given encoding.
+pub fn encode<E: TextEncoding<T>, T>(value: &T) -> Result<String, EncodeError> {
+    E::encode(value)
+}
+
+/// Decodes a value from text with the given encoding.
+pub fn decode<E: TextEncoding<T>, T>(text: &str) -> Result<T, DecodeError> {
+    E::decode(text.trim()).ok_or_else(|| DecodeError {
+        encoding: E::NAME,
+        text: text.to_string(),
+    })
+}
+
+/// Implements the `serde_with` adapter traits for encodings that are serialized as strings.
+macro_rules! string_adapter {
+    ($encoding:ty, $value:ty) => {
+        impl SerializeAs<$value> for $encoding {
+            fn serialize_as<S: Serializer>(
+                value: &$value,
+                serializer: S,
+            ) -> Result<S::Ok, S::Error> {
+                let text = encode::<Self, _>(value).map_err(serde::ser::Error::custom)?;
+
+                serializer.serialize_str(&text)
+            }
+        }
+
+        impl<'de> DeserializeAs<'de, $value> for $encoding {
+            fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<$value, D::Error> {
+                let text = String::deserialize(deserializer)?;
+
+                decode::<Self, _>(&text).map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+/// A date and time as an RFC 3339 string, like `2024-01-02T03:04:05Z`.
+///
+/// Offset date-times keep their offset, like `2024-01-02T05:04:05+02:00`. Plain dates and times are the `full-date`
+/// and `partial-time` of RFC 3339, like `2024-01-02` and `03:04:05.25`.
+pub struct Rfc3339;
+
+impl TextEncoding<DateTime<Utc>> for Rfc3339 {
+    const NAME: &'static str = "rfc3339";
+
+    fn encode(value: &DateTime<Utc>) -> Result<String, EncodeError> {
+        Ok(value.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+    }
+
+    fn decode(text: &str) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(text)
+            .ok()
+            .map(|value| value.with_timezone(&Utc))
+    }
+}
+
+string_adapter!(Rfc3339, DateTime<Utc>);
+
+impl TextEncoding<DateTime<FixedOffset>> for Rfc3339 {
+    const NAME: &'static str = "rfc3339";
+
+    fn encode(value: &DateTime<FixedOffset>) -> Result<String, EncodeError> {
+        Ok(value.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+    }
+
+    fn decode(text: &str) -> Option<DateTime<FixedOffset>> {
+        DateTime::parse_from_rfc3339(text).ok()
+    }
+}
+
+string_adapter!(Rfc3339, DateTime<FixedOffset>);
+
+impl TextEncoding<NaiveDate> for Rfc3339 {
+    const NAME: &'static str = "rfc3339";
+
+    fn encode(value: &NaiveDate) -> Result<String, EncodeError> {
+        Ok(value.format("%Y-%m-%d").to_string())
+    }
+
+    fn decode(text: &str) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()
+    }
+}
+
+string_adapter!(Rfc3339, NaiveDate);
+
+impl TextEncoding<NaiveTime> for Rfc3339 {
+    const NAME: &'static str = "rfc3339";
+
+    fn encode(value: &NaiveTime) -> Result<String, EncodeError> {
+        Ok(value.format("%H:%M:%S%.f").to_string())
+    }
+
+    fn decode(text: &str) -> Option<NaiveTime> {
+        NaiveTime::parse_from_str(text, "%H:%M:%S%.f").ok()
+    }
+}
+
+string_adapter!(Rfc3339, NaiveTime);
+
+/// A date and time as an RFC 7231 (HTTP) date, like `Tue, 02 Jan 2024 03:04:05 GMT`.
+///
+/// HTTP dates are always in GMT, so offset date-times are converted to it, and are decoded with a zero offset. Plain
+/// dates and times are the date and `time-of-day` parts of an HTTP date, like `Tue, 02 Jan 2024` and `03:04:05`.
+pub struct Rfc7231;
+
+impl Rfc7231 {
+    const FORMAT: &'static str = "%a, %d %b %Y %H:%M:%S GMT";
+    const DATE_FORMAT: &'static str = "%a, %d %b %Y";
+    const TIME_FORMAT: &'static str = "%H:%M:%S";
+}
+
+impl TextEncoding<DateTime<Utc>> for Rfc7231 {
+    const NAME: &'static str = "rfc7231";
+
+    fn encode(value: &DateTime<Utc>) -> Result<String, EncodeError> {
+        Ok(value.format(Self::FORMAT).to_string())
+    }
+
+    fn decode(text: &str) -> Option<DateTime<Utc>> {
+        NaiveDateTime::parse_from_str(text, Self::FORMAT)
+            .ok()
+            .map(|value| value.and_utc())
+    }
+}
+
+string_adapter!(Rfc7231, DateTime<Utc>);
+
+impl TextEncoding<DateTime<FixedOffset>> for Rfc7231 {
+    const NAME: &'static str = "rfc7231";
+
+    fn encode(value: &DateTime<FixedOffset>) -> Result<String, EncodeError> {
+        encode::<Self, _>(&value.with_timezone(&Utc))
+    }
+
+    fn decode(text: &str) -> Option<DateTime<FixedOffset>> {
+        <Self as TextEncoding<DateTime<Utc>>>::decode(text).map(|value| value.fixed_offset())
+    }
+}
+
+string_adapter!(Rfc7231, DateTime<FixedOffset>);
+
+impl TextEncoding<NaiveDate> for Rfc7231 {
+    const NAME: &'static str = "rfc7231";
+
+    fn encode(value: &NaiveDate) -> Result<String, EncodeError> {
+        Ok(value.format(Self::DATE_FORMAT).to_string())
+    }
+
+    fn decode(text: &str) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(text, Self::DATE_FORMAT).ok()
+    }
+}
+
+string_adapter!(Rfc7231, NaiveDate);
+
+impl TextEncoding<NaiveTime> for Rfc7231 {
+    const NAME: &'static str = "rfc7231";
+
+    fn encode(value: &NaiveTime) -> Result<String, EncodeError> {
+        Ok(value.format(Self::TIME_FORMAT).to_string())
+    }
+
+    fn decode(text: &str) -> Option<NaiveTime> {
+        NaiveTime::parse_from_str(text, Self::TIME_FORMAT).ok()
+    }
+}
+
+string_adapter!(Rfc7231, NaiveTime);
+
+/// A number that a quantity of seconds is encoded as.
+///
+/// Integers drop any fraction of a second, and floats keep it to the precision that they have.
+pub trait Number: Copy + Display + FromStr + Serialize + for<'de> Deserialize<'de> {
+    /// Converts whole seconds and nanoseconds, which are always positive, into a number.
+    fn from_seconds(seconds: i64, nanos: u32) -> Option<Self>;
+
+    /// Splits a number into whole seconds, rounded down, and the nanoseconds that remain.
+    fn to_seconds(self) -> Option<(i64, u32)>;
+}
+
+macro_rules! integer_number {
+    ($($integer:ty),*) => {
+        $(
+            impl Number for $integer {
+                fn from_seconds(seconds: i64, _: u32) -> Option<Self> {
+                    seconds.try_into().ok()
+                }
+
+                fn to_seconds(self) -> Option<(i64, u32)> {
+                    Some((self.try_into().ok()?, 0))
+                }
+            }
+        )*
+    };
+}
+
+integer_number!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+macro_rules! float_number {
+    ($($float:ty),*) => {
+        $(
+            impl Number for $float {
+                fn from_seconds(seconds: i64, nanos: u32) -> Option<Self> {
+                    Some(seconds as $float + nanos as $float / 1e9)
+                }
+
+                fn to_seconds(self) -> Option<(i64, u32)> {
+                    if !self.is_finite() || self.abs() >= i64::MAX as $float {
+                        return None;
+                    }
+
+                    let seconds = self.floor();
+                    let nanos = (((self - seconds) as f64) * 1e9).round().min(999_999_999.0);
+
+                    Some((seconds as i64, nanos as u32))
+                }
+            }
+        )*
+    };
+}
+
+float_number!(f32, f64);
+
+/// Implements the encoding traits for encodings that are serialized as a [`Number`] of seconds.
+macro_rules! number_adapter {
+    ($encoding:ident, $name:literal, $value:ty, $to_seconds:expr, $from_seconds:expr) => {
+        impl<N: Number> TextEncoding<$value> for $encoding<N> {
+            const NAME: &'static str = $name;
+
+            fn encode(value: &$value) -> Result<String, EncodeError> {
+                Self::to_number(value)
+                    .map(|number| number.to_string())
+                    .ok_or(EncodeError { encoding: $name })
+            }
+
+            fn decode(text: &str) -> Option<$value> {
+                Self::from_number(text.parse().ok()?)
+            }
+        }
+
+        impl<N: Number> $encoding<N> {
+            fn to_number(value: &$value) -> Option<N> {
+                let (seconds, nanos) = $to_seconds(value);
+
+                N::from_seconds(seconds, nanos)
+            }
+
+            fn from_number(number: N) -> Option<$value> {
+                let (seconds, nanos) = number.to_seconds()?;
+
+                $from_seconds(seconds, nanos)
+            }
+        }
+
+        impl<N: Number> SerializeAs<$value> for $encoding<N> {
+            fn serialize_as<S: Serializer>(
+                value: &$value,
+                serializer: S,
+            ) -> Result<S::Ok, S::Error> {
+                Self::to_number(value)
+                    .ok_or_else(|| serde::ser::Error::custom(EncodeError { encoding: $name }))?
+                    .serialize(serializer)
+            }
+        }
+
+        impl<'de, N: Number> DeserializeAs<'de, $value> for $encoding<N> {
+            fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<$value, D::Error> {
+                let number = N::deserialize(deserializer)?;
+
+                Self::from_number(number).ok_or_else(|| {
+                    serde::de::Error::custom(DecodeError {
+                        encoding: $name,
+                        text: number.to_string(),
+                    })
+                })
+            }
+        }
+    };
+}
+
+/// A date and time as the number of seconds since the Unix epoch.
+pub struct UnixTimestamp<N>(PhantomData<N>);
+
+number_adapter!(
+    UnixTimestamp,
+    "unixTimestamp",
+    DateTime<Utc>,
+    |value: &DateTime<Utc>| (value.timestamp(), value.timestamp_subsec_nanos()),
+    DateTime::from_timestamp
+);
+
+/// A duration as a number of seconds.
+pub struct Seconds<N>(PhantomData<N>);
+
+number_adapter!(
+    Seconds,
+    "seconds",
+    Duration,
+    duration_to_seconds,
+    duration_from_seconds
+);
+
+/// Splits a duration into whole seconds, rounded down, and the nanoseconds that remain.
+fn duration_to_seconds(value: &Duration) -> (i64, u32) {
+    let (seconds, nanos) = (value.num_seconds(), value.subsec_nanos());
+
+    if nanos < 0 {
+        (seconds - 1, (nanos + 1_000_000_000) as u32)
+    } else {
+        (seconds, nanos as u32)
+    }
+}
+
+fn duration_from_seconds(seconds: i64, nanos: u32) -> Option<Duration> {
+    Duration::try_seconds(seconds)?.checked_add(&Duration::nanoseconds(nanos.into()))
+}
+
+/// A duration as an ISO 8601 duration, like `P1DT2H30M` or `PT0.5S`.
+///
+/// Only days, weeks, hours, minutes and seconds are accepted, as years and months do not have a fixed length.
+pub struct Iso8601;
+
+impl TextEncoding<Duration> for Iso8601 {
+    const NAME: &'static str = "ISO8601";
+
+    fn encode(value: &Duration) -> Result<String, EncodeError> {
+        if value.is_zero() {
+            return Ok("PT0S".to_string());
+        }
+
+        let sign = if *value < Duration::zero() { "-" } else { "" };
+
+        let magnitude = value.abs();
+        let (mut seconds, nanos) = duration_to_seconds(&magnitude);
+
+        let days = seconds / 86_400;
+        seconds %= 86_400;
+        let hours = seconds / 3_600;
+        seconds %= 3_600;
+        let minutes = seconds / 60;
+        seconds %= 60;
+
+        let mut text = format!("{sign}P");
+
+        if days > 0 {
+            text.push_str(&format!("{days}D"));
+        }
+
+        if hours > 0 || minutes > 0 || seconds > 0 || nanos > 0 {
+            text.push('T');
+
+            if hours > 0 {
+                text.push_str(&format!("{hours}H"));
+            }
+
+            if minutes > 0 {
+                text.push_str(&format!("{minutes}M"));
+            }
+
+            if nanos > 0 {
+                let fraction = format!("{nanos:09}");
+                text.push_str(&format!("{seconds}.{}S", fraction.trim_end_matches('0')));
+            } else if seconds > 0 {
+                text.push_str(&format!("{seconds}S"));
+            }
+        }
+
+        Ok(text)
+    }
+
+    fn decode(text: &str) -> Option<Duration> {
+        let (negative, text) = match text.strip_prefix('-') {
+            Some(text) => (true, text),
+            None => (false, text.strip_prefix('+').unwrap_or(text)),
+        };
+
+        let text = text.strip_prefix('P')?;
+
+        let (date, time) = match text.split_once('T') {
+            Some((_, "")) => return None,
+            Some((date, time)) => (date, Some(time)),
+            None => (date_or_empty(text)?, None),
+        };
+
+        let mut total = add_components(Duration::zero(), date, &[('W', 604_800), ('D', 86_400)])?;
+
+        if let Some(time) = time {
+            total = add_components(total, time, &[('H', 3_600), ('M', 60), ('S', 1)])?;
+        }
+
+        Some(if negative { -total } else { total })
+    }
+}
+
+string_adapter!(Iso8601, Duration);
+
+/// Rejects an ISO 8601 duration without any components, which is otherwise read as having an empty date part.
+fn date_or_empty(text: &str) -> Option<&str> {
+    (!text.is_empty()).then_some(text)
+}
+
+/// Splits the date or time part of an ISO 8601 duration into its numbers and their units.
+fn components(text: &str) -> Option<Vec<(&str, char)>> {
+    let mut components = Vec::new();
+    let mut start = 0;
+
+    for (index, c) in text.char_indices() {
+        if c.is_ascii_alphabetic() {
+            let number = &text[start..index];
+
+            if number.is_empty() {
+                return None;
+            }
+
+            components.push((number, c));
+            start = index + 1;
+        }
+    }
+
+    (start == text.len()).then_some(components)
+}
+
+/// Adds the components of the date or time part of an ISO 8601 duration to a total.
+///
+/// The part may only have the given units and their lengths in seconds, in the order that they are given, so a unit
+/// that is repeated or out of order is rejected.
+fn add_components(mut total: Duration, text: &str, units: &[(char, i64)]) -> Option<Duration> {
+    let mut units = units.iter();
+
+    for (number, unit) in components(text)? {
+        // Units are consumed as they are found, so only those after the previous one remain.
+        let (_, seconds) = units.find(|(expected, _)| *expected == unit)?;
+
+        total = total.checked_add(&scale(number, *seconds)?)?;
+    }
+
+    Some(total)
+}
+
+/// Converts a number of units, which may have a fraction, into a duration.
+fn scale(number: &str, unit_seconds: i64) -> Option<Duration> {
+    let number = number.replace(',', ".");
+
+    let (whole, fraction) = number.split_once('.').unwrap_or((&number, ""));
+
+    if whole.is_empty() || !whole.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    if !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let whole = Duration::try_seconds(whole.parse::<i64>().ok()?.checked_mul(unit_seconds)?)?;
+
+    let fraction = if fraction.is_empty() {
+        Duration::zero()
+    } else {
+        // Nanoseconds are the finest precision of a duration, so any further digits are dropped.
+        let digits = &fraction[..fraction.len().min(9)];
+        let nanos = digits.parse::<i64>().ok()? * 10i64.pow(9 - digits.len() as u32);
+
+        Duration::nanoseconds(nanos.checked_mul(unit_seconds)?)
+    };
+
+    whole.checked_add(&fraction)
+}
+
+/// Bytes as a base64 string, with padding. Padding is optional when decoding.
+pub struct Base64;
+
+/// Bytes as a URL-safe base64 string, without padding. Padding is optional when decoding.
+pub struct Base64Url;
+
+const INDIFFERENT_PADDING: base64::engine::GeneralPurposeConfig =
+    base64::engine::GeneralPurposeConfig::new()
+        .with_encode_padding(false)
+        .with_decode_padding_mode(base64::engine::DecodePaddingMode::Indifferent);
+
+impl TextEncoding<Vec<u8>> for Base64 {
+    const NAME: &'static str = "base64";
+
+    fn encode(value: &Vec<u8>) -> Result<String, EncodeError> {
+        Ok(base64::engine::general_purpose::STANDARD.encode(value))
+    }
+
+    fn decode(text: &str) -> Option<Vec<u8>> {
+        base64::engine::GeneralPurpose::new(&base64::alphabet::STANDARD, INDIFFERENT_PADDING)
+            .decode(text)
+            .ok()
+    }
+}
+
+string_adapter!(Base64, Vec<u8>);
+
+impl TextEncoding<Vec<u8>> for Base64Url {
+    const NAME: &'static str = "base64url";
+
+    fn encode(value: &Vec<u8>) -> Result<String, EncodeError> {
+        Ok(base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(value))
+    }
+
+    fn decode(text: &str) -> Option<Vec<u8>> {
+        base64::engine::GeneralPurpose::new(&base64::alphabet::URL_SAFE, INDIFFERENT_PADDING)
+            .decode(text)
+            .ok()
+    }
+}
+
+string_adapter!(Base64Url, Vec<u8>);
+
+/// An arbitrary-precision integer as a JSON number if it is a safe integer, within ±[`MAX_SAFE_INTEGER`], and as a
+/// string of its decimal digits otherwise, as JSON parsers that read numbers as doubles round larger numbers.
+///
+/// Both forms are accepted when deserializing, but numbers with a fraction or an exponent are rejected, as they have
+/// already been rounded by the time they are read.
+pub struct BigInteger;
+
+impl TextEncoding<BigInt> for BigInteger {
+    const NAME: &'static str = "integer";
+
+    fn encode(value: &BigInt) -> Result<String, EncodeError> {
+        Ok(value.to_string())
+    }
+
+    fn decode(text: &str) -> Option<BigInt> {
+        text.parse().ok()
+    }
+}
+
+impl SerializeAs<BigInt> for BigInteger {
+    fn serialize_as<S: Serializer>(value: &BigInt, serializer: S) -> Result<S::Ok, S::Error> {
+        match i64::try_from(value) {
+            Ok(value) if (-MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER).contains(&value) => {
+                serializer.serialize_i64(value)
+            }
+            _ => serializer.collect_str(value),
+        }
+    }
+}
+
+impl<'de> DeserializeAs<'de, BigInt> for BigInteger {
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<BigInt, D::Error> {
+        struct BigIntegerVisitor;
+
+        impl serde::de::Visitor<'_> for BigIntegerVisitor {
+            type Value = BigInt;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("an integer, or a string of its decimal digits")
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, value: i64) -> Result<Self::Value, E> {
+                Ok(value.into())
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<Self::Value, E> {
+                Ok(value.into())
+            }
+
+            fn visit_i128<E: serde::de::Error>(self, value: i128) -> Result<Self::Value, E> {
+                Ok(value.into())
+            }
+
+            fn visit_u128<E: serde::de::Error>(self, value: u128) -> Result<Self::Value, E> {
+                Ok(value.into())
+            }
+
+            fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                decode::<BigInteger, _>(value).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(BigIntegerVisitor)
+    }
+}
+
+impl TextEncoding<Integer> for BigInteger {
+    const NAME: &'static str = "integer";
+
+    fn encode(value: &Integer) -> Result<String, EncodeError> {
+        Ok(value.to_string())
+    }
+
+    fn decode(text: &str) -> Option<Integer> {
+        text.parse().ok()
+    }
+}
+
+/// An arbitrary-precision `integer`, which is serialized like [`BigInteger`] serializes a `BigInt`.
+///
+/// Generated code holds integers as this type rather than as a `BigInt`, whose own serialization does not match
+/// TypeSpec, so that they are serialized the same way in arrays, records, unions, merge patches, and bodies.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Integer(pub BigInt);
+
+impl Integer {
+    pub fn into_inner(self) -> BigInt {
+        self.0
+    }
+}
+
+impl Deref for Integer {
+    type Target = BigInt;
+
+    fn deref(&self) -> &BigInt {
+        &self.0
+    }
+}
+
+impl DerefMut for Integer {
+    fn deref_mut(&mut self) -> &mut BigInt {
+        &mut self.0
+    }
+}
+
+impl From<BigInt> for Integer {
+    fn from(value: BigInt) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Integer> for BigInt {
+    fn from(value: Integer) -> Self {
+        value.0
+    }
+}
+
+macro_rules! integer_from {
+    ($($integer:ty),*) => {
+        $(
+            impl From<$integer> for Integer {
+                fn from(value: $integer) -> Self {
+                    Self(value.into())
+                }
+            }
+        )*
+    };
+}
+
+integer_from!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
+);
+
+impl Display for Integer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for Integer {
+    type Err = num_bigint::ParseBigIntError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        text.parse().map(Self)
+    }
+}
+
+impl Serialize for Integer {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        BigInteger::serialize_as(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Integer {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        BigInteger::deserialize_as(deserializer).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_with::{de::DeserializeAsWrap, ser::SerializeAsWrap};
+
+    use super::*;
+
+    fn round_trip(value: BigInt, json: &str) {
+        let integer = Integer(value);
+
+        assert_eq!(serde_json::to_string(&integer).unwrap(), json);
+        assert_eq!(serde_json::from_str::<Integer>(json).unwrap(), integer);
+    }
+
+    #[test]
+    fn big_integer_round_trips_safe_integers_as_numbers() {
+        round_trip(BigInt::from(0), "0");
+        round_trip(BigInt::from(MAX_SAFE_INTEGER), "9007199254740991");
+        round_trip(BigInt::from(-MAX_SAFE_INTEGER), "-9007199254740991");
+    }
+
+    #[test]
+    fn big_integer_round_trips_unsafe_integers_as_strings() {
+        round_trip(BigInt::from(MAX_SAFE_INTEGER + 1), r#""9007199254740992""#);
+        round_trip(
+            BigInt::from(-MAX_SAFE_INTEGER - 1),
+            r#""-9007199254740992""#,
+        );
+        round_trip(BigInt::from(u64::MAX), r#""18446744073709551615""#);
+        round_trip(
+            BigInt::from(2).pow(100),
+            r#""1267650600228229401496703205376""#,
+        );
+    }
+
+    #[test]
+    fn big_integer_accepts_unsafe_integers_as_numbers() {
+        assert_eq!(
+            serde_json::from_str::<Integer>("9007199254740993").unwrap(),
+            Integer(BigInt::from(MAX_SAFE_INTEGER + 2))
+        );
+        assert_eq!(
+            serde_json::from_str::<Integer>("18446744073709551615").unwrap(),
+            Integer(BigInt::from(u64::MAX))
+        );
+    }
+
+    /// Checks that a value is encoded as the given text, and as the given JSON by its `serde_with` adapter, and that
+    /// both are decoded back to the value.
+    fn round_trip_as<E, T>(value: T, text: &str, json: &str)
+    where
+        E: TextEncoding<T> + SerializeAs<T> + for<'de> DeserializeAs<'de, T>,
+        T: std::fmt::Debug + PartialEq,
+    {
+        assert_eq!(encode::<E, T>(&value).unwrap(), text);
+        assert_eq!(decode::<E, T>(text).unwrap(), value);
+
+        let serialized = serde_json::to_string(&SerializeAsWrap::<T, E>::new(&value)).unwrap();
+        assert_eq!(serialized, json);
+
+        let deserialized = serde_json::from_str::<DeserializeAsWrap<T, E>>(json).unwrap();
+        assert_eq!(deserialized.into_inner(), value);
+    }
+
+    /// Checks a value of an encoding that is serialized as a string.
+    fn round_trip_string<E, T>(value: T, text: &str)
+    where
+        E: TextEncoding<T> + SerializeAs<T> + for<'de> DeserializeAs<'de, T>,
+        T: std::fmt::Debug + PartialEq,
+    {
+        round_trip_as::<E, T>(value, text, &format!("{text:?}"));
+    }
+
+    /// Checks a value of an encoding that is serialized as a number.
+    fn round_trip_number<E, T>(value: T, text: &str)
+    where
+        E: TextEncoding<T> + SerializeAs<T> + for<'de> DeserializeAs<'de, T>,
+        T: std::fmt::Debug + PartialEq,
+    {
+        round_trip_as::<E, T>(value, text, text);
+    }
+
+    fn utc(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text).unwrap().to_utc()
+    }
+
+    #[test]
+    fn rfc3339_round_trips() {
+        round_trip_string::<Rfc3339, _>(utc("2024-01-02T03:04:05Z"), "2024-01-02T03:04:05Z");
+        round_trip_string::<Rfc3339, _>(utc("2024-01-02T03:04:05.25Z"), "2024-01-02T03:04:05.250Z");
+        round_trip_string::<Rfc3339, _>(
+            DateTime::parse_from_rfc3339("2024-01-02T05:04:05+02:00").unwrap(),
+            "2024-01-02T05:04:05+02:00",
+        );
+        round_trip_string::<Rfc3339, _>(NaiveDate::from_ymd_opt(2024, 1, 2).unwrap(), "2024-01-02");
+        round_trip_string::<Rfc3339, _>(
+            NaiveTime::from_hms_milli_opt(3, 4, 5, 250).unwrap(),
+            "03:04:05.250",
+        );
+    }
+
+    #[test]
+    fn rfc7231_round_trips() {
+        round_trip_string::<Rfc7231, _>(
+            utc("2024-01-02T03:04:05Z"),
+            "Tue, 02 Jan 2024 03:04:05 GMT",
+        );
+        round_trip_string::<Rfc7231, _>(
+            DateTime::parse_from_rfc3339("2024-01-02T03:04:05+00:00").unwrap(),
+            "Tue, 02 Jan 2024 03:04:05 GMT",
+        );
+        round_trip_string::<Rfc7231, _>(
+            NaiveDate::from_ymd_opt(2024, 1, 2).unwrap(),
+            "Tue, 02 Jan 2024",
+        );
+        round_trip_string::<Rfc7231, _>(NaiveTime::from_hms_opt(3, 4, 5).unwrap(), "03:04:05");
+    }
+
+    #[test]
+    fn rfc7231_converts_offset_date_times_to_gmt() {
+        let value = DateTime::parse_from_rfc3339("2024-01-02T05:04:05+02:00").unwrap();
+
+        assert_eq!(
+            encode::<Rfc7231, _>(&value).unwrap(),
+            "Tue, 02 Jan 2024 03:04:05 GMT"
+        );
+    }
+
+    #[test]
+    fn unix_timestamp_round_trips() {
+        round_trip_number::<UnixTimestamp<i64>, _>(utc("2024-01-02T03:04:05Z"), "1704164645");
+        round_trip_number::<UnixTimestamp<i32>, _>(utc("1969-12-31T23:59:59Z"), "-1");
+        round_trip_number::<UnixTimestamp<f64>, _>(utc("2024-01-02T03:04:05.5Z"), "1704164645.5");
+    }
+
+    #[test]
+    fn seconds_round_trip() {
+        round_trip_number::<Seconds<i32>, _>(Duration::seconds(90), "90");
+        round_trip_number::<Seconds<i64>, _>(Duration::seconds(-90), "-90");
+        round_trip_number::<Seconds<f64>, _>(Duration::milliseconds(1500), "1.5");
+        round_trip_number::<Seconds<f64>, _>(Duration::milliseconds(-1500), "-1.5");
+    }
+
+    #[test]
+    fn iso8601_round_trips() {
+        round_trip_string::<Iso8601, _>(Duration::zero(), "PT0S");
+        round_trip_string::<Iso8601, _>(
+            Duration::days(1) + Duration::hours(2) + Duration::minutes(30),
+            "P1DT2H30M",
+        );
+        round_trip_string::<Iso8601, _>(Duration::milliseconds(500), "PT0.5S");
+        round_trip_string::<Iso8601, _>(-Duration::minutes(1), "-PT1M");
+        round_trip_string::<Iso8601, _>(Duration::days(3), "P3D");
+    }
+
+    #[test]
+    fn iso8601_accepts_weeks_and_fractions() {
+        assert_eq!(
+            decode::<Iso8601, Duration>("P1W2D").unwrap(),
+            Duration::days(9)
+        );
+        assert_eq!(
+            decode::<Iso8601, Duration>("PT1,5H").unwrap(),
+            Duration::minutes(90)
+        );
+        assert_eq!(
+            decode::<Iso8601, Duration>("+PT1M30S").unwrap(),
+            Duration::seconds(90)
+        );
+    }
+
+    #[test]
+    fn iso8601_rejects_repeated_and_out_of_order_units() {
+        for text in ["PT1H1H", "PT1M1H", "PT1S1M", "P1D1D", "P1D1W"] {
+            assert!(decode::<Iso8601, Duration>(text).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn iso8601_rejects_malformed_durations() {
+        for text in [
+            "", "P", "PT", "1D", "P1H", "PT1D", "P1Y", "PT1", "PTH", "PT-1S", "PT.5S",
+        ] {
+            assert!(decode::<Iso8601, Duration>(text).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn base64_round_trips() {
+        round_trip_string::<Base64, _>(vec![0xfb, 0xff], "+/8=");
+        round_trip_string::<Base64, _>(Vec::new(), "");
+
+        assert_eq!(decode::<Base64, Vec<u8>>("+/8").unwrap(), [0xfb, 0xff]);
+    }
+
+    #[test]
+    fn base64url_round_trips() {
+        round_trip_string::<Base64Url, _>(vec![0xfb, 0xff], "-_8");
+
+        assert_eq!(decode::<Base64Url, Vec<u8>>("-_8=").unwrap(), [0xfb, 0xff]);
+        assert!(decode::<Base64Url, Vec<u8>>("+/8=").is_err());
+    }
+
+    #[test]
+    fn number_encodings_reject_values_out_of_range() {
+        let date = DateTime::from_timestamp(i64::from(i32::MAX) + 1, 0).unwrap();
+
+        assert_eq!(
+            encode::<UnixTimestamp<i32>, _>(&date),
+            Err(EncodeError {
+                encoding: "unixTimestamp"
+            })
+        );
+        assert_eq!(
+            encode::<UnixTimestamp<i64>, _>(&date).unwrap(),
+            "2147483648"
+        );
+
+        #[serde_with::serde_as]
+        #[derive(Serialize)]
+        struct Timestamp(#[serde_as(as = "UnixTimestamp<i32>")] DateTime<Utc>);
+
+        let error = serde_json::to_string(&Timestamp(date)).unwrap_err();
+        assert_eq!(error.to_string(), "value is out of range for unixTimestamp");
+    }
+
+    /// A model with integers in nested positions, as it is generated.
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Ledger {
+        costs: Vec<Integer>,
+        totals: std::collections::HashMap<String, Integer>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        refund: Option<Integer>,
+    }
+
+    #[test]
+    fn integers_are_serialized_in_nested_positions() {
+        let ledger = Ledger {
+            costs: vec![Integer::from(125), Integer::from(u64::MAX)],
+            totals: [("march".to_string(), Integer::from(-7))].into(),
+            refund: Some(Integer::from(MAX_SAFE_INTEGER + 1)),
+        };
+
+        let json = r#"{"costs":[125,"18446744073709551615"],"totals":{"march":-7},"refund":"9007199254740992"}"#;
+
+        assert_eq!(serde_json::to_string(&ledger).unwrap(), json);
+        assert_eq!(serde_json::from_str::<Ledger>(json).unwrap(), ledger);
+    }
+
+    #[test]
+    fn integers_are_encoded_as_their_digits() {
+        let value = Integer::from(BigInt::from(2).pow(70));
+
+        assert_eq!(
+            encode::<BigInteger, Integer>(&value).unwrap(),
+            "1180591620717411303424"
+        );
+        assert_eq!(
+            decode::<BigInteger, Integer>("1180591620717411303424").unwrap(),
+            value
+        );
+        assert!(decode::<BigInteger, Integer>("1.5").is_err());
+    }
+
+    #[test]
+    fn big_integer_rejects_rounded_numbers() {
+        assert!(serde_json::from_str::<Integer>("1.5").is_err());
+        assert!(serde_json::from_str::<Integer>("1e3").is_err());
+        assert!(serde_json::from_str::<Integer>(r#""1e3""#).is_err());
+    }
+}
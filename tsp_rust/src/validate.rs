@@ -0,0 +1,566 @@
+//! Validation of values against the constraints declared on their TypeSpec definitions.
+//!
+//! Generated models implement [`Validate`] by checking the constraint decorators on each of their properties
+//! (`@minLength`, `@maxValue`, `@pattern`, etc.) and then validating the property values themselves.
+
+use std::{collections::HashMap, fmt::Display, sync::OnceLock};
+
+use regex::Regex;
+
+/// The largest integer that a double represents exactly, and so the bound of the values of a `safeint`.
+pub const MAX_SAFE_INTEGER: i64 = (1 << 53) - 1;
+
+/// A value that can be checked against the constraints of its TypeSpec definition.
+pub trait Validate {
+    /// Records every constraint violation in this value with the given validator.
+    fn validate_into(&self, validator: &mut Validator);
+
+    /// Checks this value, returning every constraint violation it contains.
+    fn validate(&self) -> Result<(), ValidationError> {
+        let mut validator = Validator::new();
+
+        self.validate_into(&mut validator);
+
+        validator.finish()
+    }
+}
+
+/// A single constraint violation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// The JSON path of the invalid value, relative to the validated value (e.g. `$.pets[0].name`).
+    pub path: String,
+    pub message: String,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// The constraint violations found while validating a value.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("validation failed: {}", itertools::join(.violations, "; "))]
+pub struct ValidationError {
+    pub violations: Vec<Violation>,
+}
+
+/// Collects constraint violations while tracking the JSON path of the value being validated.
+#[derive(Debug)]
+pub struct Validator {
+    path: String,
+    violations: Vec<Violation>,
+}
+
+impl Default for Validator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Self {
+            path: "$".to_string(),
+            violations: Vec::new(),
+        }
+    }
+
+    /// Returns the violations recorded so far as an error, if there are any.
+    pub fn finish(self) -> Result<(), ValidationError> {
+        if self.violations.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError {
+                violations: self.violations,
+            })
+        }
+    }
+
+    /// Validates a property of the current value.
+    pub fn field(&mut self, name: &str, f: impl FnOnce(&mut Self)) {
+        let is_identifier = name.chars().next().is_some_and(|c| !c.is_ascii_digit())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+        if is_identifier {
+            self.enter(&format!(".{}", name), f);
+        } else {
+            self.enter(&format!("[{}]", serde_json::Value::from(name)), f);
+        }
+    }
+
+    /// Validates an element of the current value.
+    pub fn index(&mut self, index: usize, f: impl FnOnce(&mut Self)) {
+        self.enter(&format!("[{}]", index), f);
+    }
+
+    /// Validates an entry of the current value.
+    pub fn key(&mut self, key: &str, f: impl FnOnce(&mut Self)) {
+        self.enter(&format!("[{}]", serde_json::Value::from(key)), f);
+    }
+
+    fn enter(&mut self, segment: &str, f: impl FnOnce(&mut Self)) {
+        let len = self.path.len();
+
+        self.path.push_str(segment);
+        f(self);
+        self.path.truncate(len);
+    }
+
+    /// Records a violation at the current path.
+    pub fn violation(&mut self, message: impl Into<String>) {
+        self.violations.push(Violation {
+            path: self.path.clone(),
+            message: message.into(),
+        });
+    }
+
+    pub fn min_length(&mut self, value: &str, min: usize) {
+        if value.chars().count() < min {
+            self.violation(format!("length must be at least {}", min));
+        }
+    }
+
+    pub fn max_length(&mut self, value: &str, max: usize) {
+        if value.chars().count() > max {
+            self.violation(format!("length must be at most {}", max));
+        }
+    }
+
+    pub fn min_value<T: PartialOrd + Display>(&mut self, value: &T, min: T) {
+        if *value < min {
+            self.violation(format!("must be at least {}", min));
+        }
+    }
+
+    pub fn max_value<T: PartialOrd + Display>(&mut self, value: &T, max: T) {
+        if *value > max {
+            self.violation(format!("must be at most {}", max));
+        }
+    }
+
+    pub fn min_value_exclusive<T: PartialOrd + Display>(&mut self, value: &T, min: T) {
+        if *value <= min {
+            self.violation(format!("must be greater than {}", min));
+        }
+    }
+
+    pub fn max_value_exclusive<T: PartialOrd + Display>(&mut self, value: &T, max: T) {
+        if *value >= max {
+            self.violation(format!("must be less than {}", max));
+        }
+    }
+
+    /// Checks that the value is a `safeint`, which JSON parsers that read numbers as doubles represent exactly.
+    pub fn safe_integer(&mut self, value: &i64) {
+        if !(-MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER).contains(value) {
+            self.violation(format!(
+                "must be between {} and {}",
+                -MAX_SAFE_INTEGER, MAX_SAFE_INTEGER
+            ));
+        }
+    }
+
+    pub fn min_items(&mut self, len: usize, min: usize) {
+        if len < min {
+            self.violation(format!("must contain at least {} items", min));
+        }
+    }
+
+    pub fn max_items(&mut self, len: usize, max: usize) {
+        if len > max {
+            self.violation(format!("must contain at most {} items", max));
+        }
+    }
+
+    /// Checks that the value contains a match for the given regular expression.
+    pub fn pattern(&mut self, value: &str, pattern: &Pattern) {
+        if !pattern.is_match(value) {
+            self.violation(format!("must match the pattern '{}'", pattern.source));
+        }
+    }
+
+    /// Checks that the value is in the given well-known format.
+    ///
+    /// Formats that are not recognized are treated as annotations and are not checked.
+    pub fn format(&mut self, value: &str, format: &str) {
+        let is_valid = match format {
+            "date-time" => chrono::DateTime::parse_from_rfc3339(value).is_ok(),
+            "date" => chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok(),
+            "time" => chrono::NaiveTime::parse_from_str(value, "%H:%M:%S%.f").is_ok(),
+            "email" => value
+                .split_once('@')
+                .is_some_and(|(local, domain)| !local.is_empty() && domain.contains('.')),
+            "uri" | "url" => url::Url::parse(value).is_ok(),
+            "uuid" => {
+                let parts = value.split('-').map(str::len).collect::<Vec<_>>();
+
+                parts == [8, 4, 4, 4, 12]
+                    && value.chars().all(|c| c == '-' || c.is_ascii_hexdigit())
+            }
+            "ipv4" => value.parse::<std::net::Ipv4Addr>().is_ok(),
+            "ipv6" => value.parse::<std::net::Ipv6Addr>().is_ok(),
+            _ => true,
+        };
+
+        if !is_valid {
+            self.violation(format!("must be a valid '{}'", format));
+        }
+    }
+}
+
+/// A regular expression from a `@pattern` decorator, compiled the first time that it is used.
+///
+/// Generated code declares one `static` pattern for each `@pattern` constraint, so every pattern is compiled at most
+/// once without any shared lock. Patterns are checked by the emitter, so a pattern that cannot be compiled is logged as
+/// an error once, and then never matches.
+#[derive(Debug)]
+pub struct Pattern {
+    source: &'static str,
+    regex: OnceLock<Option<Regex>>,
+}
+
+impl Pattern {
+    pub const fn new(source: &'static str) -> Self {
+        Self {
+            source,
+            regex: OnceLock::new(),
+        }
+    }
+
+    /// Returns the regular expression that this pattern was declared with.
+    pub fn source(&self) -> &'static str {
+        self.source
+    }
+
+    /// Returns `true` if the value contains a match for this pattern.
+    pub fn is_match(&self, value: &str) -> bool {
+        self.regex
+            .get_or_init(|| {
+                Regex::new(self.source)
+                    .inspect_err(|e| log::error!("invalid pattern '{}': {}", self.source, e))
+                    .ok()
+            })
+            .as_ref()
+            .is_some_and(|regex| regex.is_match(value))
+    }
+}
+
+macro_rules! impl_validate_unconstrained {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Validate for $ty {
+                fn validate_into(&self, _: &mut Validator) {}
+            }
+        )*
+    };
+}
+
+impl_validate_unconstrained!(
+    (),
+    bool,
+    String,
+    str,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    f32,
+    f64,
+    bigdecimal::BigDecimal,
+    num_bigint::BigInt,
+    crate::serialize::Integer,
+    chrono::NaiveDate,
+    chrono::NaiveTime,
+    chrono::NaiveDateTime,
+    chrono::Duration,
+    serde_json::Value,
+);
+
+impl<Tz: chrono::TimeZone> Validate for chrono::DateTime<Tz> {
+    fn validate_into(&self, _: &mut Validator) {}
+}
+
+impl<T: Validate + ?Sized> Validate for &T {
+    fn validate_into(&self, validator: &mut Validator) {
+        (**self).validate_into(validator)
+    }
+}
+
+impl<T: Validate + ?Sized> Validate for Box<T> {
+    fn validate_into(&self, validator: &mut Validator) {
+        (**self).validate_into(validator)
+    }
+}
+
+impl<T: Validate> Validate for Option<T> {
+    fn validate_into(&self, validator: &mut Validator) {
+        if let Some(value) = self {
+            value.validate_into(validator)
+        }
+    }
+}
+
+impl<T: Validate> Validate for [T] {
+    fn validate_into(&self, validator: &mut Validator) {
+        for (index, value) in self.iter().enumerate() {
+            validator.index(index, |validator| value.validate_into(validator));
+        }
+    }
+}
+
+impl<T: Validate> Validate for Vec<T> {
+    fn validate_into(&self, validator: &mut Validator) {
+        self.as_slice().validate_into(validator)
+    }
+}
+
+impl<T: Validate> Validate for HashMap<String, T> {
+    fn validate_into(&self, validator: &mut Validator) {
+        for (key, value) in self {
+            validator.key(key, |validator| value.validate_into(validator));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Pet {
+        name: String,
+        tags: Vec<Tag>,
+        owners: HashMap<String, Tag>,
+    }
+
+    struct Tag {
+        label: String,
+    }
+
+    impl Validate for Tag {
+        fn validate_into(&self, validator: &mut Validator) {
+            validator.field("label", |validator| {
+                validator.min_length(&self.label, 1);
+            });
+        }
+    }
+
+    impl Validate for Pet {
+        fn validate_into(&self, validator: &mut Validator) {
+            validator.field("name", |validator| {
+                validator.max_length(&self.name, 3);
+            });
+            validator.field("tags", |validator| self.tags.validate_into(validator));
+            validator.field("owner-tags", |validator| {
+                self.owners.validate_into(validator)
+            });
+        }
+    }
+
+    fn paths(result: Result<(), ValidationError>) -> Vec<String> {
+        result
+            .unwrap_err()
+            .violations
+            .into_iter()
+            .map(|violation| violation.path)
+            .collect()
+    }
+
+    #[test]
+    fn valid_values_pass() {
+        let pet = Pet {
+            name: "Rex".to_string(),
+            tags: vec![Tag {
+                label: "good".to_string(),
+            }],
+            owners: HashMap::new(),
+        };
+
+        assert_eq!(pet.validate(), Ok(()));
+    }
+
+    #[test]
+    fn violations_are_reported_at_their_path() {
+        let pet = Pet {
+            name: "Fluffy".to_string(),
+            tags: vec![
+                Tag {
+                    label: "ok".to_string(),
+                },
+                Tag {
+                    label: String::new(),
+                },
+            ],
+            owners: HashMap::from([(
+                "o'brien".to_string(),
+                Tag {
+                    label: String::new(),
+                },
+            )]),
+        };
+
+        assert_eq!(
+            paths(pet.validate()),
+            [
+                "$.name",
+                "$.tags[1].label",
+                r#"$["owner-tags"]["o'brien"].label"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn lengths_count_characters() {
+        let mut validator = Validator::new();
+        validator.min_length("ééé", 3);
+        validator.max_length("ééé", 3);
+        assert_eq!(validator.finish(), Ok(()));
+
+        let mut validator = Validator::new();
+        validator.min_length("éé", 3);
+        validator.max_length("éééé", 3);
+        let error = validator.finish().unwrap_err();
+        assert_eq!(
+            error.violations,
+            [
+                Violation {
+                    path: "$".to_string(),
+                    message: "length must be at least 3".to_string(),
+                },
+                Violation {
+                    path: "$".to_string(),
+                    message: "length must be at most 3".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn inclusive_bounds_accept_their_limits() {
+        let mut validator = Validator::new();
+        validator.min_value(&1, 1);
+        validator.max_value(&10, 10);
+        validator.min_items(2, 2);
+        validator.max_items(2, 2);
+        assert_eq!(validator.finish(), Ok(()));
+
+        let mut validator = Validator::new();
+        validator.min_value(&0, 1);
+        validator.max_value(&11, 10);
+        validator.min_items(1, 2);
+        validator.max_items(3, 2);
+        assert_eq!(validator.finish().unwrap_err().violations.len(), 4);
+    }
+
+    #[test]
+    fn exclusive_bounds_reject_their_limits() {
+        let mut validator = Validator::new();
+        validator.min_value_exclusive(&0.5, 0.0);
+        validator.max_value_exclusive(&0.5, 1.0);
+        assert_eq!(validator.finish(), Ok(()));
+
+        let mut validator = Validator::new();
+        validator.min_value_exclusive(&0.0, 0.0);
+        validator.max_value_exclusive(&1.0, 1.0);
+        let messages = validator
+            .finish()
+            .unwrap_err()
+            .violations
+            .into_iter()
+            .map(|violation| violation.message)
+            .collect::<Vec<_>>();
+        assert_eq!(messages, ["must be greater than 0", "must be less than 1"]);
+    }
+
+    #[test]
+    fn safe_integers_are_bounded() {
+        let mut validator = Validator::new();
+        validator.safe_integer(&MAX_SAFE_INTEGER);
+        validator.safe_integer(&-MAX_SAFE_INTEGER);
+        assert_eq!(validator.finish(), Ok(()));
+
+        let mut validator = Validator::new();
+        validator.safe_integer(&(MAX_SAFE_INTEGER + 1));
+        assert!(validator.finish().is_err());
+    }
+
+    #[test]
+    fn patterns_match_anywhere_unless_anchored() {
+        static CONTAINS_DIGIT: Pattern = Pattern::new("[0-9]");
+        static ALL_DIGITS: Pattern = Pattern::new("^[0-9]+$");
+
+        let mut validator = Validator::new();
+        validator.pattern("abc1", &CONTAINS_DIGIT);
+        validator.pattern("123", &ALL_DIGITS);
+        assert_eq!(validator.finish(), Ok(()));
+
+        let mut validator = Validator::new();
+        validator.pattern("abc", &CONTAINS_DIGIT);
+        validator.pattern("123a", &ALL_DIGITS);
+        let error = validator.finish().unwrap_err();
+        assert_eq!(
+            error.violations[1].message,
+            "must match the pattern '^[0-9]+$'"
+        );
+        assert_eq!(error.violations.len(), 2);
+    }
+
+    #[test]
+    fn invalid_patterns_never_match() {
+        static INVALID: Pattern = Pattern::new("(");
+
+        assert_eq!(INVALID.source(), "(");
+        assert!(!INVALID.is_match(""));
+        assert!(!INVALID.is_match("("));
+    }
+
+    #[test]
+    fn formats_are_checked() {
+        let valid = [
+            ("date-time", "2024-01-02T03:04:05Z"),
+            ("date", "2024-01-02"),
+            ("time", "03:04:05.5"),
+            ("email", "someone@example.com"),
+            ("uri", "https://example.com/a?b=c"),
+            ("uuid", "123e4567-e89b-12d3-a456-426614174000"),
+            ("ipv4", "127.0.0.1"),
+            ("ipv6", "::1"),
+            ("not-a-known-format", "anything"),
+        ];
+
+        for (format, value) in valid {
+            let mut validator = Validator::new();
+            validator.format(value, format);
+            assert_eq!(validator.finish(), Ok(()), "{format}: {value}");
+        }
+
+        let invalid = [
+            ("date-time", "2024-01-02"),
+            ("date", "2024-13-02"),
+            ("time", "25:00:00"),
+            ("email", "someone@localhost"),
+            ("uri", "not a uri"),
+            ("uuid", "123e4567-e89b-12d3-a456-42661417400g"),
+            ("ipv4", "256.0.0.1"),
+            ("ipv6", "127.0.0.1"),
+        ];
+
+        for (format, value) in invalid {
+            let mut validator = Validator::new();
+            validator.format(value, format);
+            assert_eq!(
+                validator.finish().unwrap_err().violations[0].message,
+                format!("must be a valid '{}'", format),
+            );
+        }
+    }
+}